@@ -9,41 +9,33 @@ pg_module_magic!();
 ///            AS 'MODULE_PATHNAME', 'trigger_example_wrapper';
 /// ```
 #[pg_extern]
-unsafe fn trigger_example(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
-    // we can only be called as a trigger
-    if !called_as_trigger(fcinfo) {
-        panic!("not called by trigger manager");
+fn trigger_example(trigger: &PgTrigger) -> Result<PgHeapTuple, PgHeapTupleError> {
+    // for this example, we're only going to operate as an ON BEFORE INSERT FOR EACH ROW trigger
+    if trigger.when() != PgTriggerWhen::Before
+        || trigger.op() != PgTriggerOperation::Insert
+        || trigger.level() != PgTriggerLevel::Row
+    {
+        panic!("not fired in the ON BEFORE INSERT context");
     }
 
-    let trigdata: PgBox<pg_sys::TriggerData> = PgBox::from_pg(
-        fcinfo.as_ref().expect("fcinfo is NULL").context as *mut pg_sys::TriggerData,
-    );
-
-    // and for this example, we're only going to operate as an ON BEFORE INSERT FOR EACH ROW trigger
-    if trigger_fired_before(trigdata.tg_event)
-        && trigger_fired_by_insert(trigdata.tg_event)
-        && trigger_fired_for_row(trigdata.tg_event)
-    {
-        let tupdesc = PgTupleDesc::from_pg_copy(trigdata.tg_relation.as_ref().unwrap().rd_att);
-        let tuple = PgBox::<pg_sys::HeapTupleData>::from_pg(trigdata.tg_trigtuple);
-        let id = heap_getattr::<i64, AllocatedByPostgres>(&tuple, 1, &tupdesc);
-        let title = heap_getattr::<&str, AllocatedByPostgres>(&tuple, 2, &tupdesc);
-        let description = heap_getattr::<&str, AllocatedByPostgres>(&tuple, 3, &tupdesc);
-        let payload = heap_getattr::<JsonB, AllocatedByPostgres>(&tuple, 4, &tupdesc);
+    let new = trigger
+        .new()
+        .expect("a row-level INSERT trigger has a new row");
+    let id = new.get_by_name::<i64>("id")?;
+    let title = new.get_by_name::<String>("title")?;
+    let description = new.get_by_name::<String>("description")?;
+    let payload = new.get_by_name::<JsonB>("payload")?;
 
-        warning!(
-            "id={:?}, title={:?}, description={:?}, payload={:?}",
-            id,
-            title,
-            description,
-            payload
-        );
+    warning!(
+        "id={:?}, title={:?}, description={:?}, payload={:?}",
+        id,
+        title,
+        description,
+        payload
+    );
 
-        // return the inserting tuple, unchanged
-        trigdata.tg_trigtuple as pg_sys::Datum
-    } else {
-        panic!("not fired in the ON BEFORE INSERT context");
-    }
+    // return the inserting tuple, unchanged
+    Ok(new)
 }
 
 extension_sql!(
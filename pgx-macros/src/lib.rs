@@ -9,7 +9,8 @@ use operators::{impl_postgres_eq, impl_postgres_hash, impl_postgres_ord};
 
 use pgx_utils::{
     sql_entity_graph::{
-        ExtensionSql, ExtensionSqlFile, PgAggregate, PgExtern, PostgresEnum, PostgresType, Schema,
+        ExtensionSql, ExtensionSqlFile, PgAggregate, PgExtern, PostgresComposite, PostgresEnum,
+        PostgresRange, PostgresType, Schema,
     },
     *,
 };
@@ -164,6 +165,48 @@ pub fn merges(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/**
+Associated macro for a `#[pg_extern]` trigger function.  Declares a trigger that executes the
+function, which `cargo pgx schema` creates with `CREATE TRIGGER` after the function.
+
+```rust,ignore
+use pgx::*;
+
+#[pg_extern(requires = ["create_orders_table"])]
+#[pg_trigger(
+    table = "orders",
+    after,
+    events = [insert],
+    for_each = statement,
+    referencing(new_table = "new_orders"),
+)]
+fn audit_orders(trigger: &PgTrigger) {
+    for order in trigger.new_table().unwrap() {
+        notice!("{:?}", order.get_by_name::<i64>("id"));
+    }
+}
+```
+
+The table must exist before the trigger is created, so a table created with
+[`macro@extension_sql`] should be named in the function's `requires`.  `#[pg_trigger]` may be
+repeated to declare several triggers.  It accepts:
+
+* `table = "name"`: The table, or view, the trigger is on, optionally schema-qualified.  Required.
+* One of `before`, `after`, or `instead_of`.  Required.
+* `events = [insert, update, delete, truncate]`: The events that fire the trigger.  Required.
+* `for_each = row` or `for_each = statement`: Defaults to `statement`, like `CREATE TRIGGER`.
+* `referencing(new_table = "name", old_table = "name")`: The transition tables of an `after`
+  trigger with a single event, which are read with `PgTrigger::new_table()` and
+  `PgTrigger::old_table()`.
+* `name = "name"`: The trigger's name, which defaults to the function's name.
+* `when = "condition"`: The trigger's `WHEN` condition.
+* `args = ["a", "b"]`: Arguments for the trigger function, read with `PgTrigger::args()`.
+*/
+#[proc_macro_attribute]
+pub fn pg_trigger(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
 /**
 Declare a Rust module and its contents to be in a schema.
 
@@ -400,12 +443,26 @@ Optionally accepts the following attributes:
 * `immutable`: Corresponds to [`IMMUTABLE`](https://www.postgresql.org/docs/current/sql-createfunction.html).
 * `strict`: Corresponds to [`STRICT`](https://www.postgresql.org/docs/current/sql-createfunction.html).
   + In most cases, `#[pg_extern]` can detect when no `Option<T>`s are used, and automatically set this.
+* `called_on_null_input`: Corresponds to [`CALLED ON NULL INPUT`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+  + Keeps `#[pg_extern]` from automatically setting `strict`.
 * `stable`: Corresponds to [`STABLE`](https://www.postgresql.org/docs/current/sql-createfunction.html).
 * `volatile`: Corresponds to [`VOLATILE`](https://www.postgresql.org/docs/current/sql-createfunction.html).
 * `raw`: Corresponds to [`RAW`](https://www.postgresql.org/docs/current/sql-createfunction.html).
 * `parallel_safe`: Corresponds to [`PARALLEL SAFE`](https://www.postgresql.org/docs/current/sql-createfunction.html).
 * `parallel_unsafe`: Corresponds to [`PARALLEL UNSAFE`](https://www.postgresql.org/docs/current/sql-createfunction.html).
 * `parallel_restricted`: Corresponds to [`PARALLEL RESTRICTED`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+* `security_definer`: Corresponds to [`SECURITY DEFINER`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+* `security_invoker`: Corresponds to [`SECURITY INVOKER`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+* `leakproof`: Corresponds to [`LEAKPROOF`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+  + The function must also be `immutable` or `stable`.
+* `rows = 100`: Corresponds to [`ROWS`](https://www.postgresql.org/docs/current/sql-createfunction.html), for set-returning functions.
+* `set(work_mem = "64MB", search_path = ["public", "pg_temp"])`: Corresponds to [`SET`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+  + A value is quoted as a single literal, and a list of values, like `search_path` above, as one literal each.
+* `window`: Corresponds to [`WINDOW`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+  + The function must take a `pgx::WindowObject` argument, which isn't part of its SQL signature.
+  + A `NULL` argument that isn't an `Option<T>` makes the function return `NULL` for that row.
+* `support`: Corresponds to [`SUPPORT`](https://www.postgresql.org/docs/current/xfunc-optimization.html).
+  + Either the path of a `#[pg_extern]` taking a `pgx::SupportRequest`, or the SQL name of an existing function as a string.
 * `no_guard`: Do not use `#[pg_guard]` with the function.
 * `sql`: Same arguments as [`#[pgx(sql = ..)]`](macro@pgx).
 
@@ -507,6 +564,43 @@ It accepts 2 arguments:
 * A name, such as `example`
 * A type
 
+Functions can also return a `Result<T, E>`, where `E: Into<pgx::PgError>`. The function returns `T`
+as usual, and an `Err` is raised as a Postgres `ERROR` with the SQLSTATE, detail, and hint of its
+`PgError`. `Box<dyn std::error::Error>` is also accepted:
+
+```rust,ignore
+use pgx::*;
+
+#[pg_extern]
+fn parse_int(input: &str) -> Result<i32, Box<dyn std::error::Error>> {
+    Ok(input.parse::<i32>()?)
+}
+```
+
+# Triggers
+
+A function taking a `pgx::PgTrigger`, or a reference to one, is a trigger function, declared as
+`RETURNS trigger`. It can't take any other arguments. It returns `()`, a `pgx::PgHeapTuple` to use
+as the row, or an `Option<PgHeapTuple>` which is `None` to skip the row:
+
+```rust,ignore
+use pgx::*;
+
+#[pg_extern]
+fn skip_negative(trigger: &PgTrigger) -> Option<PgHeapTuple> {
+    let new = trigger.new_row()?;
+    match new.get_by_name::<i32>("id").unwrap() {
+        Some(id) if id < 0 => None,
+        _ => Some(new),
+    }
+}
+```
+
+The trigger itself can be declared with [`macro@pg_trigger`].
+
+Likewise, a function taking a `pgx::PgEventTrigger` is an event trigger function, declared as
+`RETURNS event_trigger`, which is usually declared with [`macro@pg_event_trigger`] instead.
+
 # Special Cases
 
 `pg_sys::Oid` is a special cased type alias, in order to use it as an argument or return it must be
@@ -531,7 +625,10 @@ fn example_return() -> pg_sys::Oid {
 pub fn pg_extern(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_extern_attributes(proc_macro2::TokenStream::from(attr.clone()));
 
-    let sql_graph_entity_item = PgExtern::new(attr.clone().into(), item.clone().into()).unwrap();
+    let sql_graph_entity_item = match PgExtern::new(attr.clone().into(), item.clone().into()) {
+        Ok(pg_extern) => pg_extern,
+        Err(e) => return e.to_compile_error().into(),
+    };
 
     let ast = parse_macro_input!(item as syn::Item);
     match ast {
@@ -588,6 +685,108 @@ fn rewrite_item_fn(
     }
 }
 
+/**
+Declare a function as `#[pg_procedure]` to expose it to Postgres as a procedure, which is
+created with `CREATE PROCEDURE` and run with `CALL`.  Procedures require Postgres 11 or later.
+
+A procedure can't return a value, and only accepts the `schema`, `name`, `requires` and `sql`
+options of [`macro@pg_extern`].
+
+When it's `CALL`ed outside of an explicit transaction block, the procedure's SPI connection is
+non-atomic, so it may use `Spi::commit()` and `Spi::rollback()`:
+
+```rust,ignore
+use pgx::*;
+#[pg_procedure]
+fn insert_in_batches(batches: i32) {
+    for i in 0..batches {
+        Spi::execute(|client| {
+            client.update("INSERT INTO batches VALUES ($1)", None, (i,));
+            Spi::commit();
+        });
+    }
+}
+```
+
+Every `SpiTupleTable` and `SpiCursor` obtained before a commit or rollback is freed by it, and
+panics if it's used afterwards.
+*/
+#[proc_macro_attribute]
+pub fn pg_procedure(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_extern_attributes(proc_macro2::TokenStream::from(attr.clone()));
+
+    let sql_graph_entity_item =
+        match PgExtern::new_procedure(attr.clone().into(), item.clone().into()) {
+            Ok(procedure) => procedure,
+            Err(e) => return e.to_compile_error().into(),
+        };
+
+    let ast = parse_macro_input!(item as syn::Item);
+    match ast {
+        Item::Fn(mut func) => {
+            // the procedure needs its own FunctionCallInfo to know whether it may commit
+            func.sig.inputs.push(syn::parse_quote! {
+                __pgx_procedure_fcinfo: pgx::pg_sys::FunctionCallInfo
+            });
+            func.block.stmts.insert(
+                0,
+                syn::parse_quote! {
+                    let _procedure_call = unsafe { pgx::ProcedureCall::enter(__pgx_procedure_fcinfo) };
+                },
+            );
+            rewrite_item_fn(func, args, &sql_graph_entity_item).into()
+        }
+        _ => panic!("#[pg_procedure] can only be applied to top-level functions"),
+    }
+}
+
+/**
+Declare a function as `#[pg_event_trigger]` to expose it to Postgres as an event trigger function,
+declared as `RETURNS event_trigger`, along with the event trigger that executes it, which
+`cargo pgx schema` creates with `CREATE EVENT TRIGGER` after the function.
+
+The function may take a `pgx::PgEventTrigger`, or a reference to one, describing the event, and
+can't take any other arguments or return a value other than `()` or a `Result<(), E>`:
+
+```rust,ignore
+use pgx::*;
+
+#[pg_event_trigger(event = "sql_drop", tags = ["DROP TABLE"])]
+fn log_dropped_tables(trigger: &PgEventTrigger) {
+    for object in trigger.dropped_objects().filter(|object| object.original) {
+        log!("dropped {} {}", object.object_type, object.object_identity);
+    }
+}
+```
+
+The event trigger is named like the function, and fires for every database's DDL, so it should be
+careful about what it assumes exists.  Creating it requires a superuser.  It accepts:
+
+* `event = "name"`: `ddl_command_start`, `ddl_command_end`, `sql_drop`, or `table_rewrite`.
+  Required.
+* `tags = ["CREATE TABLE", "DROP TABLE"]`: The command tags the event trigger fires for.  It fires
+  for every command if omitted.
+
+As well as the `schema`, `name`, `requires`, `error`, `sql`, `no_guard`, `security_definer`,
+`security_invoker` and `set` options of [`macro@pg_extern`], for its function.
+*/
+#[proc_macro_attribute]
+pub fn pg_event_trigger(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_extern_attributes(proc_macro2::TokenStream::from(attr.clone()));
+
+    let sql_graph_entity_item =
+        match PgExtern::new_event_trigger(attr.clone().into(), item.clone().into()) {
+            Ok(event_trigger) => event_trigger,
+            Err(e) => return e.to_compile_error().into(),
+        };
+
+    let ast = parse_macro_input!(item as syn::Item);
+    match ast {
+        Item::Fn(func) => rewrite_item_fn(func, args, &sql_graph_entity_item).into(),
+        _ => panic!("#[pg_event_trigger] can only be applied to top-level functions"),
+    }
+}
+
 /**
 Generate necessary bindings for using the enum with PostgreSQL.
 
@@ -670,6 +869,252 @@ fn impl_postgres_enum(ast: DeriveInput) -> proc_macro2::TokenStream {
     stream
 }
 
+/**
+Generate a Postgres composite type, whose attributes are the struct's named fields.
+
+Unlike [`macro@PostgresType`], values are real row values, so SQL can select individual attributes
+with `(value).field`.  Each field's type must itself be usable with `#[pg_extern]`, and fields
+that can be `NULL` must be declared as `Option<T>`.
+
+```rust,ignore
+# use pgx_pg_sys as pg_sys;
+use pgx::*;
+#[derive(Debug, PostgresComposite)]
+struct Dog {
+    name: String,
+    scritches: Option<i32>,
+}
+
+#[pg_extern]
+fn good_dogs() -> impl std::iter::Iterator<Item = Dog> {
+    vec![Dog { name: "Nami".into(), scritches: Some(10) }].into_iter()
+}
+```
+Optionally accepts the following attributes:
+
+* `sql`: Same arguments as [`#[pgx(sql = ..)]`](macro@pgx).
+*/
+#[proc_macro_derive(PostgresComposite, attributes(pgx))]
+pub fn postgres_composite(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    impl_postgres_composite(ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn impl_postgres_composite(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let type_name = name.to_string();
+    let sql_graph_entity_item = PostgresComposite::from_derive_input(ast.clone())?;
+
+    let mut from_datum = proc_macro2::TokenStream::new();
+    let mut into_datum = proc_macro2::TokenStream::new();
+    for (field_name, field_ty) in sql_graph_entity_item.fields() {
+        let field_string = field_name.to_string();
+        let is_optional = match field_ty {
+            syn::Type::Path(type_path) => type_path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident == "Option")
+                .unwrap_or(false),
+            _ => false,
+        };
+
+        let get_attribute = quote! {
+            tuple.get_by_name(#field_string).unwrap_or_else(|e| panic!("{}", e))
+        };
+        if is_optional {
+            from_datum.extend(quote! { #field_name: #get_attribute, });
+        } else {
+            from_datum.extend(quote! {
+                #field_name: #get_attribute.unwrap_or_else(|| {
+                    panic!("attribute `{}` of composite type `{}` is NULL", #field_string, #type_name)
+                }),
+            });
+        }
+        into_datum.extend(quote! {
+            tuple.set_by_name(#field_string, self.#field_name).unwrap_or_else(|e| panic!("{}", e));
+        });
+    }
+
+    let mut stream = quote! {
+        impl pgx::FromDatum for #name {
+            unsafe fn from_datum(datum: pgx::pg_sys::Datum, is_null: bool, _typoid: pgx::pg_sys::Oid) -> Option<#name> {
+                if is_null {
+                    None
+                } else if datum == 0 {
+                    panic!("{} datum is declared non-null but Datum is zero", #type_name)
+                } else {
+                    let tuple = pgx::PgHeapTuple::from_composite_datum(datum);
+                    Some(#name {
+                        #from_datum
+                    })
+                }
+            }
+        }
+
+        impl pgx::IntoDatum for #name {
+            fn into_datum(self) -> Option<pgx::pg_sys::Datum> {
+                let mut tuple = pgx::PgHeapTuple::new_composite_type(#type_name);
+                #into_datum
+                Some(tuple.into_composite_datum())
+            }
+
+            fn type_oid() -> pgx::pg_sys::Oid {
+                pgx::regtypein(#type_name)
+            }
+        }
+    };
+    sql_graph_entity_item.to_tokens(&mut stream);
+
+    Ok(stream)
+}
+
+/**
+Decode the rows of a `pgx::SpiTupleTable` into a struct, with `SpiTupleTable::rows()`.
+
+Each field is read from the column of the same name, or from the column at the field's position
+if there's no such column and the field isn't renamed.  The fields of a tuple struct are read by position.  Fields for columns
+that can be `NULL` must be declared as `Option<T>`.
+
+```rust,ignore
+use pgx::*;
+#[derive(FromSpiRow)]
+struct Dog {
+    name: String,
+    #[pgx(rename = "scritches_received")]
+    scritches: Option<i32>,
+}
+
+Spi::execute(|client| {
+    for dog in client.select("SELECT * FROM dogs", None, None).rows::<Dog>() {
+        let dog = dog.expect("failed to decode dog");
+    }
+});
+```
+
+Optionally accepts the following attributes on fields:
+
+* `#[pgx(rename = "column")]`: Read the field from the column named `column`, which must exist.
+*/
+#[proc_macro_derive(FromSpiRow, attributes(pgx))]
+pub fn from_spi_row(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    impl_from_spi_row(ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn impl_from_spi_row(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let fields = match &ast.data {
+        Data::Struct(data_struct) => &data_struct.fields,
+        Data::Enum(_) | Data::Union(_) => {
+            return Err(syn::Error::new(ast.ident.span(), "expected struct"))
+        }
+    };
+
+    let mut columns = proc_macro2::TokenStream::new();
+    let mut from_row = proc_macro2::TokenStream::new();
+    for (index, field) in fields.iter().enumerate() {
+        let position = index + 1;
+        let rename = from_spi_row_rename(field)?;
+        let column = match &field.ident {
+            Some(ident) => Some(rename.clone().unwrap_or_else(|| ident.to_string())),
+            None => None,
+        };
+        let column_name = column.clone().unwrap_or_else(|| position.to_string());
+        // a renamed field must be read from the column it names
+        let fallback = match rename {
+            Some(_) => quote! { None },
+            None => quote! { Some(#position) },
+        };
+        let column = match column {
+            Some(column) => quote! { Some(#column) },
+            None => quote! { None },
+        };
+
+        // an `Option<T>` field allows NULLs, and is read as a `T`
+        let (ty, is_optional) = match &field.ty {
+            syn::Type::Path(type_path) => match type_path.path.segments.last() {
+                Some(segment) if segment.ident == "Option" => match &segment.arguments {
+                    syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                        Some(syn::GenericArgument::Type(ty)) => (ty, true),
+                        _ => (&field.ty, false),
+                    },
+                    _ => (&field.ty, false),
+                },
+                _ => (&field.ty, false),
+            },
+            _ => (&field.ty, false),
+        };
+
+        columns.extend(quote! {
+            pgx::find_spi_column::<#ty>(tupdesc, #column, #fallback)?,
+        });
+
+        let value = if is_optional {
+            quote! { row.by_ordinal(columns[#index])?.value::<#ty>() }
+        } else {
+            quote! {
+                row.by_ordinal(columns[#index])?
+                    .value::<#ty>()
+                    .ok_or_else(|| pgx::SpiError::UnexpectedNull { column: #column_name.to_string() })?
+            }
+        };
+        match &field.ident {
+            Some(ident) => from_row.extend(quote! { #ident: #value, }),
+            None => {
+                let index = syn::Index::from(index);
+                from_row.extend(quote! { #index: #value, })
+            }
+        }
+    }
+
+    Ok(quote! {
+        impl #impl_generics pgx::FromSpiRow for #name #ty_generics #where_clause {
+            fn columns(tupdesc: &pgx::PgTupleDesc) -> std::result::Result<Vec<usize>, pgx::SpiError> {
+                Ok(vec![#columns])
+            }
+
+            fn from_spi_row(row: &pgx::SpiHeapTupleData, columns: &[usize]) -> std::result::Result<Self, pgx::SpiError> {
+                Ok(#name {
+                    #from_row
+                })
+            }
+        }
+    })
+}
+
+/// The column named by a field's `#[pgx(rename = "column")]` attribute, if it has one
+fn from_spi_row_rename(field: &syn::Field) -> syn::Result<Option<String>> {
+    let mut rename = None;
+    for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("pgx")) {
+        let nested = match attr.parse_meta()? {
+            syn::Meta::List(list) => list.nested,
+            other => {
+                return Err(syn::Error::new(
+                    other.span(),
+                    "expected `#[pgx(rename = \"...\")]`",
+                ))
+            }
+        };
+        for meta in nested {
+            match meta {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(column),
+                    ..
+                })) if path.is_ident("rename") => rename = Some(column.value()),
+                other => return Err(syn::Error::new(other.span(), "expected `rename = \"...\"`")),
+            }
+        }
+    }
+    Ok(rename)
+}
+
 /**
 Generate necessary bindings for using the type with PostgreSQL.
 
@@ -688,6 +1133,12 @@ Optionally accepts the following attributes:
 
 * `inoutfuncs(some_in_fn, some_out_fn)`: Define custom in/out functions for the type.
 * `pgvarlena_inoutfuncs(some_in_fn, some_out_fn)`: Define custom in/out functions for the `PgVarlena` of this type.
+* `#[pgx(sendrecv)]`: Also generate binary send/receive functions, which requires implementing `pgx::SendRecvFuncs`.
+* `#[pgx(fixed_size)]`: Store the type as-is, with an `INTERNALLENGTH` of its size, rather than as a varlena.  The type must be `Copy`, must not implement `serde::Serialize`/`Deserialize`, must not contain padding (its size must be the sum of its fields' sizes), and requires `#[inoutfuncs]`.
+* `#[pgx(passedbyvalue)]`: Like `fixed_size`, but also `PASSEDBYVALUE`, which avoids allocating.  The type must be 1, 2, 4, or 8 bytes.
+* `#[pgx(alignment = double)]`: The type's `ALIGNMENT`, one of `char`, `int2`, `int4`, or `double`.  Fixed-size types default to their Rust alignment.
+* `#[pgx(storage = main)]`: The type's `STORAGE`, one of `plain`, `external`, `extended`, or `main`.  Fixed-size types must use `plain`.
+* `#[pgx(typmod)]`: Also accept a type modifier, like `vector(3)`, which requires implementing `pgx::TypmodInOutFuncs`.
 * `sql`: Same arguments as [`#[pgx(sql = ..)]`](macro@pgx).
 */
 #[proc_macro_derive(
@@ -707,6 +1158,10 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
     let funcname_in = Ident::new(&format!("{}_in", name).to_lowercase(), name.span());
     let funcname_out = Ident::new(&format!("{}_out", name).to_lowercase(), name.span());
     let mut args = parse_postgres_type_args(&ast.attrs);
+    let sql_graph_entity_item = match PostgresType::from_derive_input(ast.clone()) {
+        Ok(sql_graph_entity_item) => sql_graph_entity_item,
+        Err(e) => return e.to_compile_error(),
+    };
     let mut stream = proc_macro2::TokenStream::new();
 
     // validate that we're only operating on a struct
@@ -730,6 +1185,79 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
         impl #generics pgx::PostgresType for #name #generics { }
     });
 
+    // fixed-size types are stored as-is, rather than in a varlena, so they get their own Datum conversions
+    let layout = sql_graph_entity_item.layout();
+    if layout.fixed_size {
+        if !args.contains(&PostgresTypeAttribute::InOutFuncs) {
+            panic!("#[pgx(fixed_size)] and #[pgx(passedbyvalue)] types require #[inoutfuncs]");
+        }
+
+        // Postgres copies, compares and hashes the type's bytes, which must not include padding
+        let field_types = match &ast.data {
+            Data::Struct(data) => data.fields.iter().map(|field| &field.ty).collect::<Vec<_>>(),
+            _ => unreachable!(),
+        };
+        stream.extend(quote! {
+            const _: () = assert!(
+                core::mem::size_of::<#name #generics>() == 0 #( + core::mem::size_of::<#field_types>() )*,
+                "#[pgx(fixed_size)] and #[pgx(passedbyvalue)] types must not contain padding"
+            );
+        });
+
+        let passed_by_value = layout.passed_by_value;
+        if passed_by_value {
+            stream.extend(quote! {
+                const _: () = assert!(
+                    matches!(core::mem::size_of::<#name #generics>(), 1 | 2 | 4 | 8)
+                        && core::mem::size_of::<#name #generics>() <= core::mem::size_of::<pgx::pg_sys::Datum>(),
+                    "#[pgx(passedbyvalue)] types must be 1, 2, 4, or 8 bytes and fit in a Datum"
+                );
+            });
+        }
+
+        stream.extend(quote! {
+            impl #generics pgx::FromDatum for #name #generics {
+                const NEEDS_TYPID: bool = false;
+
+                #[inline]
+                unsafe fn from_datum(datum: pgx::pg_sys::Datum, is_null: bool, _typoid: pgx::pg_sys::Oid) -> Option<Self> {
+                    if is_null {
+                        None
+                    } else {
+                        Some(pgx::fixed_size_from_datum::<Self>(datum, #passed_by_value))
+                    }
+                }
+            }
+
+            impl #generics pgx::IntoDatum for #name #generics {
+                #[inline]
+                fn into_datum(self) -> Option<pgx::pg_sys::Datum> {
+                    Some(pgx::fixed_size_into_datum(self, #passed_by_value))
+                }
+
+                fn type_oid() -> pgx::pg_sys::Oid {
+                    pgx::rust_regtypein::<Self>()
+                }
+            }
+        });
+    }
+
+    // `#[pgx(typmod)]` types have an _in function that also receives the declared typmod
+    let (in_typmod_args, in_body) = if sql_graph_entity_item.typmod_fns().is_some() {
+        (
+            quote! { , _typelem: pg_sys::Oid, typmod: i32 },
+            quote! {
+                let mut result = #name::input(input);
+                if typmod >= 0 {
+                    result.apply_typmod(typmod, false);
+                }
+                result
+            },
+        )
+    } else {
+        (quote! {}, quote! { #name::input(input) })
+    };
+
     // and if we don't have custom inout/funcs, we use the JsonInOutFuncs trait
     // which implements _in and _out #[pg_extern] functions that just return the type itself
     if args.contains(&PostgresTypeAttribute::Default) {
@@ -744,8 +1272,8 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
 
             #[doc(hidden)]
             #[pg_extern(immutable,parallel_safe)]
-            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr) -> #name #generics {
-                #name::input(input)
+            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr #in_typmod_args) -> #name #generics {
+                #in_body
             }
 
             #[doc(hidden)]
@@ -762,8 +1290,8 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
         stream.extend(quote! {
             #[doc(hidden)]
             #[pg_extern(immutable,parallel_safe)]
-            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr) -> #name #generics {
-                #name::input(input)
+            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr #in_typmod_args) -> #name #generics {
+                #in_body
             }
 
             #[doc(hidden)]
@@ -779,8 +1307,8 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
         stream.extend(quote! {
             #[doc(hidden)]
             #[pg_extern(immutable,parallel_safe)]
-            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr) -> pgx::PgVarlena<#name #generics> {
-                #name::input(input)
+            pub fn #funcname_in #generics(input: &#lifetime pgx::cstr_core::CStr #in_typmod_args) -> pgx::PgVarlena<#name #generics> {
+                #in_body
             }
 
             #[doc(hidden)]
@@ -793,7 +1321,76 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
         });
     }
 
-    let sql_graph_entity_item = PostgresType::from_derive_input(ast).unwrap();
+    // binary send/recv functions are opt-in via `#[pgx(sendrecv)]` and use the SendRecvFuncs trait
+    if let Some((funcname_send, funcname_recv)) = sql_graph_entity_item.send_recv_fns() {
+        if args.contains(&PostgresTypeAttribute::PgVarlenaInOutFuncs) {
+            stream.extend(quote! {
+                #[doc(hidden)]
+                #[pg_extern(immutable,parallel_safe)]
+                pub fn #funcname_recv #generics(internal: pgx::Internal) -> pgx::PgVarlena<#name #generics> {
+                    let mut result = pgx::PgVarlena::<#name #generics>::new();
+                    *result = #name::recv(unsafe { pgx::consume_recv_buffer(&internal) });
+                    result
+                }
+
+                #[doc(hidden)]
+                #[pg_extern(immutable,parallel_safe)]
+                pub fn #funcname_send #generics(input: pgx::PgVarlena<#name #generics>) -> Vec<u8> {
+                    input.send()
+                }
+            });
+        } else {
+            stream.extend(quote! {
+                #[doc(hidden)]
+                #[pg_extern(immutable,parallel_safe)]
+                pub fn #funcname_recv #generics(internal: pgx::Internal) -> #name #generics {
+                    #name::recv(unsafe { pgx::consume_recv_buffer(&internal) })
+                }
+
+                #[doc(hidden)]
+                #[pg_extern(immutable,parallel_safe)]
+                pub fn #funcname_send #generics(input: #name #generics) -> Vec<u8> {
+                    input.send()
+                }
+            });
+        }
+    }
+
+    // type modifiers are opt-in via `#[pgx(typmod)]` and use the TypmodInOutFuncs trait
+    if let Some(typmod_fns) = sql_graph_entity_item.typmod_fns() {
+        let funcname_typmod_in = &typmod_fns.typmod_in;
+        let funcname_typmod_out = &typmod_fns.typmod_out;
+        let funcname_coerce = &typmod_fns.coerce;
+        let value_type = if args.contains(&PostgresTypeAttribute::PgVarlenaInOutFuncs) {
+            quote! { pgx::PgVarlena<#name #generics> }
+        } else {
+            quote! { #name #generics }
+        };
+        stream.extend(quote! {
+            #[doc(hidden)]
+            #[pg_extern(immutable,parallel_safe,requires = [#name])]
+            pub fn #funcname_typmod_in #generics(modifiers: pgx::Array<&#lifetime pgx::cstr_core::CStr>) -> i32 {
+                <#name #generics as pgx::TypmodInOutFuncs>::typmod_in(modifiers)
+            }
+
+            #[doc(hidden)]
+            #[pg_extern(immutable,parallel_safe,requires = [#name])]
+            pub fn #funcname_typmod_out #generics(typmod: i32) -> &#lifetime pgx::cstr_core::CStr {
+                let mut buffer = StringInfo::new();
+                buffer.push_str(&<#name #generics as pgx::TypmodInOutFuncs>::typmod_out(typmod));
+                buffer.into()
+            }
+
+            #[doc(hidden)]
+            #[pg_extern(immutable,parallel_safe)]
+            pub fn #funcname_coerce #generics(input: #value_type, typmod: i32, is_explicit: bool) -> #value_type {
+                let mut input = input;
+                input.apply_typmod(typmod, is_explicit);
+                input
+            }
+        });
+    }
+
     sql_graph_entity_item.to_tokens(&mut stream);
 
     stream
@@ -990,6 +1587,59 @@ pub fn postgres_hash(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/**
+Generate a Postgres range type, named after the type with a `Range` suffix, whose subtype is the type.
+
+The type must also be a `PostgresType` and derive `PostgresOrd`, as Postgres requires a range's
+subtype to have a default btree operator class.  The range can then be used as `pgx::Range<T>`.
+
+```rust,ignore
+# use pgx_pg_sys as pg_sys;
+use pgx::*;
+use serde::{Deserialize, Serialize};
+#[derive(
+    Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord,
+    PostgresType, PostgresEq, PostgresOrd, PostgresRange
+)]
+pub struct Version {
+    major: i32,
+    minor: i32,
+}
+
+#[pg_extern]
+fn version_range(lower: Version, upper: Version) -> Range<Version> {
+    Range::new(RangeBound::Inclusive(lower), RangeBound::Exclusive(upper))
+}
+```
+Optionally accepts the following attributes:
+
+* `sql`: Same arguments as [`#[pgx(sql = ..)]`](macro@pgx).
+*/
+#[proc_macro_derive(PostgresRange, attributes(pgx))]
+pub fn postgres_range(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    impl_postgres_range(ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn impl_postgres_range(ast: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let sql_graph_entity_item = PostgresRange::from_derive_input(ast.clone())?;
+    let range_name = sql_graph_entity_item.range_name();
+
+    let mut stream = quote! {
+        impl pgx::datum::RangeSubType for #name {
+            fn range_type_oid() -> pg_sys::Oid {
+                pgx::regtypein(#range_name)
+            }
+        }
+    };
+    sql_graph_entity_item.to_tokens(&mut stream);
+
+    Ok(stream)
+}
+
 /**
 Declare a `pgx::Aggregate` implentation on a type as able to used by Postgres as an aggregate.
 
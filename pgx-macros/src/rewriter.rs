@@ -69,7 +69,29 @@ impl PgGuardRewriter {
         let func_name = &func.sig.ident;
         let func_span = func.span();
         let rewritten_args = self.rewrite_args(func.clone(), is_raw);
-        let rewritten_return_type = self.rewrite_return_type(func.clone());
+
+        // a `Result`'s `Err` is raised as an ERROR, and its `Ok` value returned like any other
+        let result_ok_type = match &func.sig.output {
+            ReturnType::Type(_, ty) => pgx_utils::result_ok_type(ty).cloned(),
+            ReturnType::Default => None,
+        };
+        // a trigger function returns a `HeapTuple` pointer to the trigger manager, not a Datum
+        let is_trigger = func.sig.inputs.iter().any(|arg| match arg {
+            FnArg::Typed(ty) => pgx_utils::is_pg_trigger(&ty.ty),
+            FnArg::Receiver(_) => false,
+        });
+        let rewritten_return_type = if is_trigger {
+            quote! { pgx::IntoTriggerResult::into_trigger_result(result, fcinfo) }
+        } else {
+            match &result_ok_type {
+                Some(ok_type) => {
+                    let mut ok_func = func.clone();
+                    ok_func.sig.output = syn::parse_quote! { -> #ok_type };
+                    self.rewrite_return_type(ok_func)
+                }
+                None => self.rewrite_return_type(func.clone()),
+            }
+        };
         let generics = &func.sig.generics;
         let func_name_wrapper = Ident::new(
             &format!("{}_wrapper", &func.sig.ident.to_string()),
@@ -85,12 +107,22 @@ impl PgGuardRewriter {
             Ident::new("result", Span::call_site())
         };
 
-        let func_call = quote! {
-            let #result_var_name = {
-                #rewritten_args
+        let func_call = if result_ok_type.is_some() {
+            quote! {
+                let #result_var_name = {
+                    #rewritten_args
+
+                    pgx::unwrap_or_raise(#func_name(#arg_list))
+                };
+            }
+        } else {
+            quote! {
+                let #result_var_name = {
+                    #rewritten_args
 
-                #func_name(#arg_list)
-            };
+                    #func_name(#arg_list)
+                };
+            }
         };
 
         let prolog = quote! {
@@ -100,6 +132,21 @@ impl PgGuardRewriter {
             #[allow(unused_variables)]
         };
         match categorize_return_type(&func) {
+            _ if is_trigger => (
+                PgGuardRewriter::impl_standard_udf(
+                    func_span,
+                    prolog,
+                    vis,
+                    func_name_wrapper,
+                    generics,
+                    func_call,
+                    rewritten_return_type,
+                    entity_submission,
+                    no_guard,
+                ),
+                true,
+            ),
+
             CategorizedType::Default => (
                 PgGuardRewriter::impl_standard_udf(
                     func_span,
@@ -220,10 +267,19 @@ impl PgGuardRewriter {
         entity_submission: Option<&PgExtern>,
     ) -> proc_macro2::TokenStream {
         let func_span = func.span();
-        let return_type = func.sig.output;
-        let return_type = format!("{}", quote! {#return_type});
-        let return_type =
-            proc_macro2::TokenStream::from_str(return_type.trim_start_matches("->")).unwrap();
+        let body = func.block;
+        let (return_type, body) = match &func.sig.output {
+            ReturnType::Type(_, ty) => match pgx_utils::result_ok_type(ty) {
+                Some(ok_type) => (
+                    quote! {#ok_type},
+                    quote! {
+                        pgx::unwrap_or_raise((move || -> #ty #body)())
+                    },
+                ),
+                None => (quote! {#ty}, quote! {#body}),
+            },
+            ReturnType::Default => unreachable!("a tuple-returning function has a return type"),
+        };
         let return_type = quote! {impl std::iter::Iterator<Item = #return_type>};
         let attrs = entity_submission
             .unwrap()
@@ -233,7 +289,6 @@ impl PgGuardRewriter {
 
         func.sig.output = ReturnType::Default;
         let sig = func.sig;
-        let body = func.block;
 
         // We do **not** put an entity submission here as there still exists a `pg_extern` attribute.
         //
@@ -727,6 +782,12 @@ impl FunctionSignatureRewriter {
         let mut i = 0usize;
         let fcinfo_ident: syn::Ident = syn::parse_quote! { fcinfo };
 
+        // Postgres passes a window function's arguments through its WindowObject instead
+        let is_window = self.func.sig.inputs.iter().any(|arg| match arg {
+            FnArg::Typed(ty) => pgx_utils::is_window_object(&ty.ty),
+            FnArg::Receiver(_) => false,
+        });
+
         for arg in &self.func.sig.inputs {
             match arg {
                 FnArg::Receiver(_) => panic!("Functions that take self are not supported"),
@@ -736,13 +797,55 @@ impl FunctionSignatureRewriter {
                         let mut type_ = ty.ty.clone();
                         let is_option = type_matches(&type_, "Option");
 
+                        if pgx_utils::is_pg_trigger(&type_) {
+                            // a trigger function's only argument, which comes from the trigger manager
+                            let trigger = match type_.deref() {
+                                Type::Reference(_) => {
+                                    quote! { &pgx::PgTrigger::from_fcinfo(#fcinfo_ident) }
+                                }
+                                _ => quote! { pgx::PgTrigger::from_fcinfo(#fcinfo_ident) },
+                            };
+                            stream.extend(quote_spanned! {ident.span()=>
+                                let #name = #trigger;
+                            });
+                            continue;
+                        }
+
+                        if pgx_utils::is_pg_event_trigger(&type_) {
+                            // an event trigger function's only argument, from the event trigger manager
+                            let event_trigger = match type_.deref() {
+                                Type::Reference(_) => {
+                                    quote! { &pgx::PgEventTrigger::from_fcinfo(#fcinfo_ident) }
+                                }
+                                _ => quote! { pgx::PgEventTrigger::from_fcinfo(#fcinfo_ident) },
+                            };
+                            stream.extend(quote_spanned! {ident.span()=>
+                                let #name = #event_trigger;
+                            });
+                            continue;
+                        }
+
+                        if pgx_utils::is_window_object(&type_) {
+                            // not one of the function's SQL arguments
+                            stream.extend(quote_spanned! {ident.span()=>
+                                let #name = pgx::WindowObject::from_fcinfo(#fcinfo_ident);
+                            });
+                            continue;
+                        }
+
                         let ts = if is_option {
                             let option_type = extract_option_type(&type_);
                             let mut option_type = syn::parse2::<syn::Type>(option_type).unwrap();
                             pgx_utils::anonymonize_lifetimes(&mut option_type);
 
-                            quote_spanned! {ident.span()=>
-                                let #name = pgx::pg_getarg::<#option_type>(#fcinfo_ident, #i);
+                            if is_window {
+                                quote_spanned! {ident.span()=>
+                                    let #name = pgx::WindowObject::from_fcinfo(#fcinfo_ident).current_arg::<#option_type>(#i);
+                                }
+                            } else {
+                                quote_spanned! {ident.span()=>
+                                    let #name = pgx::pg_getarg::<#option_type>(#fcinfo_ident, #i);
+                                }
                             }
                         } else if type_matches(&type_, "pg_sys :: FunctionCallInfo")
                             || type_matches(&type_, "pgx :: pg_sys :: FunctionCallInfo")
@@ -754,6 +857,16 @@ impl FunctionSignatureRewriter {
                             quote_spanned! {ident.span()=>
                                 let #name = pgx::pg_getarg_datum_raw(#fcinfo_ident, #i) as #type_;
                             }
+                        } else if is_window {
+                            // Postgres doesn't apply STRICT to window functions, so a NULL argument
+                            // that can't be represented returns NULL, as if it did
+                            pgx_utils::anonymonize_lifetimes(&mut type_);
+                            quote_spanned! {ident.span()=>
+                                let #name = match pgx::WindowObject::from_fcinfo(#fcinfo_ident).current_arg::<#type_>(#i) {
+                                    Some(arg) => arg,
+                                    None => return pgx::pg_return_null(#fcinfo_ident),
+                                };
+                            }
                         } else {
                             pgx_utils::anonymonize_lifetimes(&mut type_);
                             quote_spanned! {ident.span()=>
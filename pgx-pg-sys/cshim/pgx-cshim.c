@@ -46,6 +46,45 @@ void pgx_ereport(int level, int code, char *message, char *file, int lineno, int
                     errmsg("%s", message), errcontext_msg("%s:%d:%d", file, lineno, colno)));
 }
 
+PGDLLEXPORT void pgx_ereport_report(int level, int code, char *message, char *detail, char *hint, char *context, int position, char *schema_name, char *table_name, char *column_name, char *datatype_name, char *constraint_name, const char *file, int lineno, const char *funcname);
+void pgx_ereport_report(int level, int code, char *message, char *detail, char *hint, char *context, int position, char *schema_name, char *table_name, char *column_name, char *datatype_name, char *constraint_name, const char *file, int lineno, const char *funcname) {
+    /*
+     * like ereport(), but with the location of the Rust caller.  `file` and `funcname` are kept
+     * in the ErrorData without being copied, so they must live for the rest of the backend
+     */
+#if PG_VERSION_NUM >= 130000
+    if (errstart(level, TEXTDOMAIN)) {
+#else
+    if (errstart(level, file, lineno, funcname, TEXTDOMAIN)) {
+#endif
+        errcode(code);
+        errmsg("%s", message);
+        if (detail != NULL)
+            errdetail("%s", detail);
+        if (hint != NULL)
+            errhint("%s", hint);
+        if (context != NULL)
+            errcontext_msg("%s", context);
+        if (position > 0)
+            errposition(position);
+        if (schema_name != NULL)
+            err_generic_string(PG_DIAG_SCHEMA_NAME, schema_name);
+        if (table_name != NULL)
+            err_generic_string(PG_DIAG_TABLE_NAME, table_name);
+        if (column_name != NULL)
+            err_generic_string(PG_DIAG_COLUMN_NAME, column_name);
+        if (datatype_name != NULL)
+            err_generic_string(PG_DIAG_DATATYPE_NAME, datatype_name);
+        if (constraint_name != NULL)
+            err_generic_string(PG_DIAG_CONSTRAINT_NAME, constraint_name);
+#if PG_VERSION_NUM >= 130000
+        errfinish(file, lineno, funcname);
+#else
+        errfinish(0);
+#endif
+    }
+}
+
 PGDLLEXPORT void pgx_SET_VARSIZE(struct varlena *ptr, int size);
 void pgx_SET_VARSIZE(struct varlena *ptr, int size) {
     SET_VARSIZE(ptr, size);
@@ -233,6 +233,23 @@ mod all_versions {
         pub fn pgx_list_nth_oid(list: *mut super::List, nth: i32) -> super::Oid;
         pub fn pgx_list_nth_cell(list: *mut super::List, nth: i32) -> *mut super::ListCell;
         pub fn pgx_GETSTRUCT(tuple: pg_sys::HeapTuple) -> *mut std::os::raw::c_char;
+        pub fn pgx_ereport_report(
+            level: i32,
+            code: i32,
+            message: *const std::os::raw::c_char,
+            detail: *const std::os::raw::c_char,
+            hint: *const std::os::raw::c_char,
+            context: *const std::os::raw::c_char,
+            position: i32,
+            schema_name: *const std::os::raw::c_char,
+            table_name: *const std::os::raw::c_char,
+            column_name: *const std::os::raw::c_char,
+            datatype_name: *const std::os::raw::c_char,
+            constraint_name: *const std::os::raw::c_char,
+            file: *const std::os::raw::c_char,
+            lineno: i32,
+            funcname: *const std::os::raw::c_char,
+        );
     }
 
     #[inline]
@@ -453,6 +470,13 @@ mod internal {
         pub use crate::pg10::AllocSetContextCreate as AllocSetContextCreateExtended;
         pub type QueryCompletion = std::os::raw::c_char;
 
+        // PG10's pg_type.h only #defines INT4RANGEOID, so we provide the other builtin range types
+        pub const NUMRANGEOID: u32 = 3906;
+        pub const TSRANGEOID: u32 = 3908;
+        pub const TSTZRANGEOID: u32 = 3910;
+        pub const DATERANGEOID: u32 = 3912;
+        pub const INT8RANGEOID: u32 = 3926;
+
         pub unsafe fn add_string_reloption(
             kinds: bits32,
             name: *const ::std::os::raw::c_char,
@@ -828,6 +828,8 @@ pub const AF_UNSPEC: u32 = 0;
 pub const AF_LOCAL: u32 = 1;
 pub const AF_UNIX: u32 = 1;
 pub const AF_FILE: u32 = 1;
+pub const PGSQL_AF_INET: u32 = 2;
+pub const PGSQL_AF_INET6: u32 = 3;
 pub const AF_INET: u32 = 2;
 pub const AF_AX25: u32 = 3;
 pub const AF_IPX: u32 = 4;
@@ -62461,6 +62463,95 @@ pub struct SharedRecordTypmodRegistry {
 extern "C" {
     pub fn lookup_type_cache(type_id: Oid, flags: ::std::os::raw::c_int) -> *mut TypeCacheEntry;
 }
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RangeType {
+    pub vl_len_: int32,
+    pub rangetypid: Oid,
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RangeBound {
+    pub val: Datum,
+    pub infinite: bool,
+    pub inclusive: bool,
+    pub lower: bool,
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_get_typcache(fcinfo: FunctionCallInfo, rngtypid: Oid) -> *mut TypeCacheEntry;
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_serialize(
+        typcache: *mut TypeCacheEntry,
+        lower: *mut RangeBound,
+        upper: *mut RangeBound,
+        empty: bool,
+    ) -> *mut RangeType;
+}
+#[pg_guard]
+extern "C" {
+    pub fn range_deserialize(
+        typcache: *mut TypeCacheEntry,
+        range: *const RangeType,
+        lower: *mut RangeBound,
+        upper: *mut RangeBound,
+        empty: *mut bool,
+    );
+}
+#[pg_guard]
+extern "C" {
+    pub fn make_range(
+        typcache: *mut TypeCacheEntry,
+        lower: *mut RangeBound,
+        upper: *mut RangeBound,
+        empty: bool,
+    ) -> *mut RangeType;
+}
+#[pg_guard]
+extern "C" {
+    pub fn make_empty_range(typcache: *mut TypeCacheEntry) -> *mut RangeType;
+}
+#[pg_guard]
+extern "C" {
+    pub fn IsBinaryCoercible(srctype: Oid, targettype: Oid) -> bool;
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct inet_struct {
+    pub family: ::std::os::raw::c_uchar,
+    pub bits: ::std::os::raw::c_uchar,
+    pub ipaddr: [::std::os::raw::c_uchar; 16usize],
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct inet {
+    pub vl_len_: [::std::os::raw::c_char; 4usize],
+    pub inet_data: inet_struct,
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct macaddr {
+    pub a: ::std::os::raw::c_uchar,
+    pub b: ::std::os::raw::c_uchar,
+    pub c: ::std::os::raw::c_uchar,
+    pub d: ::std::os::raw::c_uchar,
+    pub e: ::std::os::raw::c_uchar,
+    pub f: ::std::os::raw::c_uchar,
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct macaddr8 {
+    pub a: ::std::os::raw::c_uchar,
+    pub b: ::std::os::raw::c_uchar,
+    pub c: ::std::os::raw::c_uchar,
+    pub d: ::std::os::raw::c_uchar,
+    pub e: ::std::os::raw::c_uchar,
+    pub f: ::std::os::raw::c_uchar,
+    pub g: ::std::os::raw::c_uchar,
+    pub h: ::std::os::raw::c_uchar,
+}
 #[pg_guard]
 extern "C" {
     pub fn InitDomainConstraintRef(
@@ -62614,6 +62705,65 @@ pub struct HashAggSpill {
 pub struct WindowObjectData {
     pub _address: u8,
 }
+pub const WINDOW_SEEK_CURRENT: u32 = 0;
+pub const WINDOW_SEEK_HEAD: u32 = 1;
+pub const WINDOW_SEEK_TAIL: u32 = 2;
+pub type WindowObject = *mut WindowObjectData;
+#[pg_guard]
+extern "C" {
+    pub fn WinGetPartitionLocalMemory(
+        winobj: WindowObject,
+        sz: Size,
+    ) -> *mut ::std::os::raw::c_void;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinGetCurrentPosition(winobj: WindowObject) -> int64;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinGetPartitionRowCount(winobj: WindowObject) -> int64;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinSetMarkPosition(winobj: WindowObject, markpos: int64);
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinRowsArePeers(winobj: WindowObject, pos1: int64, pos2: int64) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinGetFuncArgInPartition(
+        winobj: WindowObject,
+        argno: ::std::os::raw::c_int,
+        relpos: ::std::os::raw::c_int,
+        seektype: ::std::os::raw::c_int,
+        set_mark: bool,
+        isnull: *mut bool,
+        isout: *mut bool,
+    ) -> Datum;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinGetFuncArgInFrame(
+        winobj: WindowObject,
+        argno: ::std::os::raw::c_int,
+        relpos: ::std::os::raw::c_int,
+        seektype: ::std::os::raw::c_int,
+        set_mark: bool,
+        isnull: *mut bool,
+        isout: *mut bool,
+    ) -> Datum;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinGetFuncArgCurrent(
+        winobj: WindowObject,
+        argno: ::std::os::raw::c_int,
+        isnull: *mut bool,
+    ) -> Datum;
+}
 #[repr(C)]
 #[derive(Debug, Default, Copy, Clone)]
 pub struct ParallelExecutorInfo {
@@ -6,6 +6,8 @@
 use crate::FlushErrorState;
 use std::any::Any;
 use std::cell::Cell;
+use std::ffi::CStr;
+use std::os::raw::c_char;
 use std::panic::catch_unwind;
 
 extern "C" {
@@ -97,6 +99,12 @@ pub fn register_pg_guard_panic_handler() {
             }
         }
 
+        // a Postgres ERROR has no interesting Rust location, and as it might be caught and
+        // swallowed, remembering its location would only mislabel the next real panic!()
+        if info.payload().is::<JumpContext>() {
+            return;
+        }
+
         PANIC_LOCATION.with(|p| {
             let existing = p.take();
 
@@ -166,6 +174,18 @@ impl<T> PgTryResult<T> {
         }
     }
 
+    /// Retrieve the returned value, or the caught error as a boxed [`CaughtError`], which can be
+    /// inspected and then either swallowed or rethrown with [`CaughtError::rethrow`].
+    ///
+    /// ## Safety
+    ///
+    /// Like [`PgTryResult::unwrap_or`], the caught error is cleared, but the work it interrupted
+    /// isn't rolled back.  Unless it's rethrown, this can leave Postgres in an undefined state.
+    /// Use [`pg_try_subtransaction`] to catch errors and safely continue.
+    pub unsafe fn catch_error(self) -> Result<T, Box<CaughtError>> {
+        self.0.map_err(|e| Box::new(CaughtError::from_panic(e)))
+    }
+
     /// Perform some operation cleanup operation after the try block if an error was thrown.
     ///
     /// In the event an error was caught, it is rethrown.
@@ -227,6 +247,221 @@ where
     try_guard(try_func)
 }
 
+/// Like [`pg_try`], but runs `try_func` in a subtransaction, which is rolled back if it raises an
+/// error, so Postgres is left in a known state and it's safe to continue after the error.
+///
+/// This is what PL/pgSQL does for a block with an `EXCEPTION` clause, and is just as expensive.
+///
+/// ```rust,no_run
+/// use pgx_pg_sys::*;
+///
+/// let result = pg_try_subtransaction(|| {
+///     // something that might raise an ERROR
+/// });
+/// if let Err(e) = result {
+///     if e.sqlstate() != "23505" {
+///         // not a unique_violation, so let it abort the transaction
+///         e.rethrow()
+///     }
+/// }
+/// ```
+pub fn pg_try_subtransaction<Try, R>(try_func: Try) -> Result<R, Box<CaughtError>>
+where
+    Try: FnOnce() -> R + std::panic::UnwindSafe + std::panic::RefUnwindSafe,
+{
+    unsafe {
+        let oldcontext = crate::CurrentMemoryContext;
+        let oldowner = crate::CurrentResourceOwner;
+
+        crate::BeginInternalSubTransaction(std::ptr::null_mut());
+
+        // run `try_func` in the caller's memory context, so what it allocates outlives the
+        // subtransaction
+        crate::CurrentMemoryContext = oldcontext;
+
+        let result = catch_unwind(try_func);
+
+        match result {
+            Ok(result) => {
+                crate::ReleaseCurrentSubTransaction();
+                crate::CurrentMemoryContext = oldcontext;
+                crate::CurrentResourceOwner = oldowner;
+                Ok(result)
+            }
+            Err(e) => {
+                crate::CurrentMemoryContext = oldcontext;
+                let caught = CaughtError::from_panic(e);
+
+                crate::RollbackAndReleaseCurrentSubTransaction();
+                crate::CurrentMemoryContext = oldcontext;
+                crate::CurrentResourceOwner = oldowner;
+                Err(Box::new(caught))
+            }
+        }
+    }
+}
+
+/// A Postgres ERROR, or Rust panic, caught by [`PgTryResult::catch_error`] or
+/// [`pg_try_subtransaction`], copied out of Postgres' `ErrorData`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaughtError {
+    /// The encoded SQLSTATE, comparable to `PgSqlErrorCode` variants cast to an `i32`
+    pub sqlerrcode: i32,
+    pub message: String,
+    pub detail: Option<String>,
+    pub hint: Option<String>,
+    pub context: Option<String>,
+    /// The one-based character position of the error in the query string
+    pub position: Option<i32>,
+    pub schema_name: Option<String>,
+    pub table_name: Option<String>,
+    pub column_name: Option<String>,
+    pub datatype_name: Option<String>,
+    pub constraint_name: Option<String>,
+    /// The source file the error was raised from
+    pub file: Option<String>,
+    pub line: i32,
+    /// The C function the error was raised from, if it was raised by Postgres
+    pub funcname: Option<String>,
+}
+
+impl CaughtError {
+    /// Copy and clear the error Postgres is currently handling
+    ///
+    /// ## Safety
+    ///
+    /// Postgres must be handling an error, as it is after a `JumpContext` panic is caught, and
+    /// `CurrentMemoryContext` must not be `ErrorContext`
+    pub unsafe fn take_current() -> Self {
+        let edata = crate::CopyErrorData();
+        FlushErrorState();
+
+        let caught = {
+            let edata = edata.as_ref().expect("CopyErrorData() returned NULL");
+            CaughtError {
+                sqlerrcode: edata.sqlerrcode,
+                message: cstr_to_string(edata.message).unwrap_or_default(),
+                detail: cstr_to_string(edata.detail),
+                hint: cstr_to_string(edata.hint),
+                context: cstr_to_string(edata.context),
+                position: if edata.cursorpos > 0 {
+                    Some(edata.cursorpos)
+                } else {
+                    None
+                },
+                schema_name: cstr_to_string(edata.schema_name),
+                table_name: cstr_to_string(edata.table_name),
+                column_name: cstr_to_string(edata.column_name),
+                datatype_name: cstr_to_string(edata.datatype_name),
+                constraint_name: cstr_to_string(edata.constraint_name),
+                file: cstr_to_string(edata.filename),
+                line: edata.lineno,
+                funcname: cstr_to_string(edata.funcname),
+            }
+        };
+        crate::FreeErrorData(edata);
+        caught
+    }
+
+    unsafe fn from_panic(e: Box<dyn Any + Send>) -> Self {
+        match downcast_err(e) {
+            // a Rust panic!(), reported like `catch_guard()` would
+            Ok(message) => {
+                let location = take_panic_location();
+                CaughtError {
+                    sqlerrcode: 2600, // ERRCODE_INTERNAL_ERROR
+                    message,
+                    detail: None,
+                    hint: None,
+                    context: Some(format!(
+                        "{}:{}:{}",
+                        location.file, location.line, location.col
+                    )),
+                    position: None,
+                    schema_name: None,
+                    table_name: None,
+                    column_name: None,
+                    datatype_name: None,
+                    constraint_name: None,
+                    file: Some(location.file),
+                    line: location.line as i32,
+                    funcname: None,
+                }
+            }
+            Err(_) => CaughtError::take_current(),
+        }
+    }
+
+    /// The five-character SQLSTATE, such as `"23505"`
+    pub fn sqlstate(&self) -> String {
+        (0..5)
+            .map(|i| (((self.sqlerrcode >> (6 * i)) & 0x3F) as u8 + b'0') as char)
+            .collect()
+    }
+
+    /// Raise this error again, with its original location
+    pub fn rethrow(self) -> ! {
+        use crate::{ereport_cstr_ptr as as_ptr, ereport_cstring as to_cstring};
+
+        let message = to_cstring(&self.message);
+        let detail = self.detail.as_deref().map(to_cstring);
+        let hint = self.hint.as_deref().map(to_cstring);
+        let context = self.context.as_deref().map(to_cstring);
+        let schema_name = self.schema_name.as_deref().map(to_cstring);
+        let table_name = self.table_name.as_deref().map(to_cstring);
+        let column_name = self.column_name.as_deref().map(to_cstring);
+        let datatype_name = self.datatype_name.as_deref().map(to_cstring);
+        let constraint_name = self.constraint_name.as_deref().map(to_cstring);
+        let (file, line) = match &self.file {
+            Some(file) => (file.as_str(), self.line),
+            None => (file!(), line!() as i32),
+        };
+        let funcname = self
+            .funcname
+            .as_deref()
+            .map_or(std::ptr::null(), |funcname| {
+                crate::ereport_static_cstr(funcname).as_ptr()
+            });
+
+        unsafe {
+            crate::pgx_ereport_report(
+                crate::ERROR as i32,
+                self.sqlerrcode,
+                message.as_ptr(),
+                as_ptr(&detail),
+                as_ptr(&hint),
+                as_ptr(&context),
+                self.position.unwrap_or(0),
+                as_ptr(&schema_name),
+                as_ptr(&table_name),
+                as_ptr(&column_name),
+                as_ptr(&datatype_name),
+                as_ptr(&constraint_name),
+                crate::ereport_static_cstr(file).as_ptr(),
+                line,
+                funcname,
+            );
+        }
+        unreachable!("ereport(ERROR) returned")
+    }
+}
+
+impl std::fmt::Display for CaughtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CaughtError {}
+
+unsafe fn cstr_to_string(s: *const c_char) -> Option<String> {
+    if s.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(s).to_string_lossy().into_owned())
+    }
+}
+
 fn try_guard<Try, R>(try_func: Try) -> PgTryResult<R>
 where
     Try: FnOnce() -> R + std::panic::UnwindSafe + std::panic::RefUnwindSafe,
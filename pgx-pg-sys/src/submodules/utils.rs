@@ -3,6 +3,10 @@
 
 //! General utility functions
 use crate as pg_sys;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
 
 /// Converts a `pg_sys::NameData` struct into a `&str`.  
 ///
@@ -14,3 +18,41 @@ pub fn name_data_to_str(name_data: &pg_sys::NameData) -> &str {
         .to_str()
         .unwrap()
 }
+
+/// Converts `s` into a `CString` for `pgx_ereport_report()`, dropping interior NULs, which would
+/// only truncate it in C anyways
+#[doc(hidden)]
+pub fn ereport_cstring(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).expect("failed to create CString")
+}
+
+/// The pointer to pass to `pgx_ereport_report()` for an optional field, NULL when it isn't set
+#[doc(hidden)]
+pub fn ereport_cstr_ptr(s: &Option<CString>) -> *const c_char {
+    s.as_ref().map_or(std::ptr::null(), |s| s.as_ptr())
+}
+
+/// `name`, the file or function name of a report, as a C string that lives until the backend
+/// exits.
+///
+/// Postgres keeps the file and function names given to `ereport()` in its `ErrorData` without
+/// copying them, nor does `CopyErrorData()` copy them, so they must never be freed.  Each distinct
+/// name is leaked once.
+#[doc(hidden)]
+pub fn ereport_static_cstr(name: &str) -> &'static CStr {
+    thread_local! {
+        static NAMES: RefCell<HashMap<String, &'static CStr>> = RefCell::new(HashMap::new());
+    }
+
+    NAMES.with(|names| {
+        let mut names = names.borrow_mut();
+        match names.get(name) {
+            Some(cstr) => *cstr,
+            None => {
+                let cstr: &'static CStr = Box::leak(ereport_cstring(name).into_boxed_c_str());
+                names.insert(name.to_string(), cstr);
+                cstr
+            }
+        }
+    })
+}
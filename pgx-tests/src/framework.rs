@@ -193,6 +193,15 @@ fn get_pg_config() -> PgConfig {
         .clone()
 }
 
+/// A new connection to the test database, for tests that must run statements outside of the
+/// transaction block a `#[pg_test]` is called in, such as a `CALL` of a procedure that commits
+pub fn connect_outside_transaction(
+    postgresql_conf: Vec<&'static str>,
+) -> eyre::Result<postgres::Client> {
+    initialize_test_framework(postgresql_conf)?;
+    Ok(client().0)
+}
+
 pub fn client() -> (postgres::Client, String) {
     fn determine_session_id(client: &mut Client) -> String {
         let result = client.query("SELECT to_hex(trunc(EXTRACT(EPOCH FROM backend_start))::integer) || '.' || to_hex(pid) AS sid FROM pg_stat_activity WHERE pid = pg_backend_pid();", &[]).expect("failed to determine session id");
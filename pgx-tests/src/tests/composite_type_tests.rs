@@ -0,0 +1,126 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[derive(Debug, PartialEq, PostgresComposite)]
+pub struct Animal {
+    name: String,
+    legs: i32,
+    nickname: Option<String>,
+}
+
+#[derive(Debug, PartialEq, PostgresComposite)]
+pub struct Enclosure {
+    label: String,
+    resident: Animal,
+}
+
+#[pg_extern]
+fn make_animal(name: String, legs: i32, nickname: Option<String>) -> Animal {
+    Animal {
+        name,
+        legs,
+        nickname,
+    }
+}
+
+#[pg_extern]
+fn animal_name(animal: Animal) -> String {
+    animal.name
+}
+
+#[pg_extern]
+fn all_animals() -> impl std::iter::Iterator<Item = Animal> {
+    vec![
+        Animal {
+            name: "cat".into(),
+            legs: 4,
+            nickname: Some("Nami".into()),
+        },
+        Animal {
+            name: "bird".into(),
+            legs: 2,
+            nickname: None,
+        },
+    ]
+    .into_iter()
+}
+
+#[pg_extern]
+fn animal_array() -> Vec<Animal> {
+    all_animals().collect()
+}
+
+#[pg_extern]
+fn enclose(label: String, resident: Animal) -> Enclosure {
+    Enclosure { label, resident }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use super::Animal;
+    use pgx::*;
+
+    #[pg_test]
+    fn test_return_composite() {
+        let legs = Spi::get_one::<i32>("SELECT (make_animal('cat', 4, NULL)).legs;")
+            .expect("failed to get SPI result");
+        assert_eq!(4, legs)
+    }
+
+    #[pg_test]
+    fn test_take_composite() {
+        let name = Spi::get_one::<String>("SELECT animal_name(ROW('dog', 4, 'Brandy')::Animal);")
+            .expect("failed to get SPI result");
+        assert_eq!("dog", name)
+    }
+
+    #[pg_test]
+    fn test_composite_from_datum() {
+        let animal = Spi::get_one::<Animal>("SELECT make_animal('cat', 4, 'Nami');")
+            .expect("failed to get SPI result");
+        assert_eq!(
+            Animal {
+                name: "cat".into(),
+                legs: 4,
+                nickname: Some("Nami".into()),
+            },
+            animal
+        )
+    }
+
+    #[pg_test]
+    fn test_setof_composite() {
+        let names = Spi::get_one::<String>(
+            "SELECT string_agg(name || ':' || COALESCE(nickname, '-'), ',') FROM all_animals();",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!("cat:Nami,bird:-", names)
+    }
+
+    #[pg_test]
+    fn test_composite_array() {
+        let legs = Spi::get_one::<i32>("SELECT (animal_array())[2].legs;")
+            .expect("failed to get SPI result");
+        assert_eq!(2, legs)
+    }
+
+    #[pg_test]
+    fn test_nested_composite() {
+        let nickname = Spi::get_one::<String>(
+            "SELECT ((enclose('A1', make_animal('cat', 4, 'Nami'))).resident).nickname;",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!("Nami", nickname)
+    }
+
+    #[pg_test(error = "attribute `legs` of composite type `Animal` is NULL")]
+    fn test_null_required_attribute() {
+        Spi::get_one::<String>("SELECT animal_name(ROW('snake', NULL, NULL)::Animal);");
+    }
+}
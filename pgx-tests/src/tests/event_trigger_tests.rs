@@ -0,0 +1,125 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+extension_sql!(
+    r#"
+CREATE TABLE event_trigger_log (entry text);
+"#,
+    name = "create_event_trigger_log",
+);
+
+// event triggers fire for every test's DDL, so only log the table these tests create
+fn is_audited(object_identity: &str) -> bool {
+    object_identity == "tests.event_trigger_audited"
+}
+
+fn log_entry(entry: String) {
+    Spi::execute(|mut client| {
+        client.update("INSERT INTO event_trigger_log VALUES ($1)", None, (entry,));
+    });
+}
+
+#[pg_event_trigger(
+    event = "ddl_command_end",
+    tags = ["CREATE TABLE", "ALTER TABLE"],
+    requires = ["create_event_trigger_log"],
+)]
+fn event_trigger_log_ddl(trigger: &PgEventTrigger) {
+    let statement = match trigger.parse_tree().type_ {
+        pg_sys::NodeTag_T_CreateStmt => "CreateStmt",
+        pg_sys::NodeTag_T_AlterTableStmt => "AlterTableStmt",
+        _ => "other",
+    };
+    for command in trigger
+        .ddl_commands()
+        .filter(|command| is_audited(&command.object_identity))
+    {
+        log_entry(format!(
+            "{:?} {} {} {}: {} {} in_extension={}",
+            trigger.event(),
+            trigger.event_name(),
+            trigger.tag(),
+            statement,
+            command.object_type,
+            command.object_identity,
+            command.in_extension,
+        ));
+    }
+}
+
+#[pg_event_trigger(
+    event = "sql_drop",
+    tags = ["DROP TABLE"],
+    requires = ["create_event_trigger_log"],
+)]
+fn event_trigger_log_drops(trigger: PgEventTrigger) -> Result<(), Box<dyn std::error::Error>> {
+    for object in trigger
+        .dropped_objects()
+        .filter(|object| object.original && is_audited(&object.object_identity))
+    {
+        log_entry(format!(
+            "{:?} {}: {} {} {:?}",
+            trigger.event(),
+            trigger.tag(),
+            object.object_type,
+            object.object_identity,
+            object.address_names,
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_event_trigger_declared() {
+        let declared = Spi::get_one::<String>(
+            "SELECT evtevent || ' ' || array_to_string(evttags, ',') || ' ' || proname || ' ' || \
+             prorettype::regtype::text FROM pg_event_trigger JOIN pg_proc ON pg_proc.oid = evtfoid \
+             WHERE evtname = 'event_trigger_log_ddl'",
+        );
+        assert_eq!(
+            declared,
+            Some(
+                "ddl_command_end CREATE TABLE,ALTER TABLE event_trigger_log_ddl event_trigger"
+                    .to_string()
+            )
+        );
+    }
+
+    #[pg_test]
+    fn test_event_trigger_context() {
+        Spi::run("CREATE TABLE tests.event_trigger_audited (id int)");
+        Spi::run("ALTER TABLE tests.event_trigger_audited ADD COLUMN name text");
+        Spi::run("DROP TABLE tests.event_trigger_audited");
+
+        let entries = Spi::connect(|client| {
+            let entries = client
+                .select(
+                    "SELECT entry FROM event_trigger_log ORDER BY ctid",
+                    None,
+                    None,
+                )
+                .map(|row| row.by_ordinal(1).unwrap().value::<String>().unwrap())
+                .collect::<Vec<_>>();
+            Ok(Some(entries))
+        })
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                "DdlCommandEnd ddl_command_end CREATE TABLE CreateStmt: table tests.event_trigger_audited in_extension=false",
+                "DdlCommandEnd ddl_command_end ALTER TABLE AlterTableStmt: table tests.event_trigger_audited in_extension=false",
+                r#"SqlDrop DROP TABLE: table tests.event_trigger_audited ["tests", "event_trigger_audited"]"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use std::num::NonZeroUsize;
+
+extension_sql!(
+    r#"CREATE TYPE dog AS (name text, scritches integer);"#,
+    name = "create_dog_type",
+);
+
+#[pg_extern(requires = ["create_dog_type"])]
+fn scritch_dog(mut dog: composite_type!("dog")) -> composite_type!("dog") {
+    let scritches: i32 = dog
+        .get_by_name("scritches")
+        .expect("no scritches attribute")
+        .unwrap_or_default();
+    dog.set_by_name("scritches", scritches + 1)
+        .expect("failed to set scritches");
+    dog
+}
+
+#[pg_extern(requires = ["create_dog_type"])]
+fn make_dog(name: String, scritches: Option<i32>) -> composite_type!("dog") {
+    let mut dog = PgHeapTuple::new_composite_type("dog");
+    dog.set_by_name("name", name).expect("failed to set name");
+    dog.set_by_index(NonZeroUsize::new(2).unwrap(), scritches)
+        .expect("failed to set scritches");
+    dog
+}
+
+#[pg_extern(requires = ["create_dog_type"])]
+fn dog_name(dog: Option<composite_type!("dog")>) -> Option<String> {
+    dog?.get_by_name("name").expect("no name attribute")
+}
+
+#[pg_extern(requires = ["create_dog_type"])]
+fn make_dogs(names: Vec<String>) -> impl std::iter::Iterator<Item = composite_type!("dog")> {
+    names.into_iter().map(|name| {
+        let mut dog = PgHeapTuple::new_composite_type("dog");
+        dog.set_by_name("name", name).expect("failed to set name");
+        dog
+    })
+}
+
+#[pg_extern]
+fn record_attribute_count(record: PgHeapTuple) -> i32 {
+    record.attributes().count() as i32
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+    use std::num::NonZeroUsize;
+
+    #[pg_test]
+    fn test_scritch_dog() {
+        let rc =
+            Spi::get_one::<bool>("SELECT scritch_dog(ROW('Nami', 1)::dog) = ROW('Nami', 2)::dog;")
+                .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_scritch_dog_null_attribute() {
+        let scritches =
+            Spi::get_one::<i32>("SELECT (scritch_dog(ROW('Nami', NULL)::dog)).scritches;")
+                .expect("failed to get SPI result");
+        assert_eq!(1, scritches)
+    }
+
+    #[pg_test]
+    fn test_make_dog() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT make_dog('Brandy', 5) = ROW('Brandy', 5)::dog AND (make_dog('Brandy', NULL)).scritches IS NULL;",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_dog_name() {
+        let name = Spi::get_one::<String>("SELECT dog_name(ROW('Nami', 1)::dog);")
+            .expect("failed to get SPI result");
+        assert_eq!("Nami", name);
+        assert!(Spi::get_one::<String>("SELECT dog_name(NULL);").is_none());
+    }
+
+    #[pg_test]
+    fn test_make_dogs() {
+        let names = Spi::get_one::<String>(
+            "SELECT string_agg(name, ',') FROM make_dogs(ARRAY['Nami', 'Brandy']);",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!("Nami,Brandy", names)
+    }
+
+    #[pg_test]
+    fn test_record_attribute_count() {
+        let count = Spi::get_one::<i32>("SELECT record_attribute_count(ROW(1, 'two', 3.0));")
+            .expect("failed to get SPI result");
+        assert_eq!(3, count)
+    }
+
+    #[pg_test]
+    fn test_get_and_set() {
+        let mut dog = PgHeapTuple::new_composite_type("dog");
+        assert_eq!(2, dog.len());
+        assert_eq!(Ok(None), dog.get_by_name::<String>("name"));
+
+        dog.set_by_name("name", "Nami").unwrap();
+        dog.set_by_index(NonZeroUsize::new(2).unwrap(), 42).unwrap();
+        assert_eq!(
+            Ok(Some("Nami".to_string())),
+            dog.get_by_index::<String>(NonZeroUsize::new(1).unwrap())
+        );
+        assert_eq!(Ok(Some(42)), dog.get_by_name::<i32>("scritches"));
+
+        dog.set_by_name::<Option<i32>>("scritches", None).unwrap();
+        assert_eq!(Ok(None), dog.get_by_name::<i32>("scritches"));
+    }
+
+    #[pg_test]
+    fn test_from_datum() {
+        let dog = Spi::get_one::<PgHeapTuple>("SELECT ROW('Nami', 7)::dog;")
+            .expect("failed to get SPI result");
+        assert_eq!(Ok(Some(7)), dog.get_by_name::<i32>("scritches"));
+    }
+
+    #[pg_test]
+    fn test_attribute_errors() {
+        let mut dog = PgHeapTuple::new_composite_type("dog");
+        assert_eq!(
+            Err(PgHeapTupleError::NoSuchAttributeName("tail".to_string())),
+            dog.get_by_name::<i32>("tail")
+        );
+        assert_eq!(
+            Err(PgHeapTupleError::NoSuchAttributeNumber(
+                NonZeroUsize::new(3).unwrap()
+            )),
+            dog.set_by_index(NonZeroUsize::new(3).unwrap(), 1)
+        );
+        assert!(matches!(
+            dog.set_by_name("scritches", "lots"),
+            Err(PgHeapTupleError::IncompatibleTypes { .. })
+        ));
+    }
+
+    #[pg_test(error = "type \"not_a_type\" does not exist")]
+    fn test_new_composite_type_unknown_type() {
+        PgHeapTuple::new_composite_type("not_a_type");
+    }
+}
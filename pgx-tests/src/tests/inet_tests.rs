@@ -1,6 +1,165 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
+use pgx::*;
+use std::convert::TryFrom;
+
+#[pg_extern]
+fn inet_network(inet: Inet) -> Cidr {
+    inet.network()
+}
+
+#[pg_extern]
+fn inet_broadcast(inet: Inet) -> String {
+    inet.broadcast().to_string()
+}
+
+#[pg_extern]
+fn inet_masklen(inet: Inet) -> i32 {
+    inet.masklen() as i32
+}
+
+#[pg_extern]
+fn cidr_contains(cidr: Cidr, inet: Inet) -> bool {
+    cidr.contains(&inet)
+}
+
+#[pg_extern]
+fn take_and_return_cidr(cidr: Cidr) -> Cidr {
+    cidr
+}
+
+#[pg_extern]
+fn take_and_return_macaddr(mac: MacAddr) -> MacAddr {
+    mac
+}
+
+#[pg_extern]
+fn take_and_return_macaddr8(mac: MacAddr8) -> MacAddr8 {
+    mac
+}
+
+#[pg_extern]
+fn macaddr_to_macaddr8(mac: MacAddr) -> MacAddr8 {
+    mac.into()
+}
+
+#[pg_extern]
+fn macaddr8_to_macaddr(mac: MacAddr8) -> MacAddr {
+    MacAddr::try_from(mac).expect("macaddr8 is not an EUI-48 address")
+}
+
+#[cfg(test)]
+#[pgx::pg_schema]
+mod conversion_tests {
+    use pgx::*;
+    use std::convert::TryFrom;
+    use std::net::IpAddr;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_inet_parse_and_display() {
+        for (input, expected) in &[
+            ("192.168.0.1", "192.168.0.1"),
+            ("192.168.0.1/32", "192.168.0.1"),
+            ("192.168.0.1/24", "192.168.0.1/24"),
+            ("2001:db8::1/64", "2001:db8::1/64"),
+        ] {
+            assert_eq!(*expected, Inet::from_str(input).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn test_inet_parse_invalid() {
+        assert_eq!(
+            Err(NetworkAddressError::InvalidMaskLength(33)),
+            Inet::from_str("10.0.0.1/33")
+        );
+        assert_eq!(
+            Err(NetworkAddressError::InvalidAddress("10.0.0".to_string())),
+            Inet::from_str("10.0.0")
+        );
+    }
+
+    #[test]
+    fn test_cidr_host_bits() {
+        assert_eq!(
+            Err(NetworkAddressError::HostBitsSet),
+            Cidr::from_str("10.1.2.3/8")
+        );
+        assert_eq!(
+            "10.0.0.0/8",
+            Cidr::from_str("10.0.0.0/8").unwrap().to_string()
+        );
+        assert_eq!(
+            Err(NetworkAddressError::HostBitsSet),
+            Cidr::try_from(Inet::from_str("10.1.2.3/8").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_inet_helpers() {
+        let inet = Inet::from_str("192.168.1.5/22").unwrap();
+        assert_eq!("192.168.0.0/22", inet.network().to_string());
+        assert_eq!(IpAddr::from_str("192.168.3.255").unwrap(), inet.broadcast());
+        assert_eq!(IpAddr::from_str("255.255.252.0").unwrap(), inet.netmask());
+        assert_eq!(IpAddr::from_str("0.0.3.255").unwrap(), inet.hostmask());
+
+        let inet = Inet::from_str("2001:db8::1/32").unwrap();
+        assert_eq!("2001:db8::/32", inet.network().to_string());
+        assert_eq!(
+            IpAddr::from_str("2001:db8:ffff:ffff:ffff:ffff:ffff:ffff").unwrap(),
+            inet.broadcast()
+        );
+    }
+
+    #[test]
+    fn test_containment() {
+        let cidr = Cidr::from_str("10.0.0.0/8").unwrap();
+        assert!(cidr.contains(&Inet::from_str("10.1.0.0/16").unwrap()));
+        assert!(cidr.contains(&Inet::from_str("10.0.0.0/8").unwrap()));
+        assert!(!cidr.contains(&Inet::from_str("10.0.0.0/7").unwrap()));
+        assert!(!cidr.contains(&Inet::from_str("::a00:0/104").unwrap()));
+        assert!(cidr.contains_addr(&IpAddr::from_str("10.255.255.255").unwrap()));
+        assert!(!cidr.contains_addr(&IpAddr::from_str("11.0.0.0").unwrap()));
+    }
+
+    #[test]
+    fn test_inet_ordering() {
+        let mut values = vec![
+            Inet::from_str("::1").unwrap(),
+            Inet::from_str("10.0.0.1").unwrap(),
+            Inet::from_str("10.0.0.0/8").unwrap(),
+            Inet::from_str("9.255.255.255").unwrap(),
+        ];
+        values.sort();
+        let values = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            vec!["9.255.255.255", "10.0.0.0/8", "10.0.0.1", "::1"],
+            values
+        );
+    }
+
+    #[test]
+    fn test_macaddr_parse_and_display() {
+        let mac = MacAddr::from_str("08-00-2B-01-02-03").unwrap();
+        assert_eq!(MacAddr([0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]), mac);
+        assert_eq!("08:00:2b:01:02:03", mac.to_string());
+        assert_eq!(mac, MacAddr::from_str("08002b010203").unwrap());
+        assert!(MacAddr::from_str("08:00:2b:01:02").is_err());
+    }
+
+    #[test]
+    fn test_macaddr8_conversions() {
+        let mac = MacAddr::from_str("08:00:2b:01:02:03").unwrap();
+        let mac8 = MacAddr8::from(mac);
+        assert_eq!("08:00:2b:ff:fe:01:02:03", mac8.to_string());
+        assert_eq!(Ok(mac), MacAddr::try_from(mac8));
+        assert_eq!(mac8, MacAddr8::from_str("08:00:2b:01:02:03").unwrap());
+        assert!(MacAddr::try_from(MacAddr8::from_str("08:00:2b:01:02:03:04:05").unwrap()).is_err());
+    }
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
 mod tests {
@@ -8,17 +167,18 @@ mod tests {
     use crate as pgx_tests;
 
     use pgx::*;
+    use std::str::FromStr;
 
     #[pg_test]
     fn test_deserialize_inet() {
         let inet =
             serde_json::from_str::<Inet>("\"192.168.0.1\"").expect("failed to deserialize inet");
-        assert_eq!("192.168.0.1", &inet.0)
+        assert_eq!("192.168.0.1", &inet.to_string())
     }
 
     #[pg_test]
     fn test_serialize_inet() {
-        let json = serde_json::to_string(&Inet("192.168.0.1".to_owned()))
+        let json = serde_json::to_string(&Inet::from_str("192.168.0.1").unwrap())
             .expect("failed to serialize inet");
         assert_eq!("\"192.168.0.1\"", &json);
     }
@@ -36,4 +196,98 @@ mod tests {
         .expect("failed to get SPI result");
         assert!(rc)
     }
+
+    #[pg_test]
+    fn test_take_and_return_inet_v6() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT tests.take_and_return_inet('2001:db8::1/64') = '2001:db8::1/64'::inet;",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_inet_from_datum() {
+        let inet = Spi::get_one::<Inet>("SELECT '192.168.1.5/24'::inet;")
+            .expect("failed to get SPI result");
+        assert_eq!(Inet::from_str("192.168.1.5/24").unwrap(), inet);
+    }
+
+    #[pg_test]
+    fn test_inet_network() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT inet_network('192.168.1.5/24') = network('192.168.1.5/24'::inet);",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_inet_broadcast() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT inet_broadcast('192.168.1.5/24') = host(broadcast('192.168.1.5/24'::inet));",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_inet_masklen() {
+        let masklen = Spi::get_one::<i32>("SELECT inet_masklen('2001:db8::/48');")
+            .expect("failed to get SPI result");
+        assert_eq!(48, masklen)
+    }
+
+    #[pg_test]
+    fn test_cidr_contains() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT cidr_contains('10.0.0.0/8', '10.1.2.3') AND NOT cidr_contains('10.0.0.0/8', '11.0.0.1');",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_take_and_return_cidr() {
+        let rc =
+            Spi::get_one::<bool>("SELECT take_and_return_cidr('10.0.0.0/8') = '10.0.0.0/8'::cidr;")
+                .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_take_and_return_macaddr() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT take_and_return_macaddr('08:00:2b:01:02:03') = '08:00:2b:01:02:03'::macaddr;",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_take_and_return_macaddr8() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT take_and_return_macaddr8('08:00:2b:01:02:03:04:05') = '08:00:2b:01:02:03:04:05'::macaddr8;",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_macaddr_to_macaddr8() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT macaddr_to_macaddr8('08:00:2b:01:02:03') = '08:00:2b:01:02:03'::macaddr::macaddr8;",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
+
+    #[pg_test]
+    fn test_macaddr8_to_macaddr() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT macaddr8_to_macaddr('08:00:2b:ff:fe:01:02:03') = '08:00:2b:01:02:03'::macaddr;",
+        )
+        .expect("failed to get SPI result");
+        assert!(rc)
+    }
 }
@@ -0,0 +1,196 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern]
+fn accept_interval(i: Interval) -> Interval {
+    i
+}
+
+#[pg_extern]
+fn return_interval_parts(i: Interval) -> Vec<i64> {
+    vec![i.months() as i64, i.days() as i64, i.micros()]
+}
+
+#[pg_extern]
+fn add_intervals(a: Interval, b: Interval) -> Interval {
+    a + b
+}
+
+#[pg_extern]
+fn timestamp_plus_interval(t: Timestamp, i: Interval) -> Timestamp {
+    t + i
+}
+
+#[pg_extern]
+fn timestamp_with_time_zone_minus_interval(
+    t: TimestampWithTimeZone,
+    i: Interval,
+) -> TimestampWithTimeZone {
+    t - i
+}
+
+#[pg_extern]
+fn date_plus_interval(d: Date, i: Interval) -> Timestamp {
+    d + i
+}
+
+#[pg_extern]
+fn timestamp_difference(a: Timestamp, b: Timestamp) -> Interval {
+    a - b
+}
+
+#[cfg(test)]
+#[pgx::pg_schema]
+mod conversion_tests {
+    use pgx::*;
+    use serde_json::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_interval_from_duration() {
+        let interval = Interval::try_from(time::Duration::seconds(90)).unwrap();
+        assert_eq!(0, interval.months());
+        assert_eq!(0, interval.days());
+        assert_eq!(90_000_000, interval.micros());
+    }
+
+    #[test]
+    fn test_interval_from_duration_sub_microsecond() {
+        assert_eq!(
+            Err(IntervalConversionError::SubMicrosecondPrecision),
+            Interval::try_from(time::Duration::nanoseconds(1500))
+        );
+    }
+
+    #[test]
+    fn test_duration_from_interval() {
+        let duration = time::Duration::try_from(Interval::from_micros(1_500_000)).unwrap();
+        assert_eq!(time::Duration::milliseconds(1500), duration);
+    }
+
+    #[test]
+    fn test_duration_from_interval_with_days() {
+        assert_eq!(
+            Err(IntervalConversionError::HasMonthsOrDays),
+            time::Duration::try_from(Interval::new(0, 1, 0))
+        );
+    }
+
+    #[test]
+    fn test_interval_comparison() {
+        assert_eq!(Interval::from_months(1), Interval::from_days(30));
+        assert!(Interval::from_days(1) < Interval::from_micros(25 * 60 * 60 * 1_000_000));
+        assert_eq!(
+            Interval::new(1, 2, 3),
+            Interval::from_months(1) + Interval::from_days(2) + Interval::from_micros(3)
+        );
+    }
+
+    #[test]
+    fn test_interval_serialization() {
+        let interval = Interval::new(14, 3, 4 * 3_600_000_000 + 5 * 60_000_000 + 6_500_000);
+        let json = json!({ "interval test": interval });
+
+        assert_eq!(json!({"interval test":"P1Y2M3DT4H5M6.5S"}), json);
+    }
+
+    #[test]
+    fn test_zero_interval_serialization() {
+        let json = json!({ "interval test": Interval::new(0, 0, 0) });
+
+        assert_eq!(json!({"interval test":"PT0S"}), json);
+    }
+
+    #[test]
+    fn test_negative_interval_serialization() {
+        let json = json!({ "interval test": Interval::new(-1, 0, -500_000) });
+
+        assert_eq!(json!({"interval test":"P-1MT-0.5S"}), json);
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_accept_interval() {
+        let result = Spi::get_one::<bool>(
+            "SELECT accept_interval('1 year 2 months 3 days 04:05:06.789') = '1 year 2 months 3 days 04:05:06.789'::interval;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_return_interval_parts() {
+        let result = Spi::get_one::<Vec<i64>>(
+            "SELECT return_interval_parts('-1 year 2 days -00:00:01');",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(vec![-12, 2, -1_000_000], result)
+    }
+
+    #[pg_test]
+    fn test_add_intervals() {
+        let result = Spi::get_one::<bool>(
+            "SELECT add_intervals('1 month', '1 day 1 hour') = '1 month 1 day 1 hour'::interval;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_timestamp_plus_interval() {
+        let result = Spi::get_one::<bool>(
+            "SELECT timestamp_plus_interval('2020-01-31 12:00:00', '1 month 1 hour') = '2020-02-29 13:00:00'::timestamp;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_timestamp_with_time_zone_minus_interval() {
+        let result = Spi::get_one::<bool>(
+            "SELECT timestamp_with_time_zone_minus_interval(now(), '1 day') = now() - '1 day'::interval;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_date_plus_interval() {
+        let result = Spi::get_one::<bool>(
+            "SELECT date_plus_interval('2020-02-28', '1 day 12 hours') = '2020-02-29 12:00:00'::timestamp;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_timestamp_difference() {
+        let result = Spi::get_one::<bool>(
+            "SELECT timestamp_difference('2020-03-01 00:00:00', '2020-02-28 12:00:00') = '1 day 12 hours'::interval;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_interval_deserialization() {
+        let interval: Interval = serde_json::from_str("\"1 day 02:00:00\"").unwrap();
+        assert_eq!(Interval::new(0, 1, 2 * 3_600_000_000), interval);
+    }
+
+    #[pg_test]
+    fn test_interval_deserialization_invalid() {
+        let interval: Result<Interval, _> = serde_json::from_str("\"not an interval\"");
+        assert!(interval.is_err());
+    }
+}
@@ -1,6 +1,25 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
+use pgx::*;
+
+#[pg_extern]
+fn ereport_all_fields() {
+    ereport!(
+        PgLogLevel::ERROR,
+        PgSqlErrorCode::ERRCODE_CHECK_VIOLATION,
+        "value is out of range",
+        detail = "the detail",
+        hint = "the hint",
+        context = "the context",
+        schema_name = "the_schema",
+        table_name = "the_table",
+        column_name = "the_column",
+        datatype_name = "the_type",
+        constraint_name = "the_constraint",
+    );
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
 mod tests {
@@ -76,6 +95,95 @@ mod tests {
         )
     }
 
+    #[pg_test]
+    fn test_ereport_macro_notice() {
+        ereport!(
+            PgLogLevel::NOTICE,
+            PgSqlErrorCode::ERRCODE_SUCCESSFUL_COMPLETION,
+            "ereport notice",
+            hint = "notices return",
+        );
+    }
+
+    #[pg_test]
+    fn test_level_macros_with_fields() {
+        notice!(
+            code = PgSqlErrorCode::ERRCODE_SUCCESSFUL_COMPLETION,
+            "notice with fields",
+            detail = "some detail",
+        );
+        warning!(
+            code = PgSqlErrorCode::ERRCODE_WARNING,
+            format!("warning with {}", "fields"),
+            hint = "some hint",
+        );
+    }
+
+    #[pg_test(error = "error with fields")]
+    fn test_error_macro_with_fields() {
+        error!(
+            code = PgSqlErrorCode::ERRCODE_DATA_EXCEPTION,
+            "error with fields",
+            detail = "some detail",
+            table_name = "some_table",
+        );
+    }
+
+    #[pg_test(error = "ereport builder error")]
+    fn test_ereport_builder() {
+        PgErrorReport::new(
+            PgLogLevel::ERROR,
+            PgSqlErrorCode::ERRCODE_DATA_EXCEPTION,
+            "ereport builder error",
+        )
+        .detail("some detail")
+        .report()
+    }
+
+    #[pg_test]
+    fn test_ereport_all_fields() {
+        // raises its own error if the error wasn't reported as expected
+        Spi::run(
+            r#"
+            DO $$
+            DECLARE
+                detail text;
+                hint text;
+                context text;
+                schema_name text;
+                table_name text;
+                column_name text;
+                datatype_name text;
+                constraint_name text;
+            BEGIN
+                PERFORM ereport_all_fields();
+                RAISE 'no error was raised';
+            EXCEPTION WHEN check_violation THEN
+                GET STACKED DIAGNOSTICS
+                    detail = PG_EXCEPTION_DETAIL,
+                    hint = PG_EXCEPTION_HINT,
+                    context = PG_EXCEPTION_CONTEXT,
+                    schema_name = SCHEMA_NAME,
+                    table_name = TABLE_NAME,
+                    column_name = COLUMN_NAME,
+                    datatype_name = PG_DATATYPE_NAME,
+                    constraint_name = CONSTRAINT_NAME;
+                IF detail <> 'the detail'
+                    OR hint <> 'the hint'
+                    OR context NOT LIKE 'the context%'
+                    OR schema_name <> 'the_schema'
+                    OR table_name <> 'the_table'
+                    OR column_name <> 'the_column'
+                    OR datatype_name <> 'the_type'
+                    OR constraint_name <> 'the_constraint' THEN
+                    RAISE 'unexpected error fields';
+                END IF;
+            END;
+            $$;
+            "#,
+        );
+    }
+
     #[pg_test(error = "panic message")]
     fn test_panic() {
         panic!("panic message")
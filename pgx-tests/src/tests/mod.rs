@@ -6,15 +6,19 @@ mod anyarray_tests;
 mod array_tests;
 mod bytea_tests;
 mod cfg_tests;
+mod composite_type_tests;
 mod datetime_tests;
 mod default_arg_value_tests;
 mod derive_pgtype_lifetimes;
 mod enum_type_tests;
+mod event_trigger_tests;
 mod fcinfo_tests;
 mod guc_tests;
+mod heap_tuple_tests;
 mod hooks_tests;
 mod inet_tests;
 mod internal_tests;
+mod interval_tests;
 mod json_tests;
 mod lifetime_tests;
 mod log_tests;
@@ -25,12 +29,22 @@ mod pg_extern_tests;
 mod pg_try_tests;
 mod pgbox_tests;
 mod postgres_type_tests;
+#[cfg(not(feature = "pg10"))]
+mod procedure_tests;
+mod range_tests;
+mod result_tests;
 mod schema_tests;
 mod spi_tests;
 mod srf_tests;
 mod struct_type_tests;
+mod subxact_tests;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+mod support_tests;
+mod trigger_tests;
+mod typmod_tests;
 mod uuid_tests;
 mod variadic_tests;
+mod window_tests;
 mod xact_callback_tests;
 mod xid64_tests;
 
@@ -1,6 +1,154 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
+use pgx::*;
+use std::convert::TryFrom;
+
+#[pg_extern]
+fn add_numerics(a: Numeric, b: Numeric) -> Numeric {
+    a + b
+}
+
+#[pg_extern]
+fn multiply_numerics(a: Numeric, b: Numeric) -> Numeric {
+    a * b
+}
+
+#[pg_extern]
+fn divide_numerics(a: Numeric, b: Numeric) -> Numeric {
+    a / b
+}
+
+#[pg_extern]
+fn round_numeric(n: Numeric, scale: i32) -> Numeric {
+    n.round(scale)
+}
+
+#[pg_extern]
+fn numeric_as_i64(n: Numeric) -> i64 {
+    i64::try_from(n).expect("numeric does not fit in an i64")
+}
+
+#[pg_extern]
+fn numeric_as_f64(n: Numeric) -> f64 {
+    f64::try_from(n).expect("numeric does not fit in an f64")
+}
+
+#[pg_extern]
+fn rescale_numeric(n: Numeric, precision: i32, scale: i32) -> Numeric {
+    n.rescale(precision, scale)
+        .expect("numeric does not fit the precision and scale")
+}
+
+#[pg_extern]
+fn return_an_i128_numeric() -> Numeric {
+    i128::MIN.into()
+}
+
+#[cfg(test)]
+#[pgx::pg_schema]
+mod conversion_tests {
+    use pgx::*;
+    use serde_json::*;
+    use std::convert::TryFrom;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_numeric_parse_and_display() {
+        for (input, expected) in &[
+            ("0", "0"),
+            ("-1.50", "-1.50"),
+            ("007.10", "7.10"),
+            (".5", "0.5"),
+            ("0.000012", "0.000012"),
+            ("1.5e-3", "0.0015"),
+            ("1e10", "10000000000"),
+            (
+                "1234567890123456789012345678901234567890",
+                "1234567890123456789012345678901234567890",
+            ),
+            ("nan", "NaN"),
+            ("-Infinity", "-Infinity"),
+        ] {
+            assert_eq!(*expected, Numeric::from_str(input).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn test_numeric_comparison() {
+        let a = Numeric::from(1);
+        let b = Numeric::from_str("1.000").unwrap();
+        assert_eq!(a, b);
+        assert!(Numeric::from(-1) < Numeric::from(0.5));
+        assert!(Numeric::from(-2) < Numeric::from(-1));
+        assert!(Numeric::from(0) > Numeric::from_str("-0.001").unwrap());
+        assert!(Numeric::from_str("0.001").unwrap() < Numeric::from(1));
+        assert!(Numeric::from(10000) > Numeric::from(9999));
+        assert!(Numeric::negative_infinity() < Numeric::from(i128::MIN));
+        assert!(Numeric::infinity() > Numeric::from(u128::MAX));
+        assert!(Numeric::nan() > Numeric::infinity());
+        assert_eq!(Numeric::nan(), Numeric::nan());
+    }
+
+    #[test]
+    fn test_numeric_parse_invalid() {
+        assert_eq!(
+            "invalid Numeric value: 1.2.3",
+            Numeric::from_str("1.2.3").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_numeric_from_primitives() {
+        assert_eq!("-9223372036854775808", Numeric::from(i64::MIN).to_string());
+        assert_eq!(
+            "340282366920938463463374607431768211455",
+            Numeric::from(u128::MAX).to_string()
+        );
+        assert_eq!("64.64646464", Numeric::from(64.64646464f64).to_string());
+        assert!(Numeric::from(f64::NAN).is_nan());
+        assert_eq!("0.1", Numeric::from(0.1f32).to_string());
+    }
+
+    #[test]
+    fn test_primitives_from_numeric() {
+        assert_eq!(Ok(i128::MIN), i128::try_from(Numeric::from(i128::MIN)));
+        assert_eq!(
+            Ok(20000),
+            i32::try_from(Numeric::from_str("20000.00").unwrap())
+        );
+        assert_eq!(Ok(1.5), f64::try_from(Numeric::from_str("1.5").unwrap()));
+        assert_eq!(Ok(0.1f32), f32::try_from(Numeric::from_str("0.1").unwrap()));
+        assert_eq!(
+            Err(NumericConversionError::OutOfRange),
+            i64::try_from(Numeric::from(u64::MAX))
+        );
+        assert_eq!(
+            Err(NumericConversionError::OutOfRange),
+            u64::try_from(Numeric::from(-1))
+        );
+        assert_eq!(
+            Err(NumericConversionError::NotAnInteger),
+            i32::try_from(Numeric::from_str("1.5").unwrap())
+        );
+        assert_eq!(
+            Err(NumericConversionError::NotFinite),
+            i32::try_from(Numeric::nan())
+        );
+        assert_eq!(
+            Err(NumericConversionError::OutOfRange),
+            f64::try_from(Numeric::from_str("1e400").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_numeric_serialization() {
+        let json = json!({ "numeric test": Numeric::from_str("3.14159").unwrap() });
+
+        assert_eq!(json!({"numeric test":"3.14159"}), json);
+    }
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
 mod tests {
@@ -63,4 +211,96 @@ mod tests {
             .to_string();
         assert_eq!("invalid Numeric value: foo", &error);
     }
+
+    #[pg_test]
+    fn test_numeric_round_trip() {
+        let result = Spi::get_one::<Numeric>("SELECT '-12345678901234567890.000123'::numeric;")
+            .expect("failed to get SPI result");
+        assert_eq!("-12345678901234567890.000123", result.to_string());
+    }
+
+    #[pg_test]
+    fn test_numeric_long_format_round_trip() {
+        let result = Spi::get_one::<bool>(
+            "SELECT tests.add_numerics('1e300', '1e-300') = '1e300'::numeric + '1e-300'::numeric;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result);
+    }
+
+    #[pg_test]
+    fn test_add_numerics() {
+        let result = Spi::get_one::<bool>("SELECT add_numerics(1.25, 2.5) = 3.75;")
+            .expect("failed to get SPI result");
+        assert!(result);
+    }
+
+    #[pg_test]
+    fn test_multiply_numerics() {
+        let result = Spi::get_one::<bool>(
+            "SELECT multiply_numerics(18446744073709551615, 18446744073709551615) = 18446744073709551615::numeric * 18446744073709551615;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result);
+    }
+
+    #[pg_test(error = "division by zero")]
+    fn test_divide_numerics_by_zero() {
+        Spi::get_one::<Numeric>("SELECT divide_numerics(1, 0);");
+    }
+
+    #[pg_test]
+    fn test_round_numeric() {
+        let result = Spi::get_one::<bool>("SELECT round_numeric(2.345, 2) = 2.35;")
+            .expect("failed to get SPI result");
+        assert!(result);
+    }
+
+    #[pg_test]
+    fn test_numeric_as_i64() {
+        let result = Spi::get_one::<i64>("SELECT numeric_as_i64(-9223372036854775808);")
+            .expect("failed to get SPI result");
+        assert_eq!(i64::MIN, result);
+    }
+
+    #[pg_test(error = "numeric does not fit in an i64: NotAnInteger")]
+    fn test_numeric_as_i64_fraction() {
+        Spi::get_one::<i64>("SELECT numeric_as_i64(1.5);");
+    }
+
+    #[pg_test]
+    fn test_numeric_as_f64() {
+        let result =
+            Spi::get_one::<f64>("SELECT numeric_as_f64(0.125);").expect("failed to get SPI result");
+        assert_eq!(0.125, result);
+    }
+
+    #[pg_test]
+    fn test_rescale_numeric() {
+        let result =
+            Spi::get_one::<bool>("SELECT rescale_numeric(123.456, 5, 2)::text = '123.46';")
+                .expect("failed to get SPI result");
+        assert!(result);
+    }
+
+    #[pg_test(error = "numeric does not fit the precision and scale: OutOfRange")]
+    fn test_rescale_numeric_overflow() {
+        Spi::get_one::<Numeric>("SELECT rescale_numeric(12345.6, 5, 2);");
+    }
+
+    #[pg_test]
+    fn test_return_an_i128_numeric() {
+        let result = Spi::get_one::<bool>(
+            "SELECT return_an_i128_numeric() = -170141183460469231731687303715884105728;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result);
+    }
+
+    #[pg_test]
+    fn test_numeric_nan() {
+        let result =
+            Spi::get_one::<Numeric>("SELECT 'NaN'::numeric;").expect("failed to get SPI result");
+        assert!(result.is_nan());
+    }
 }
@@ -28,6 +28,72 @@ mod tests {
         assert!(result)
     }
 
+    #[pg_extern(security_definer, leakproof, immutable)]
+    fn is_security_definer_leakproof(value: i32) -> bool {
+        value > 0
+    }
+
+    #[pg_test]
+    fn test_security_definer_leakproof() {
+        let result = Spi::get_one::<bool>(
+            "SELECT prosecdef AND proleakproof FROM pg_proc WHERE proname = 'is_security_definer_leakproof'",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_extern(called_on_null_input)]
+    fn is_called_on_null_input(value: i32) -> i32 {
+        value
+    }
+
+    #[pg_test]
+    fn test_called_on_null_input() {
+        let result = Spi::get_one::<bool>(
+            "SELECT proisstrict FROM pg_proc WHERE proname = 'is_called_on_null_input'",
+        )
+        .expect("failed to get SPI result");
+        assert!(!result)
+    }
+
+    #[pg_extern(rows = 42)]
+    fn has_rows_estimate() -> impl Iterator<Item = i32> {
+        0..3
+    }
+
+    #[pg_test]
+    fn test_rows() {
+        let result =
+            Spi::get_one::<f32>("SELECT prorows FROM pg_proc WHERE proname = 'has_rows_estimate'")
+                .expect("failed to get SPI result");
+        assert_eq!(result, 42.0)
+    }
+
+    #[pg_extern(set(
+        work_mem = "1234kB",
+        search_path = ["public", "pg_temp"],
+        application_name = "pgx, tests"
+    ))]
+    fn current_work_mem() -> String {
+        Spi::get_one("SHOW work_mem").expect("failed to get SPI result")
+    }
+
+    #[pg_test]
+    fn test_set() {
+        let result = Spi::get_one::<String>(
+            "SELECT array_to_string(proconfig, ';') FROM pg_proc WHERE proname = 'current_work_mem'",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(
+            result,
+            "work_mem=1234kB;search_path=public, pg_temp;application_name=pgx, tests"
+        );
+
+        let work_mem = Spi::get_one::<String>("SELECT tests.current_work_mem()")
+            .expect("failed to get SPI result");
+        assert_eq!(work_mem, "1234kB");
+    }
+
     // Ensures `@FUNCTION_NAME@` is handled.
     #[pg_extern(sql = r#"
         CREATE OR REPLACE FUNCTION tests."overridden_sql_with_fn_name"() RETURNS void
@@ -94,4 +94,89 @@ mod tests {
     fn test_pg_try_unwrap_or_rethrow_with_error_in_rethrow() {
         pg_try(|| panic!("rethrow a panic")).unwrap_or_rethrow(|| panic!("panic in rethrow"));
     }
+
+    #[pg_test]
+    fn test_pg_try_catch_error_no_error() {
+        let result = unsafe { pg_try(|| 42).catch_error() };
+        assert_eq!(Ok(42), result);
+    }
+
+    #[pg_test]
+    fn test_pg_try_catch_error_with_panic() {
+        let error = unsafe { pg_try::<_, ()>(|| panic!("caught a panic")).catch_error() }
+            .expect_err("no error was caught");
+        assert_eq!(error.message, "caught a panic");
+        assert_eq!(error.sqlstate(), "XX000");
+    }
+
+    #[pg_test]
+    fn test_pg_try_subtransaction_no_error() {
+        let result = pg_try_subtransaction(|| Spi::get_one::<i32>("SELECT 42"));
+        assert_eq!(Ok(Some(42)), result);
+    }
+
+    #[pg_test]
+    fn test_pg_try_subtransaction_with_error() {
+        let error =
+            pg_try_subtransaction(|| Spi::run("SELECT 1 / 0")).expect_err("no error was caught");
+        assert_eq!(error.message, "division by zero");
+        assert_eq!(error.sqlstate(), "22012");
+        assert_eq!(
+            error.sqlerrcode,
+            PgSqlErrorCode::ERRCODE_DIVISION_BY_ZERO as i32
+        );
+
+        // the transaction is still usable
+        let result = Spi::get_one::<i32>("SELECT 42");
+        assert_eq!(Some(42), result);
+    }
+
+    #[pg_test]
+    fn test_pg_try_subtransaction_error_position() {
+        let error = pg_try_subtransaction(|| Spi::run("SELECT no_such_column"))
+            .expect_err("no error was caught");
+        assert_eq!(error.sqlstate(), "42703");
+        assert_eq!(error.position, Some(8));
+    }
+
+    #[pg_test]
+    fn test_pg_try_subtransaction_error_fields() {
+        Spi::run(
+            "CREATE TABLE tests.subtransaction_fields (id int CONSTRAINT subtransaction_fields_id_check CHECK (id > 0))",
+        );
+        let error = pg_try_subtransaction(|| {
+            Spi::run("INSERT INTO tests.subtransaction_fields VALUES (0)")
+        })
+        .expect_err("no error was caught");
+        assert_eq!(error.sqlstate(), "23514");
+        assert_eq!(error.schema_name.as_deref(), Some("tests"));
+        assert_eq!(error.table_name.as_deref(), Some("subtransaction_fields"));
+        assert_eq!(
+            error.constraint_name.as_deref(),
+            Some("subtransaction_fields_id_check")
+        );
+        assert_eq!(error.funcname.as_deref(), Some("ExecConstraints"));
+        assert!(error.file.is_some());
+    }
+
+    #[pg_test]
+    fn test_pg_try_subtransaction_rolls_back() {
+        Spi::run("CREATE TABLE tests.subtransaction_rollback (id int)");
+        let result = pg_try_subtransaction(|| {
+            Spi::run("INSERT INTO tests.subtransaction_rollback VALUES (1)");
+            Spi::run("SELECT 1 / 0");
+        });
+        assert!(result.is_err());
+
+        Spi::run("INSERT INTO tests.subtransaction_rollback VALUES (2)");
+        let result = Spi::get_one::<i64>("SELECT sum(id) FROM tests.subtransaction_rollback");
+        assert_eq!(Some(2), result);
+    }
+
+    #[pg_test(error = "division by zero")]
+    fn test_pg_try_subtransaction_rethrow() {
+        if let Err(e) = pg_try_subtransaction(|| Spi::run("SELECT 1 / 0")) {
+            e.rethrow()
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use pgx::cstr_core::CStr;
 use pgx::*;
 use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
 use std::str::FromStr;
 
 #[derive(Copy, Clone, PostgresType)]
@@ -60,6 +61,123 @@ pub struct JsonType {
     c: i64,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize, PostgresType)]
+#[pgx(sendrecv)]
+pub struct BinaryType {
+    a: i32,
+    b: i64,
+}
+
+impl SendRecvFuncs for BinaryType {
+    fn send(&self) -> Vec<u8> {
+        let mut buffer = self.a.to_be_bytes().to_vec();
+        buffer.extend_from_slice(&self.b.to_be_bytes());
+        buffer
+    }
+
+    fn recv(input: &[u8]) -> Self {
+        assert_eq!(12, input.len(), "invalid BinaryType binary representation");
+        BinaryType {
+            a: i32::from_be_bytes(input[0..4].try_into().unwrap()),
+            b: i64::from_be_bytes(input[4..12].try_into().unwrap()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PostgresType)]
+#[pgvarlena_inoutfuncs]
+#[pgx(sendrecv)]
+pub struct BinaryVarlenaType {
+    a: i32,
+}
+
+impl PgVarlenaInOutFuncs for BinaryVarlenaType {
+    fn input(input: &CStr) -> PgVarlena<Self> {
+        let mut result = PgVarlena::<BinaryVarlenaType>::new();
+        result.a = i32::from_str(input.to_str().unwrap()).expect("a is not a valid i32");
+        result
+    }
+
+    fn output(&self, buffer: &mut StringInfo) {
+        buffer.push_str(&self.a.to_string())
+    }
+}
+
+impl SendRecvFuncs for BinaryVarlenaType {
+    fn send(&self) -> Vec<u8> {
+        self.a.to_be_bytes().to_vec()
+    }
+
+    fn recv(input: &[u8]) -> Self {
+        BinaryVarlenaType {
+            a: i32::from_be_bytes(input.try_into().expect("invalid BinaryVarlenaType")),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, PostgresType)]
+#[inoutfuncs]
+#[pgx(passedbyvalue)]
+pub struct ByValueId {
+    value: u64,
+}
+
+impl InOutFuncs for ByValueId {
+    fn input(input: &CStr) -> Self {
+        ByValueId {
+            value: u64::from_str(input.to_str().unwrap()).expect("value is not a valid u64"),
+        }
+    }
+
+    fn output(&self, buffer: &mut StringInfo) {
+        buffer.push_str(&self.value.to_string())
+    }
+}
+
+#[pg_extern]
+fn byvalueid_next(id: ByValueId) -> ByValueId {
+    ByValueId {
+        value: id.value + 1,
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, PostgresType)]
+#[inoutfuncs]
+#[pgx(fixed_size)]
+pub struct FixedSizePoint {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+impl InOutFuncs for FixedSizePoint {
+    fn input(input: &CStr) -> Self {
+        let mut iter = input.to_str().unwrap().split(',');
+        let (x, y, z) = (iter.next(), iter.next(), iter.next());
+
+        FixedSizePoint {
+            x: i32::from_str(x.unwrap()).expect("x is not a valid i32"),
+            y: i32::from_str(y.unwrap()).expect("y is not a valid i32"),
+            z: i32::from_str(z.unwrap()).expect("z is not a valid i32"),
+        }
+    }
+
+    fn output(&self, buffer: &mut StringInfo) {
+        buffer.push_str(&format!("{},{},{}", self.x, self.y, self.z))
+    }
+}
+
+#[pg_extern]
+fn fixedsizepoint_sum(point: FixedSizePoint) -> i32 {
+    point.x + point.y + point.z
+}
+
+#[derive(Serialize, Deserialize, PostgresType)]
+#[pgx(alignment = double, storage = main)]
+pub struct MainStorageType {
+    a: i64,
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
 mod tests {
@@ -67,7 +185,8 @@ mod tests {
     use crate as pgx_tests;
 
     use crate::tests::postgres_type_tests::{
-        CustomTextFormatSerializedType, JsonType, VarlenaType,
+        BinaryType, ByValueId, CustomTextFormatSerializedType, FixedSizePoint, JsonType,
+        VarlenaType,
     };
     use pgx::*;
 
@@ -99,4 +218,76 @@ mod tests {
         assert_eq!(result.b, 2.0);
         assert_eq!(result.c, 3);
     }
+
+    #[pg_test]
+    fn test_binarytype_send() {
+        let result =
+            Spi::get_one::<Vec<u8>>(r#"SELECT binarytype_send('{"a": 1, "b": 2}'::BinaryType)"#)
+                .expect("SPI returned NULL");
+        assert_eq!(vec![0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2], result);
+    }
+
+    #[pg_test]
+    fn test_binarytype_recv_roundtrip() {
+        let value = BinaryType { a: -7, b: i64::MAX };
+        assert_eq!(value, BinaryType::recv(&value.send()));
+    }
+
+    #[pg_test]
+    fn test_sendrecv_registered() {
+        let rc = Spi::get_one::<bool>(
+            "SELECT bool_and(send.proname = typname || '_send' AND recv.proname = typname || '_recv')
+               FROM pg_type
+               JOIN pg_proc send ON send.oid = typsend
+               JOIN pg_proc recv ON recv.oid = typreceive
+              WHERE typname IN ('binarytype', 'binaryvarlenatype')",
+        )
+        .expect("SPI returned NULL");
+        assert!(rc);
+    }
+
+    #[pg_test]
+    fn test_binaryvarlenatype_send() {
+        let result =
+            Spi::get_one::<Vec<u8>>("SELECT binaryvarlenatype_send('258'::BinaryVarlenaType)")
+                .expect("SPI returned NULL");
+        assert_eq!(vec![0, 0, 1, 2], result);
+    }
+
+    #[pg_test]
+    fn test_passedbyvalue_type() {
+        let id = Spi::get_one::<ByValueId>("SELECT byvalueid_next('41'::ByValueId);")
+            .expect("SPI returned NULL");
+        assert_eq!(ByValueId { value: 42 }, id);
+    }
+
+    #[pg_test]
+    fn test_fixed_size_type() {
+        Spi::run("CREATE TABLE fixed_size_points (p FixedSizePoint, id ByValueId);");
+        Spi::run("INSERT INTO fixed_size_points VALUES ('1,2,3', '7'), ('4,5,6', '8');");
+        let sum = Spi::get_one::<i32>(
+            "SELECT sum(fixedsizepoint_sum(p))::integer FROM fixed_size_points;",
+        )
+        .expect("SPI returned NULL");
+        assert_eq!(21, sum);
+
+        let point =
+            Spi::get_one::<FixedSizePoint>("SELECT p FROM fixed_size_points WHERE id::text = '8';")
+                .expect("SPI returned NULL");
+        assert_eq!(FixedSizePoint { x: 4, y: 5, z: 6 }, point);
+    }
+
+    #[pg_test]
+    fn test_type_layout() {
+        let layout = Spi::get_one::<String>(
+            "SELECT string_agg(typname || ':' || typlen || ':' || typbyval || ':' || typalign || ':' || typstorage, ',' ORDER BY typname)
+               FROM pg_type
+              WHERE typname IN ('byvalueid', 'fixedsizepoint', 'mainstoragetype', 'jsontype')",
+        )
+        .expect("SPI returned NULL");
+        assert_eq!(
+            "byvalueid:8:true:d:p,fixedsizepoint:12:false:i:p,jsontype:-1:false:i:x,mainstoragetype:-1:false:d:m",
+            layout
+        );
+    }
 }
@@ -0,0 +1,141 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_procedure]
+fn insert_procedure_value(value: i32) {
+    Spi::execute(|mut client| {
+        client.update("INSERT INTO procedure_values VALUES ($1)", None, (value,));
+    });
+}
+
+#[pg_procedure]
+fn commit_procedure() {
+    Spi::execute(|_| Spi::commit());
+}
+
+#[pg_procedure]
+fn commit_and_rollback_procedure() {
+    Spi::execute(|mut client| {
+        client.update("CREATE TABLE committed_values (value int)", None, None);
+        client.update("INSERT INTO committed_values VALUES (1)", None, None);
+        Spi::commit();
+        client.update("INSERT INTO committed_values VALUES (2)", None, None);
+        Spi::rollback();
+    });
+}
+
+#[pg_procedure]
+fn insert_after_rollback_procedure() {
+    Spi::execute(|mut client| {
+        client.update("INSERT INTO rolled_back_values VALUES (1)", None, None);
+        Spi::rollback();
+        client.update("INSERT INTO rolled_back_values VALUES (2)", None, None);
+        Spi::commit();
+    });
+}
+
+#[pg_procedure]
+fn read_row_after_commit_procedure() {
+    Spi::execute(|client| {
+        let row = client
+            .select("SELECT 'committed'::text", None, None)
+            .next()
+            .unwrap();
+        Spi::commit();
+        row.by_ordinal(1).unwrap().value::<String>();
+    });
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_call_procedure() {
+        Spi::run("CREATE TABLE procedure_values (value int)");
+        Spi::run("CALL insert_procedure_value(42)");
+        let value = Spi::get_one::<i32>("SELECT value FROM procedure_values")
+            .expect("failed to get SPI result");
+        assert_eq!(42, value);
+    }
+
+    #[pg_test]
+    fn test_procedure_is_a_procedure() {
+        let kind = Spi::get_one::<String>(
+            "SELECT prokind::text FROM pg_proc WHERE proname = 'insert_procedure_value'",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!("p", kind);
+    }
+
+    #[pg_test(error = "invalid transaction termination")]
+    fn test_commit_in_atomic_call() {
+        Spi::run("CALL commit_procedure()");
+    }
+
+    #[test]
+    fn test_commit_in_procedure() {
+        let mut client =
+            pgx_tests::connect_outside_transaction(crate::pg_test::postgresql_conf_options())
+                .expect("failed to connect");
+        client
+            .simple_query("CALL commit_and_rollback_procedure()")
+            .expect("failed to call procedure");
+        let values = client
+            .query_one("SELECT array_agg(value)::text FROM committed_values", &[])
+            .expect("failed to select committed values")
+            .get::<_, String>(0);
+        client
+            .simple_query("DROP TABLE committed_values")
+            .expect("failed to drop table");
+        assert_eq!("{1}", values);
+    }
+
+    #[test]
+    fn test_statement_after_rollback_in_procedure() {
+        let mut client =
+            pgx_tests::connect_outside_transaction(crate::pg_test::postgresql_conf_options())
+                .expect("failed to connect");
+        client
+            .simple_query("CREATE TABLE rolled_back_values (value int)")
+            .expect("failed to create table");
+        client
+            .simple_query("CALL insert_after_rollback_procedure()")
+            .expect("failed to call procedure");
+        let values = client
+            .query_one("SELECT array_agg(value)::text FROM rolled_back_values", &[])
+            .expect("failed to select rolled back values")
+            .get::<_, String>(0);
+        client
+            .simple_query("DROP TABLE rolled_back_values")
+            .expect("failed to drop table");
+        assert_eq!("{2}", values);
+    }
+
+    #[test]
+    fn test_row_after_commit_in_procedure() {
+        let mut client =
+            pgx_tests::connect_outside_transaction(crate::pg_test::postgresql_conf_options())
+                .expect("failed to connect");
+        let result = client.simple_query("CALL read_row_after_commit_procedure()");
+        assert_eq!(
+            Some("SpiHeapTupleData used after its transaction ended"),
+            result
+                .err()
+                .as_ref()
+                .and_then(|error| error.as_db_error())
+                .map(|error| error.message())
+        );
+    }
+
+    #[pg_test(error = "transaction control requires an SPI connection")]
+    fn test_commit_without_connection() {
+        Spi::commit();
+    }
+}
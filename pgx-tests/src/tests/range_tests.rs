@@ -0,0 +1,232 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    PostgresType,
+    PostgresEq,
+    PostgresOrd,
+    PostgresRange,
+)]
+pub struct Version {
+    major: i32,
+    minor: i32,
+}
+
+#[pg_extern]
+fn accept_int4range(r: Range<i32>) -> Range<i32> {
+    r
+}
+
+#[pg_extern]
+fn accept_int8range(r: Range<i64>) -> Range<i64> {
+    r
+}
+
+#[pg_extern]
+fn accept_numrange(r: Range<Numeric>) -> Range<Numeric> {
+    r
+}
+
+#[pg_extern]
+fn accept_tsrange(r: Range<Timestamp>) -> Range<Timestamp> {
+    r
+}
+
+#[pg_extern]
+fn accept_tstzrange(r: Range<TimestampWithTimeZone>) -> Range<TimestampWithTimeZone> {
+    r
+}
+
+#[pg_extern]
+fn accept_daterange(r: Range<Date>) -> Range<Date> {
+    r
+}
+
+#[pg_extern]
+fn int4range_lower(r: Range<i32>) -> Option<i32> {
+    r.lower().and_then(|bound| bound.get().cloned())
+}
+
+#[pg_extern]
+fn int4range_is_empty(r: Range<i32>) -> bool {
+    r.is_empty()
+}
+
+#[pg_extern]
+fn make_int4range(lower: i32, upper: i32) -> Range<i32> {
+    (lower..=upper).into()
+}
+
+#[pg_extern]
+fn make_unbounded_int8range(lower: i64) -> Range<i64> {
+    (lower..).into()
+}
+
+#[pg_extern]
+fn make_empty_int4range() -> Range<i32> {
+    Range::empty()
+}
+
+#[pg_extern]
+fn make_version_range(lower: Version, upper: Version) -> Range<Version> {
+    Range::new(RangeBound::Inclusive(lower), RangeBound::Exclusive(upper))
+}
+
+#[pg_extern]
+fn version_range_upper_major(r: Range<Version>) -> Option<i32> {
+    r.upper()
+        .and_then(|bound| bound.get())
+        .map(|version| version.major)
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_accept_int4range() {
+        let result =
+            Spi::get_one::<bool>("SELECT accept_int4range('[1,10)') = '[1,10)'::int4range;")
+                .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_accept_int8range() {
+        let result = Spi::get_one::<bool>("SELECT accept_int8range('(,10]') = '(,10]'::int8range;")
+            .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_accept_numrange() {
+        let result =
+            Spi::get_one::<bool>("SELECT accept_numrange('(1.5,2.5]') = '(1.5,2.5]'::numrange;")
+                .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_accept_tsrange() {
+        let result = Spi::get_one::<bool>(
+            "SELECT accept_tsrange('[2020-01-01 00:00:00,2020-02-01 12:00:00)') = '[2020-01-01 00:00:00,2020-02-01 12:00:00)'::tsrange;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_accept_tstzrange() {
+        let result = Spi::get_one::<bool>(
+            "SELECT accept_tstzrange(tstzrange(now(), NULL)) = tstzrange(now(), NULL);",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_accept_daterange() {
+        let result = Spi::get_one::<bool>(
+            "SELECT accept_daterange('[2020-01-01,2020-12-31]') = '[2020-01-01,2020-12-31]'::daterange;",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_accept_empty_range() {
+        let result = Spi::get_one::<bool>("SELECT isempty(accept_int4range('empty'));")
+            .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_int4range_lower() {
+        let result = Spi::get_one::<i32>("SELECT int4range_lower('(1,10)');")
+            .expect("failed to get SPI result");
+        // Postgres canonicalizes discrete ranges to `[)` form
+        assert_eq!(2, result)
+    }
+
+    #[pg_test]
+    fn test_int4range_lower_infinite() {
+        let result = Spi::get_one::<i32>("SELECT int4range_lower('(,10)');");
+        assert_eq!(None, result)
+    }
+
+    #[pg_test]
+    fn test_int4range_is_empty() {
+        let result = Spi::get_one::<bool>("SELECT int4range_is_empty('[5,5)');")
+            .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_make_int4range() {
+        let result = Spi::get_one::<bool>("SELECT make_int4range(1, 10) = '[1,11)'::int4range;")
+            .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_make_unbounded_int8range() {
+        let result =
+            Spi::get_one::<bool>("SELECT upper_inf(make_unbounded_int8range(42)) AND lower(make_unbounded_int8range(42)) = 42;")
+                .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test]
+    fn test_make_empty_int4range() {
+        let result = Spi::get_one::<bool>("SELECT make_empty_int4range() = 'empty'::int4range;")
+            .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_test(error = "range lower bound must be less than or equal to range upper bound")]
+    fn test_make_int4range_invalid() {
+        Spi::get_one::<bool>("SELECT make_int4range(10, 1) IS NOT NULL;");
+    }
+
+    #[pg_test]
+    fn test_range_round_trip() {
+        let range: Range<i32> =
+            Spi::get_one("SELECT '[1,10)'::int4range;").expect("failed to get SPI result");
+        assert_eq!(
+            Range::new(RangeBound::Inclusive(1), RangeBound::Exclusive(10)),
+            range
+        );
+    }
+
+    #[pg_test]
+    fn test_custom_range() {
+        let result = Spi::get_one::<i32>(
+            r#"SELECT version_range_upper_major(make_version_range('{"major": 1, "minor": 0}', '{"major": 2, "minor": 3}'));"#,
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(2, result)
+    }
+
+    #[pg_test]
+    fn test_custom_range_contains() {
+        let result = Spi::get_one::<bool>(
+            r#"SELECT make_version_range('{"major": 1, "minor": 0}', '{"major": 2, "minor": 0}') @> '{"major": 1, "minor": 5}'::Version;"#,
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern]
+fn result_checked_divide(a: i32, b: i32) -> Result<i32, PgError> {
+    a.checked_div(b).ok_or_else(|| {
+        PgError::new(
+            PgSqlErrorCode::ERRCODE_DIVISION_BY_ZERO,
+            "cannot divide by zero",
+        )
+        .detail(format!("tried to divide {} by zero", a))
+        .hint("divide by something else")
+    })
+}
+
+#[pg_extern]
+fn result_parse_int(input: &str) -> Result<i32, Box<dyn std::error::Error>> {
+    Ok(input.parse::<i32>()?)
+}
+
+#[pg_extern]
+fn result_count_to(n: i32) -> Result<impl Iterator<Item = i32>, PgError> {
+    if n < 0 {
+        return Err(PgError::new(
+            PgSqlErrorCode::ERRCODE_INVALID_PARAMETER_VALUE,
+            "cannot count to a negative number",
+        ));
+    }
+    Ok(1..=n)
+}
+
+#[pg_extern]
+fn result_unit(fail: bool) -> Result<(), PgError> {
+    if fail {
+        Err(PgError::new(
+            PgSqlErrorCode::ERRCODE_RAISE_EXCEPTION,
+            "failed",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_result_ok() {
+        let result = Spi::get_one::<i32>("SELECT result_checked_divide(10, 2)")
+            .expect("failed to get SPI result");
+        assert_eq!(result, 5);
+    }
+
+    #[pg_test(error = "cannot divide by zero")]
+    fn test_result_err() {
+        Spi::run("SELECT result_checked_divide(10, 0)");
+    }
+
+    #[pg_test]
+    fn test_result_err_sqlstate() {
+        // raises its own error if the error wasn't reported as expected
+        Spi::run(
+            r#"
+            DO $$
+            DECLARE
+                detail text;
+                hint text;
+            BEGIN
+                PERFORM result_checked_divide(10, 0);
+                RAISE 'no error was raised';
+            EXCEPTION WHEN division_by_zero THEN
+                GET STACKED DIAGNOSTICS detail = PG_EXCEPTION_DETAIL, hint = PG_EXCEPTION_HINT;
+                IF detail <> 'tried to divide 10 by zero' OR hint <> 'divide by something else' THEN
+                    RAISE 'unexpected detail or hint: %, %', detail, hint;
+                END IF;
+            END;
+            $$;
+            "#,
+        );
+    }
+
+    #[pg_test]
+    fn test_result_boxed_error() {
+        let result =
+            Spi::get_one::<i32>("SELECT result_parse_int('42')").expect("failed to get SPI result");
+        assert_eq!(result, 42);
+    }
+
+    #[pg_test(error = "invalid digit found in string")]
+    fn test_result_boxed_error_err() {
+        Spi::run("SELECT result_parse_int('forty-two')");
+    }
+
+    #[pg_test]
+    fn test_result_setof() {
+        let result = Spi::get_one::<i64>("SELECT sum(n) FROM result_count_to(4) n")
+            .expect("failed to get SPI result");
+        assert_eq!(result, 10);
+    }
+
+    #[pg_test(error = "cannot count to a negative number")]
+    fn test_result_setof_err() {
+        Spi::run("SELECT * FROM result_count_to(-1)");
+    }
+
+    #[pg_test]
+    fn test_result_unit() {
+        Spi::run("SELECT result_unit(false)");
+    }
+
+    #[pg_test(error = "failed")]
+    fn test_result_unit_err() {
+        Spi::run("SELECT result_unit(true)");
+    }
+}
@@ -162,4 +162,460 @@ mod tests {
         );
         assert_eq!(result, Some(1));
     }
+
+    #[pg_test]
+    fn test_try_get_one() {
+        assert_eq!(
+            Ok(Some(42)),
+            Spi::try_get_one::<i32>("SELECT 42").map_err(|_| ())
+        );
+    }
+
+    #[pg_test]
+    fn test_try_select_catches_error() {
+        Spi::execute(|client| {
+            match client.try_select("SELECT 1 / 0", None, None) {
+                Err(SpiError::Postgres {
+                    sqlstate, message, ..
+                }) => {
+                    assert_eq!("22012", sqlstate);
+                    assert_eq!("division by zero", message);
+                }
+                _ => panic!("division by zero was not caught"),
+            }
+
+            // the transaction is still usable
+            assert_eq!(
+                Some(2),
+                client.select("SELECT 1 + 1", None, None).first().get_one()
+            );
+        });
+    }
+
+    #[pg_test]
+    fn test_try_run_detail_and_hint() {
+        let error = Spi::try_run(
+            "DO $$ BEGIN RAISE EXCEPTION 'bad value' USING ERRCODE = '22023', DETAIL = 'the detail', HINT = 'the hint'; END $$;",
+        )
+        .expect_err("exception was not caught");
+        match error {
+            SpiError::Postgres {
+                sqlstate,
+                message,
+                detail,
+                hint,
+            } => {
+                assert_eq!("22023", sqlstate);
+                assert_eq!("bad value", message);
+                assert_eq!(Some("the detail"), detail.as_deref());
+                assert_eq!(Some("the hint"), hint.as_deref());
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[pg_test]
+    fn test_try_run_rolls_back_failed_statement() {
+        Spi::run("CREATE TABLE tests.try_run (id int);");
+        Spi::run("INSERT INTO tests.try_run VALUES (1);");
+        assert!(Spi::try_run("INSERT INTO tests.try_run VALUES (2); SELECT 1 / 0;").is_err());
+        assert!(Spi::try_run("THIS IS NOT A VALID QUERY").is_err());
+        assert_eq!(
+            Some(1),
+            Spi::get_one::<i64>("SELECT count(*) FROM tests.try_run;")
+        );
+    }
+
+    #[pg_test]
+    fn test_tuple_args() {
+        Spi::execute(|client| {
+            let (sum, greeting) = client
+                .select("SELECT $1 + 1, $2 || '!'", None, (41i32, "hello"))
+                .first()
+                .get_two::<i32, String>();
+            assert_eq!(Some(42), sum);
+            assert_eq!(Some("hello!".to_string()), greeting);
+
+            let is_null = client
+                .select("SELECT $1 IS NULL", None, (None::<i64>,))
+                .first()
+                .get_one::<bool>();
+            assert_eq!(Some(true), is_null);
+        });
+    }
+
+    #[pg_test]
+    fn test_array_and_custom_type_args() {
+        use crate::tests::typmod_tests::Vector;
+
+        Spi::execute(|client| {
+            let len = client
+                .select("SELECT array_length($1, 1)", None, (vec![1, 2, 3],))
+                .first()
+                .get_one::<i32>();
+            assert_eq!(Some(3), len);
+
+            let vector = Spi::get_one::<Vector>("SELECT '[1, 2]'::Vector")
+                .expect("failed to get SPI result");
+            let text = client
+                .select("SELECT $1::text", None, (vector,))
+                .first()
+                .get_one::<String>();
+            assert_eq!(Some("[1,2]".to_string()), text);
+        });
+    }
+
+    #[pg_test]
+    fn test_dyn_args() {
+        Spi::execute(|client| {
+            let args: &[&dyn IntoDatumDyn] = &[&2i64, &"two"];
+            let result = client
+                .select("SELECT repeat($2, $1::int)", None, args)
+                .first()
+                .get_one::<String>();
+            assert_eq!(Some("twotwo".to_string()), result);
+        });
+    }
+
+    #[derive(Debug, PartialEq, FromSpiRow)]
+    struct Person {
+        id: i32,
+        #[pgx(rename = "full_name")]
+        name: String,
+        nickname: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, FromSpiRow)]
+    struct Pair(i64, String);
+
+    #[pg_test]
+    fn test_from_spi_row() {
+        Spi::execute(|client| {
+            let people = client
+                .select(
+                    "SELECT 1 AS id, 'Ada Lovelace' AS full_name, 'Ada' AS nickname
+                     UNION ALL
+                     SELECT 2, 'Alan Turing', NULL",
+                    None,
+                    None,
+                )
+                .rows::<Person>()
+                .collect::<Result<Vec<_>, _>>()
+                .expect("failed to decode rows");
+            assert_eq!(
+                vec![
+                    Person {
+                        id: 1,
+                        name: "Ada Lovelace".into(),
+                        nickname: Some("Ada".into())
+                    },
+                    Person {
+                        id: 2,
+                        name: "Alan Turing".into(),
+                        nickname: None
+                    },
+                ],
+                people
+            );
+        });
+    }
+
+    #[pg_test]
+    fn test_from_spi_row_by_position() {
+        Spi::execute(|client| {
+            let pairs = client
+                .select("SELECT 42::bigint, 'answer'::varchar", None, None)
+                .rows::<Pair>()
+                .collect::<Result<Vec<_>, _>>()
+                .expect("failed to decode rows");
+            assert_eq!(vec![Pair(42, "answer".into())], pairs);
+        });
+    }
+
+    #[pg_test]
+    fn test_from_spi_row_missing_rename() {
+        Spi::execute(|client| {
+            let mut rows = client
+                .select(
+                    "SELECT 1 AS id, 'Ada Lovelace' AS fullname, NULL AS nickname",
+                    None,
+                    None,
+                )
+                .rows::<Person>();
+            match rows.next() {
+                Some(Err(SpiError::Noattribute)) => (),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        });
+    }
+
+    #[pg_test]
+    fn test_from_spi_row_type_mismatch() {
+        Spi::execute(|client| {
+            let mut rows = client
+                .select(
+                    "SELECT 1.5 AS id, 'Ada Lovelace' AS full_name, NULL AS nickname",
+                    None,
+                    None,
+                )
+                .rows::<Person>();
+            match rows.next() {
+                Some(Err(SpiError::TypeMismatch {
+                    column,
+                    expected_type,
+                    actual_type,
+                })) => {
+                    assert_eq!("id", column);
+                    assert_eq!("integer", expected_type);
+                    assert_eq!("numeric", actual_type);
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+            assert!(rows.next().is_none());
+        });
+    }
+
+    #[pg_test]
+    fn test_from_spi_row_unexpected_null() {
+        Spi::execute(|client| {
+            let mut rows = client
+                .select(
+                    "SELECT NULL::int AS id, 'Ada Lovelace' AS full_name, NULL AS nickname",
+                    None,
+                    None,
+                )
+                .rows::<Person>();
+            match rows.next() {
+                Some(Err(SpiError::UnexpectedNull { column })) => assert_eq!("id", column),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        });
+    }
+
+    #[pg_test]
+    fn test_prepared_statement() {
+        let sum = Spi::connect(|client| {
+            let prepared = client.prepare("SELECT $1 + 1", &[PgBuiltInOids::INT4OID.oid()]);
+            assert_eq!(1, prepared.len());
+            let sum = (1..=3)
+                .map(|i| {
+                    client
+                        .select(&prepared, None, Some(vec![i.into_datum()]))
+                        .first()
+                        .get_one::<i32>()
+                        .unwrap()
+                })
+                .sum::<i32>();
+            Ok(Some(sum))
+        });
+        assert_eq!(Some(9), sum);
+    }
+
+    #[pg_test]
+    fn test_prepared_statement_null_argument() {
+        let is_null = Spi::connect(|client| {
+            let prepared = client.prepare("SELECT $1 IS NULL", &[PgBuiltInOids::TEXTOID.oid()]);
+            Ok(client
+                .select(&prepared, None, Some(vec![None]))
+                .first()
+                .get_one::<bool>())
+        });
+        assert_eq!(Some(true), is_null);
+    }
+
+    #[pg_test(error = "prepared statement expects 1 arguments, but 0 were provided")]
+    fn test_prepared_statement_argument_mismatch() {
+        Spi::execute(|client| {
+            let prepared = client.prepare("SELECT $1", &[PgBuiltInOids::INT4OID.oid()]);
+            client.select(&prepared, None, None);
+        });
+    }
+
+    #[pg_test]
+    fn test_prepared_statement_tuple_arguments() {
+        let concatenated = Spi::connect(|client| {
+            let prepared = client.prepare(
+                "SELECT $1 || $2::text",
+                &[PgBuiltInOids::TEXTOID.oid(), PgBuiltInOids::INT4OID.oid()],
+            );
+            Ok(client
+                .select(&prepared, None, ("answer: ", 42i32))
+                .first()
+                .get_one::<String>())
+        });
+        assert_eq!(Some("answer: 42".to_string()), concatenated);
+    }
+
+    #[pg_test(error = "prepared statement expects 2 arguments, but 1 were provided")]
+    fn test_prepared_statement_tuple_argument_count_mismatch() {
+        Spi::execute(|client| {
+            let prepared = client.prepare(
+                "SELECT $1 || $2::text",
+                &[PgBuiltInOids::TEXTOID.oid(), PgBuiltInOids::INT4OID.oid()],
+            );
+            client.select(&prepared, None, ("answer: ",));
+        });
+    }
+
+    #[pg_test(
+        error = "prepared statement expects argument $1 to be of type integer, but it is of type text"
+    )]
+    fn test_prepared_statement_tuple_argument_type_mismatch() {
+        Spi::execute(|client| {
+            let prepared = client.prepare("SELECT $1 + 1", &[PgBuiltInOids::INT4OID.oid()]);
+            client.select(&prepared, None, ("one",));
+        });
+    }
+
+    #[pg_test]
+    fn test_owned_prepared_statement() {
+        static PLAN: once_cell::sync::OnceCell<OwnedPreparedStatement> =
+            once_cell::sync::OnceCell::new();
+
+        for i in 0..3i64 {
+            let result = Spi::connect(|client| {
+                let plan = PLAN.get_or_init(|| {
+                    client
+                        .prepare("SELECT $1 * 2", &[PgBuiltInOids::INT8OID.oid()])
+                        .keep()
+                });
+                Ok(client
+                    .select(plan, None, Some(vec![i.into_datum()]))
+                    .first()
+                    .get_one::<i64>())
+            });
+            assert_eq!(Some(i * 2), result);
+        }
+    }
+
+    #[pg_test]
+    fn test_cursor_iterates_in_batches() {
+        let sum = Spi::connect(|client| {
+            let sum = client
+                .open_cursor("SELECT * FROM generate_series(1, 1000)", None)
+                .with_batch_size(7)
+                .map(|row| row[1].value::<i32>().unwrap() as i64)
+                .sum::<i64>();
+            Ok(Some(sum))
+        });
+        assert_eq!(Some(500500), sum);
+    }
+
+    #[pg_test]
+    fn test_cursor_fetch() {
+        Spi::execute(|client| {
+            let mut cursor = client.open_cursor(
+                "SELECT * FROM generate_series(1, $1)",
+                Some(vec![(PgBuiltInOids::INT4OID.oid(), 5.into_datum())]),
+            );
+            assert_eq!(3, cursor.fetch(3).len());
+            let rest = cursor.fetch(10);
+            assert_eq!(2, rest.len());
+            assert_eq!(Some(4), rest.first().get_one::<i32>());
+            assert!(cursor.fetch(10).is_empty());
+        });
+    }
+
+    #[pg_test]
+    fn test_prepared_statement_cursor() {
+        Spi::execute(|client| {
+            let prepared = client.prepare(
+                "SELECT * FROM generate_series(1, $1)",
+                &[PgBuiltInOids::INT4OID.oid()],
+            );
+            let count = client
+                .open_cursor(&prepared, Some(vec![42.into_datum()]))
+                .count();
+            assert_eq!(42, count);
+        });
+    }
+
+    #[pg_test]
+    fn test_scroll_cursor() {
+        Spi::execute(|client| {
+            let mut cursor = client.open_cursor_with_options(
+                "SELECT * FROM generate_series(1, 10)",
+                None,
+                &SpiCursorOptions {
+                    name: None,
+                    scroll: true,
+                },
+            );
+            assert_eq!(5, cursor.fetch(5).len());
+            let previous = cursor.scroll_fetch(FetchDirection::Backward, 2);
+            assert_eq!(
+                vec![4, 3],
+                previous
+                    .map(|row| row[1].value::<i32>().unwrap())
+                    .collect::<Vec<_>>()
+            );
+
+            cursor.scroll_move(FetchDirection::Absolute, -2);
+            assert_eq!(Some(10), cursor.fetch(1).first().get_one::<i32>());
+            assert_eq!(
+                Some(1),
+                cursor
+                    .scroll_fetch(FetchDirection::Absolute, 1)
+                    .first()
+                    .get_one::<i32>()
+            );
+        });
+    }
+
+    #[pg_test(error = "cursor can only scan forward")]
+    fn test_cursor_without_scroll() {
+        Spi::execute(|client| {
+            let mut cursor = client.open_cursor("SELECT * FROM generate_series(1, 10)", None);
+            cursor.fetch(5);
+            cursor.scroll_fetch(FetchDirection::Backward, 2);
+        });
+    }
+
+    #[pg_extern]
+    fn open_series_cursor(name: &str) -> String {
+        Spi::connect(|client| {
+            let cursor = client.open_cursor_with_options(
+                "SELECT * FROM generate_series(1, 10)",
+                None,
+                &SpiCursorOptions {
+                    name: Some(name.to_string()),
+                    scroll: false,
+                },
+            );
+            Ok(Some(cursor.detach_into_name()))
+        })
+        .unwrap()
+    }
+
+    #[pg_extern]
+    fn fetch_series_cursor(name: &str, count: i64) -> Vec<i32> {
+        Spi::connect(|client| {
+            let mut cursor = client.find_cursor(name).expect("no such cursor");
+            let values = cursor
+                .fetch(count)
+                .map(|row| row[1].value::<i32>().unwrap())
+                .collect::<Vec<_>>();
+            cursor.detach_into_name();
+            Ok(Some(values))
+        })
+        .unwrap()
+    }
+
+    #[pg_test]
+    fn test_resume_named_cursor() {
+        let name = Spi::get_one::<String>("SELECT tests.open_series_cursor('series');")
+            .expect("SPI returned NULL");
+        assert_eq!("series", name);
+
+        let first = Spi::get_one::<Vec<i32>>("SELECT tests.fetch_series_cursor('series', 3);");
+        let second = Spi::get_one::<Vec<i32>>("SELECT tests.fetch_series_cursor('series', 3);");
+        assert_eq!(Some(vec![1, 2, 3]), first);
+        assert_eq!(Some(vec![4, 5, 6]), second);
+
+        Spi::execute(|client| {
+            // dropping the cursor closes it
+            client.find_cursor("series").expect("no such cursor");
+            assert!(client.find_cursor("series").is_none());
+        });
+    }
 }
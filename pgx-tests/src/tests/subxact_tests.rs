@@ -0,0 +1,147 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn count_rows() -> i64 {
+        Spi::get_one("SELECT count(*) FROM tests.subxact_table").expect("failed to get SPI result")
+    }
+
+    fn create_table() {
+        Spi::run("CREATE TABLE tests.subxact_table (id int PRIMARY KEY)");
+    }
+
+    #[pg_test]
+    fn test_subxact_commits_on_ok() {
+        create_table();
+        let result: Result<i32, Box<CaughtError>> = subxact(|| {
+            Spi::run("INSERT INTO tests.subxact_table VALUES (1)");
+            Ok(42)
+        });
+        assert_eq!(result, Ok(42));
+        assert_eq!(count_rows(), 1);
+    }
+
+    #[pg_test]
+    fn test_subxact_rolls_back_on_err() {
+        create_table();
+        let result: Result<(), Box<dyn std::error::Error>> = subxact(|| {
+            Spi::run("INSERT INTO tests.subxact_table VALUES (1)");
+            Err("changed my mind".into())
+        });
+        assert_eq!(result.unwrap_err().to_string(), "changed my mind");
+        assert_eq!(count_rows(), 0);
+    }
+
+    #[pg_test]
+    fn test_subxact_rolls_back_on_error() {
+        create_table();
+        let result: Result<(), PgError> = subxact(|| {
+            Spi::run("INSERT INTO tests.subxact_table VALUES (1)");
+            Spi::run("INSERT INTO tests.subxact_table VALUES (1)");
+            Ok(())
+        });
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.sqlerrcode(),
+            PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION as i32
+        );
+        assert_eq!(error.code(), Some(PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION));
+        assert_eq!(count_rows(), 0);
+    }
+
+    #[pg_test]
+    fn test_subxact_rolls_back_on_panic() {
+        create_table();
+        let result: Result<(), Box<CaughtError>> = subxact(|| {
+            Spi::run("INSERT INTO tests.subxact_table VALUES (1)");
+            panic!("panic in subxact")
+        });
+        assert_eq!(result.unwrap_err().message, "panic in subxact");
+        assert_eq!(count_rows(), 0);
+    }
+
+    #[pg_test]
+    fn test_subxact_partial_failure() {
+        create_table();
+        let mut loaded = 0;
+        for id in &[1, 2, 2, 3] {
+            let result: Result<(), Box<CaughtError>> = subxact(|| {
+                Spi::execute(|mut client| {
+                    client.update("INSERT INTO tests.subxact_table VALUES ($1)", None, (*id,));
+                });
+                Ok(())
+            });
+            if result.is_ok() {
+                loaded += 1;
+            }
+        }
+        assert_eq!(loaded, 3);
+        assert_eq!(count_rows(), 3);
+    }
+
+    #[pg_test]
+    fn test_subxact_nested() {
+        create_table();
+        let result: Result<(), Box<CaughtError>> = subxact(|| {
+            Spi::run("INSERT INTO tests.subxact_table VALUES (1)");
+            let inner: Result<(), Box<CaughtError>> = subxact(|| {
+                Spi::run("INSERT INTO tests.subxact_table VALUES (2)");
+                Spi::run("SELECT 1 / 0");
+                Ok(())
+            });
+            assert!(inner.is_err());
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(count_rows(), 1);
+    }
+
+    #[pg_test]
+    fn test_subxact_in_spi_connection() {
+        create_table();
+        let count = Spi::connect(|client| {
+            let result: Result<(), Box<CaughtError>> = subxact(|| {
+                Spi::run("INSERT INTO tests.subxact_table VALUES (1)");
+                Spi::run("SELECT 1 / 0");
+                Ok(())
+            });
+            assert!(result.is_err());
+            let count = client
+                .select("SELECT count(*) FROM tests.subxact_table", None, None)
+                .first()
+                .get_one::<i64>();
+            Ok(count)
+        });
+        assert_eq!(count, Some(0));
+    }
+
+    #[pg_test]
+    fn test_pg_sub_transaction_dropped() {
+        create_table();
+        {
+            let _subxact = PgSubTransaction::begin();
+            Spi::run("INSERT INTO tests.subxact_table VALUES (1)");
+        }
+        assert_eq!(count_rows(), 0);
+
+        let subxact = PgSubTransaction::begin();
+        Spi::run("INSERT INTO tests.subxact_table VALUES (1)");
+        subxact.commit();
+        assert_eq!(count_rows(), 1);
+    }
+
+    #[pg_test(error = "subtransactions must be finished in the reverse order they were started in")]
+    fn test_pg_sub_transaction_out_of_order() {
+        let outer = PgSubTransaction::begin();
+        let inner = PgSubTransaction::begin();
+        outer.commit();
+        inner.commit();
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern]
+fn support_count_to_support(request: SupportRequest) -> SupportResponse {
+    match request {
+        SupportRequest::Rows(rows) => match rows.const_arg::<i32>(0) {
+            Some(Some(n)) => rows.set_rows(n as f64),
+            _ => SupportResponse::not_handled(),
+        },
+        _ => SupportResponse::not_handled(),
+    }
+}
+
+#[pg_extern(support = support_count_to_support)]
+fn support_count_to(n: i32) -> impl Iterator<Item = i32> {
+    1..=n
+}
+
+#[pg_extern]
+fn support_simplify_to_true_support(request: SupportRequest) -> SupportResponse {
+    match request {
+        SupportRequest::Simplify(simplify) => {
+            simplify.replace_with(unsafe { PgBox::from_pg(pg_sys::makeBoolConst(true, false)) })
+        }
+        _ => SupportResponse::not_handled(),
+    }
+}
+
+/// Always `false` when it's actually called, but the planner replaces it with `true`
+#[pg_extern(support = support_simplify_to_true_support)]
+fn support_simplify_to_true(_value: i32) -> bool {
+    false
+}
+
+#[pg_extern]
+fn support_selectivity_support(request: SupportRequest) -> SupportResponse {
+    match request {
+        SupportRequest::Selectivity(selectivity) => selectivity.set_selectivity(0.1),
+        _ => SupportResponse::not_handled(),
+    }
+}
+
+#[pg_extern(support = support_selectivity_support)]
+fn support_selectivity(value: i32) -> bool {
+    value % 10 == 0
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn plan_rows(query: &str) -> f64 {
+        let explain = Spi::explain(query);
+        explain.0[0]["Plan"]["Plan Rows"]
+            .as_f64()
+            .expect("EXPLAIN has no Plan Rows")
+    }
+
+    #[pg_test]
+    fn test_support_function_is_attached() {
+        let support = Spi::get_one::<String>(
+            "SELECT prosupport::regproc::text FROM pg_proc WHERE proname = 'support_count_to'",
+        )
+        .expect("failed to get SPI result");
+        assert!(support.ends_with("support_count_to_support"));
+    }
+
+    #[pg_test]
+    fn test_support_request_rows() {
+        assert_eq!(42.0, plan_rows("SELECT * FROM support_count_to(42)"));
+    }
+
+    #[pg_test]
+    fn test_support_request_simplify() {
+        let simplified = Spi::get_one::<bool>("SELECT support_simplify_to_true(1)")
+            .expect("failed to get SPI result");
+        assert!(simplified);
+    }
+
+    #[pg_test]
+    fn test_support_request_selectivity() {
+        Spi::run("CREATE TABLE support_values AS SELECT v FROM generate_series(1, 1000) v");
+        Spi::run("ANALYZE support_values");
+        assert_eq!(
+            100.0,
+            plan_rows("SELECT * FROM support_values WHERE support_selectivity(v)")
+        );
+    }
+}
@@ -0,0 +1,292 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern]
+fn trigger_lowercase_name(trigger: &PgTrigger) -> Result<Option<PgHeapTuple>, PgHeapTupleError> {
+    let mut new = trigger
+        .new_row()
+        .expect("not fired by a row-level INSERT or UPDATE");
+    if let Some(name) = new.get_by_name::<String>("name")? {
+        new.set_by_name("name", name.to_lowercase())?;
+    }
+    Ok(Some(new))
+}
+
+#[pg_extern]
+fn trigger_skip_negative(trigger: &PgTrigger) -> Option<PgHeapTuple> {
+    let new = trigger
+        .new_row()
+        .expect("not fired by a row-level INSERT or UPDATE");
+    match new.get_by_name::<i32>("id").unwrap() {
+        Some(id) if id < 0 => None,
+        _ => Some(new),
+    }
+}
+
+#[pg_extern]
+fn trigger_unchanged(_trigger: &PgTrigger) {}
+
+#[pg_extern]
+fn trigger_mismatched_row(_trigger: &PgTrigger) -> PgHeapTuple {
+    PgHeapTuple::new_composite_type("tests.trigger_mismatched_row")
+}
+
+#[pg_extern]
+fn trigger_log_event(trigger: PgTrigger) {
+    let id = |tuple: Option<PgHeapTuple>| tuple.and_then(|t| t.get_by_name::<i32>("id").unwrap());
+    let event = format!(
+        "{} {:?} {:?} {:?} {} {:?} old={:?} new={:?}",
+        trigger.name(),
+        trigger.when(),
+        trigger.op(),
+        trigger.level(),
+        trigger.relation().name(),
+        trigger.args(),
+        id(trigger.old_row()),
+        id(trigger.new_row()),
+    );
+
+    Spi::execute(|mut client| {
+        client.update("INSERT INTO tests.trigger_log VALUES ($1)", None, (event,));
+    });
+}
+
+extension_sql!(
+    r#"
+CREATE TABLE trigger_transition_table (id int PRIMARY KEY, name text);
+CREATE TABLE trigger_transition_log (event text);
+"#,
+    name = "create_trigger_transition_table",
+);
+
+#[derive(PostgresComposite)]
+struct TriggerTransitionRow {
+    id: i32,
+    name: Option<String>,
+}
+
+#[pg_extern(requires = ["create_trigger_transition_table"])]
+#[pg_trigger(
+    name = "log_inserts",
+    table = "trigger_transition_table",
+    after,
+    events = [insert],
+    referencing(new_table = "new_rows"),
+    args = ["inserted"],
+)]
+#[pg_trigger(
+    name = "log_updates",
+    table = "trigger_transition_table",
+    after,
+    events = [update],
+    for_each = statement,
+    referencing(old_table = "old_rows", new_table = "new_rows"),
+    args = ["updated"],
+)]
+fn trigger_log_transitions(trigger: &PgTrigger) {
+    let old = trigger.old_table().map(|table| {
+        table
+            .map(|row| row.get_by_name::<String>("name").unwrap())
+            .collect::<Vec<_>>()
+    });
+    let new = trigger.new_table().map(|table| {
+        table
+            .rows::<TriggerTransitionRow>()
+            .map(|row| (row.id, row.name))
+            .collect::<Vec<_>>()
+    });
+    // a second table reuses the dropped first one's read pointer, and starts from the first row
+    if let Some(new) = &new {
+        assert_eq!(trigger.new_table().unwrap().count(), new.len());
+    }
+    let event = format!(
+        "{} {:?} {:?} old={:?} new={:?}",
+        trigger.name(),
+        trigger.level(),
+        trigger.args(),
+        old,
+        new
+    );
+
+    Spi::execute(|mut client| {
+        client.update(
+            "INSERT INTO trigger_transition_log VALUES ($1)",
+            None,
+            (event,),
+        );
+    });
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn create_table() {
+        Spi::run("CREATE TABLE tests.trigger_table (id int PRIMARY KEY, name text)");
+        Spi::run("CREATE TABLE tests.trigger_log (event text)");
+    }
+
+    fn events() -> Vec<String> {
+        Spi::connect(|client| {
+            let events = client
+                .select(
+                    "SELECT event FROM tests.trigger_log ORDER BY ctid",
+                    None,
+                    None,
+                )
+                .map(|row| row.by_ordinal(1).unwrap().value::<String>().unwrap())
+                .collect::<Vec<_>>();
+            Ok(Some(events))
+        })
+        .unwrap()
+    }
+
+    #[pg_test]
+    fn test_trigger_returns_trigger() {
+        let rettype = Spi::get_one::<String>(
+            "SELECT prorettype::regtype::text FROM pg_proc WHERE proname = 'trigger_log_event'",
+        );
+        assert_eq!(rettype, Some("trigger".to_string()));
+    }
+
+    #[pg_test]
+    fn test_trigger_modifies_new() {
+        create_table();
+        Spi::run(
+            "CREATE TRIGGER lowercase BEFORE INSERT OR UPDATE ON tests.trigger_table \
+             FOR EACH ROW EXECUTE PROCEDURE trigger_lowercase_name()",
+        );
+        Spi::run("INSERT INTO tests.trigger_table VALUES (1, 'Brandy')");
+        assert_eq!(
+            Spi::get_one::<String>("SELECT name FROM tests.trigger_table WHERE id = 1"),
+            Some("brandy".to_string())
+        );
+
+        Spi::run("UPDATE tests.trigger_table SET name = 'SPOT' WHERE id = 1");
+        assert_eq!(
+            Spi::get_one::<String>("SELECT name FROM tests.trigger_table WHERE id = 1"),
+            Some("spot".to_string())
+        );
+    }
+
+    #[pg_test]
+    fn test_trigger_skips_row() {
+        create_table();
+        Spi::run(
+            "CREATE TRIGGER skip_negative BEFORE INSERT ON tests.trigger_table \
+             FOR EACH ROW EXECUTE PROCEDURE trigger_skip_negative()",
+        );
+        Spi::run("INSERT INTO tests.trigger_table VALUES (1, 'a'), (-1, 'b'), (2, 'c')");
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM tests.trigger_table"),
+            Some(2)
+        );
+    }
+
+    #[pg_test]
+    fn test_trigger_unchanged() {
+        create_table();
+        Spi::run(
+            "CREATE TRIGGER unchanged BEFORE INSERT OR UPDATE OR DELETE ON tests.trigger_table \
+             FOR EACH ROW EXECUTE PROCEDURE trigger_unchanged()",
+        );
+        Spi::run("INSERT INTO tests.trigger_table VALUES (1, 'a')");
+        Spi::run("UPDATE tests.trigger_table SET name = 'b'");
+        assert_eq!(
+            Spi::get_one::<String>("SELECT name FROM tests.trigger_table WHERE id = 1"),
+            Some("b".to_string())
+        );
+
+        Spi::run("DELETE FROM tests.trigger_table");
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM tests.trigger_table"),
+            Some(0)
+        );
+    }
+
+    #[pg_test(
+        error = "returned row structure does not match the structure of the triggering table"
+    )]
+    fn test_trigger_mismatched_row() {
+        create_table();
+        Spi::run("CREATE TYPE tests.trigger_mismatched_row AS (id int)");
+        Spi::run(
+            "CREATE TRIGGER mismatched BEFORE INSERT ON tests.trigger_table \
+             FOR EACH ROW EXECUTE PROCEDURE trigger_mismatched_row()",
+        );
+        Spi::run("INSERT INTO tests.trigger_table VALUES (1, 'a')");
+    }
+
+    #[pg_test]
+    fn test_trigger_context() {
+        create_table();
+        Spi::run(
+            "CREATE TRIGGER log_rows AFTER INSERT OR UPDATE OR DELETE ON tests.trigger_table \
+             FOR EACH ROW EXECUTE PROCEDURE trigger_log_event('a', 'b')",
+        );
+        Spi::run(
+            "CREATE TRIGGER log_truncate BEFORE TRUNCATE ON tests.trigger_table \
+             FOR EACH STATEMENT EXECUTE PROCEDURE trigger_log_event()",
+        );
+        Spi::run("INSERT INTO tests.trigger_table VALUES (1, 'a')");
+        Spi::run("UPDATE tests.trigger_table SET id = 2");
+        Spi::run("DELETE FROM tests.trigger_table");
+        Spi::run("TRUNCATE tests.trigger_table");
+
+        assert_eq!(
+            events(),
+            vec![
+                r#"log_rows After Insert Row trigger_table ["a", "b"] old=None new=Some(1)"#,
+                r#"log_rows After Update Row trigger_table ["a", "b"] old=Some(1) new=Some(2)"#,
+                r#"log_rows After Delete Row trigger_table ["a", "b"] old=Some(2) new=None"#,
+                r#"log_truncate Before Truncate Statement trigger_table [] old=None new=None"#,
+            ]
+        );
+    }
+
+    #[pg_test]
+    fn test_trigger_declared() {
+        let triggers = Spi::get_one::<String>(
+            "SELECT string_agg(pg_get_triggerdef(oid), '; ' ORDER BY tgname) FROM pg_trigger \
+             WHERE tgrelid = 'trigger_transition_table'::regclass",
+        )
+        .expect("no triggers were declared");
+        assert!(triggers.contains("CREATE TRIGGER log_inserts AFTER INSERT"));
+        assert!(triggers.contains("REFERENCING NEW TABLE AS new_rows FOR EACH STATEMENT"));
+        assert!(triggers.contains("CREATE TRIGGER log_updates AFTER UPDATE"));
+        assert!(triggers.contains("REFERENCING OLD TABLE AS old_rows NEW TABLE AS new_rows"));
+        assert!(triggers.contains("trigger_log_transitions('updated')"));
+    }
+
+    #[pg_test]
+    fn test_trigger_transition_tables() {
+        Spi::run("INSERT INTO trigger_transition_table VALUES (1, 'a'), (2, NULL)");
+        Spi::run("UPDATE trigger_transition_table SET name = 'b' WHERE id = 1");
+
+        let events = Spi::connect(|client| {
+            let events = client
+                .select(
+                    "SELECT event FROM trigger_transition_log ORDER BY ctid",
+                    None,
+                    None,
+                )
+                .map(|row| row.by_ordinal(1).unwrap().value::<String>().unwrap())
+                .collect::<Vec<_>>();
+            Ok(Some(events))
+        })
+        .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                r#"log_inserts Statement ["inserted"] old=None new=Some([(1, Some("a")), (2, None)])"#,
+                r#"log_updates Statement ["updated"] old=Some([Some("a")]) new=Some([(1, Some("b"))])"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::cstr_core::CStr;
+use pgx::*;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, PostgresType)]
+#[inoutfuncs]
+#[pgx(typmod)]
+pub struct Vector {
+    values: Vec<f32>,
+}
+
+impl InOutFuncs for Vector {
+    fn input(input: &CStr) -> Self {
+        let input = input.to_str().unwrap();
+        let values = input
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .map(|value| f32::from_str(value.trim()).expect("invalid vector element"))
+            .collect();
+        Vector { values }
+    }
+
+    fn output(&self, buffer: &mut StringInfo) {
+        let values = self
+            .values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>();
+        buffer.push_str(&format!("[{}]", values.join(",")))
+    }
+}
+
+impl TypmodInOutFuncs for Vector {
+    fn typmod_in(modifiers: Array<&CStr>) -> i32 {
+        let modifiers = modifiers.iter_deny_null().collect::<Vec<_>>();
+        if modifiers.len() != 1 {
+            error!("invalid type modifier for vector");
+        }
+        match i32::from_str(modifiers[0].to_str().unwrap()) {
+            Ok(dimensions) if dimensions > 0 => dimensions,
+            _ => error!("dimensions for type vector must be at least 1"),
+        }
+    }
+
+    fn typmod_out(typmod: i32) -> String {
+        format!("({})", typmod)
+    }
+
+    fn apply_typmod(&mut self, typmod: i32, _is_explicit: bool) {
+        if self.values.len() != typmod as usize {
+            error!("expected {} dimensions, not {}", typmod, self.values.len());
+        }
+    }
+}
+
+#[pg_extern]
+fn vector_declared_dimensions(_vector: Vector, fcinfo: pg_sys::FunctionCallInfo) -> i32 {
+    unsafe { get_getarg_typmod(fcinfo, 0) }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use super::Vector;
+    use pgx::*;
+
+    #[pg_test]
+    fn test_typmod_input() {
+        let vector = Spi::get_one::<Vector>("SELECT '[1, 2, 3]'::Vector(3);")
+            .expect("failed to get SPI result");
+        assert_eq!(
+            Vector {
+                values: vec![1.0, 2.0, 3.0]
+            },
+            vector
+        );
+    }
+
+    #[pg_test(error = "expected 2 dimensions, not 3")]
+    fn test_typmod_input_mismatch() {
+        Spi::get_one::<Vector>("SELECT '[1, 2, 3]'::Vector(2);");
+    }
+
+    #[pg_test(error = "dimensions for type vector must be at least 1")]
+    fn test_typmod_in_invalid() {
+        Spi::get_one::<Vector>("SELECT '[1]'::Vector(0);");
+    }
+
+    #[pg_test]
+    fn test_typmod_out() {
+        Spi::run("CREATE TABLE typmod_vectors (v Vector(3));");
+        let ty = Spi::get_one::<String>(
+            "SELECT format_type(atttypid, atttypmod) FROM pg_attribute WHERE attrelid = 'typmod_vectors'::regclass AND attname = 'v';",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!("vector(3)", ty);
+    }
+
+    #[pg_test(error = "expected 3 dimensions, not 1")]
+    fn test_typmod_coerce_on_assignment() {
+        Spi::run("CREATE TABLE typmod_vectors (v Vector(3));");
+        Spi::run("INSERT INTO typmod_vectors VALUES ('[1, 2, 3]'::Vector);");
+        Spi::run("INSERT INTO typmod_vectors VALUES ('[1]'::Vector);");
+    }
+
+    #[pg_test]
+    fn test_get_getarg_typmod() {
+        Spi::run("CREATE TABLE typmod_vectors (v Vector(3));");
+        Spi::run("INSERT INTO typmod_vectors VALUES ('[1, 2, 3]');");
+        let dimensions =
+            Spi::get_one::<i32>("SELECT vector_declared_dimensions(v) FROM typmod_vectors;")
+                .expect("failed to get SPI result");
+        assert_eq!(3, dimensions);
+
+        let dimensions = Spi::get_one::<i32>("SELECT vector_declared_dimensions('[1]');")
+            .expect("failed to get SPI result");
+        assert_eq!(-1, dimensions);
+    }
+}
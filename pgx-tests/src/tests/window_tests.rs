@@ -0,0 +1,119 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern(window)]
+fn window_row_number(window: WindowObject) -> i64 {
+    window.current_position() + 1
+}
+
+#[pg_extern(window)]
+fn window_partition_count(window: WindowObject) -> i64 {
+    window.partition_row_count()
+}
+
+#[pg_extern(window)]
+fn window_previous(_value: Option<i32>, window: WindowObject) -> Option<i32> {
+    window
+        .arg_in_partition(0, -1, WindowSeek::Current, false)
+        .flatten()
+}
+
+#[pg_extern(window)]
+fn window_current_plus_last_in_frame(value: i32, window: WindowObject) -> Option<i32> {
+    window
+        .arg_in_frame::<i32>(0, 0, WindowSeek::Tail, false)
+        .flatten()
+        .map(|last| value + last)
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[cfg(not(feature = "pg10"))]
+    #[pg_test]
+    fn test_window_is_a_window_function() {
+        let kind = Spi::get_one::<String>(
+            "SELECT prokind::text FROM pg_proc WHERE proname = 'window_row_number'",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!("w", kind);
+    }
+
+    // `pg_proc.prokind` replaced `proiswindow` in Postgres 11
+    #[cfg(feature = "pg10")]
+    #[pg_test]
+    fn test_window_is_a_window_function() {
+        let is_window = Spi::get_one::<bool>(
+            "SELECT proiswindow FROM pg_proc WHERE proname = 'window_row_number'",
+        )
+        .expect("failed to get SPI result");
+        assert!(is_window);
+    }
+
+    #[pg_test]
+    fn test_window_current_position() {
+        let numbers = Spi::get_one::<Vec<i64>>(
+            "SELECT array_agg(n ORDER BY v) FROM (
+                SELECT v, window_row_number() OVER (PARTITION BY v % 2 ORDER BY v) n
+                FROM generate_series(1, 5) v
+            ) t",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(vec![1, 1, 2, 2, 3], numbers);
+    }
+
+    #[pg_test]
+    fn test_window_partition_row_count() {
+        let counts = Spi::get_one::<Vec<i64>>(
+            "SELECT array_agg(c ORDER BY v) FROM (
+                SELECT v, window_partition_count() OVER (PARTITION BY v % 2) c
+                FROM generate_series(1, 5) v
+            ) t",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(vec![3, 2, 3, 2, 3], counts);
+    }
+
+    #[pg_test]
+    fn test_window_arg_in_partition() {
+        let previous = Spi::get_one::<Vec<Option<i32>>>(
+            "SELECT array_agg(p ORDER BY v) FROM (
+                SELECT v, window_previous(v) OVER (ORDER BY v) p
+                FROM generate_series(1, 3) v
+            ) t",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(vec![None, Some(1), Some(2)], previous);
+    }
+
+    #[pg_test]
+    fn test_window_arg_in_frame() {
+        let sums = Spi::get_one::<Vec<i32>>(
+            "SELECT array_agg(s ORDER BY v) FROM (
+                SELECT v, window_current_plus_last_in_frame(v) OVER (ORDER BY v) s
+                FROM generate_series(1, 3) v
+            ) t",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(vec![2, 4, 6], sums);
+    }
+
+    #[pg_test]
+    fn test_window_null_argument() {
+        let sums = Spi::get_one::<Vec<Option<i32>>>(
+            "SELECT array_agg(s ORDER BY v) FROM (
+                SELECT v, window_current_plus_last_in_frame(v) OVER (ORDER BY v) s
+                FROM (VALUES (1), (NULL), (3)) t(v)
+            ) t",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(vec![Some(2), Some(6), None], sums);
+    }
+}
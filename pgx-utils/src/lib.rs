@@ -177,11 +177,20 @@ pub enum ExternArgs {
     ParallelSafe,
     ParallelUnsafe,
     ParallelRestricted,
+    Window,
+    SecurityDefiner,
+    SecurityInvoker,
+    Leakproof,
+    CalledOnNullInput,
     Error(String),
     Schema(String),
     Name(String),
     Cost(String),
+    Rows(String),
     Requires(Vec<PositioningRef>),
+    Support(PositioningRef),
+    /// A `SET name TO value, ...` clause, with each value quoted as its own literal
+    Set(String, Vec<String>),
 }
 
 impl core::fmt::Display for ExternArgs {
@@ -195,12 +204,22 @@ impl core::fmt::Display for ExternArgs {
             ExternArgs::ParallelSafe => write!(f, "PARALLEL SAFE"),
             ExternArgs::ParallelUnsafe => write!(f, "PARALLEL UNSAFE"),
             ExternArgs::ParallelRestricted => write!(f, "PARALLEL RESTRICTED"),
+            ExternArgs::Window => write!(f, "WINDOW"),
+            ExternArgs::SecurityDefiner => write!(f, "SECURITY DEFINER"),
+            ExternArgs::SecurityInvoker => write!(f, "SECURITY INVOKER"),
+            ExternArgs::Leakproof => write!(f, "LEAKPROOF"),
+            ExternArgs::CalledOnNullInput => write!(f, "CALLED ON NULL INPUT"),
             ExternArgs::Error(_) => Ok(()),
             ExternArgs::NoGuard => Ok(()),
             ExternArgs::Schema(_) => Ok(()),
             ExternArgs::Name(_) => Ok(()),
             ExternArgs::Cost(cost) => write!(f, "COST {}", cost),
+            ExternArgs::Rows(rows) => write!(f, "ROWS {}", rows),
             ExternArgs::Requires(_) => Ok(()),
+            // rendered separately, as the support function's name must be resolved
+            ExternArgs::Support(_) => Ok(()),
+            // rendered on their own lines, as their values are case-sensitive
+            ExternArgs::Set(_, _) => Ok(()),
         }
     }
 }
@@ -217,6 +236,11 @@ impl ToTokens for ExternArgs {
             ExternArgs::ParallelSafe => tokens.append(format_ident!("ParallelSafe")),
             ExternArgs::ParallelUnsafe => tokens.append(format_ident!("ParallelUnsafe")),
             ExternArgs::ParallelRestricted => tokens.append(format_ident!("ParallelRestricted")),
+            ExternArgs::Window => tokens.append(format_ident!("Window")),
+            ExternArgs::SecurityDefiner => tokens.append(format_ident!("SecurityDefiner")),
+            ExternArgs::SecurityInvoker => tokens.append(format_ident!("SecurityInvoker")),
+            ExternArgs::Leakproof => tokens.append(format_ident!("Leakproof")),
+            ExternArgs::CalledOnNullInput => tokens.append(format_ident!("CalledOnNullInput")),
             ExternArgs::Error(_s) => {
                 tokens.append_all(
                     quote! {
@@ -257,6 +281,30 @@ impl ToTokens for ExternArgs {
                     .to_token_stream(),
                 );
             }
+            ExternArgs::Rows(_s) => {
+                tokens.append_all(
+                    quote! {
+                        Rows(String::from("#_s"))
+                    }
+                    .to_token_stream(),
+                );
+            }
+            ExternArgs::Set(name, values) => {
+                tokens.append_all(
+                    quote! {
+                        Set(String::from(#name), vec![#(String::from(#values)),*])
+                    }
+                    .to_token_stream(),
+                );
+            }
+            ExternArgs::Support(item) => {
+                tokens.append_all(
+                    quote! {
+                        Support(#item)
+                    }
+                    .to_token_stream(),
+                );
+            }
         }
     }
 }
@@ -296,6 +344,51 @@ pub fn parse_extern_attributes(attr: TokenStream) -> HashSet<ExternArgs> {
                     "parallel_safe" => args.insert(ExternArgs::ParallelSafe),
                     "parallel_unsafe" => args.insert(ExternArgs::ParallelUnsafe),
                     "parallel_restricted" => args.insert(ExternArgs::ParallelRestricted),
+                    "window" => args.insert(ExternArgs::Window),
+                    "security_definer" => args.insert(ExternArgs::SecurityDefiner),
+                    "security_invoker" => args.insert(ExternArgs::SecurityInvoker),
+                    "leakproof" => args.insert(ExternArgs::Leakproof),
+                    "called_on_null_input" => args.insert(ExternArgs::CalledOnNullInput),
+                    "rows" => {
+                        let _punc = itr.next().unwrap();
+                        let literal = itr.next().unwrap();
+                        args.insert(ExternArgs::Rows(literal.to_string()))
+                    }
+                    "set" => match itr.next() {
+                        Some(TokenTree::Group(g)) => {
+                            let mut clauses = g.stream().into_iter();
+                            let mut inserted = false;
+                            while let Some(name) = clauses.next() {
+                                if let TokenTree::Punct(_) = name {
+                                    continue;
+                                }
+                                let _punc = clauses.next().unwrap();
+                                let literals = match clauses.next().unwrap() {
+                                    // a list of values, such as `search_path = ["public", "pg_temp"]`
+                                    TokenTree::Group(list) => list
+                                        .stream()
+                                        .into_iter()
+                                        .filter(|value| !matches!(value, TokenTree::Punct(_)))
+                                        .collect::<Vec<_>>(),
+                                    value => vec![value],
+                                };
+                                let values = literals
+                                    .into_iter()
+                                    .map(|value| {
+                                        let value = value.to_string();
+                                        let value =
+                                            unescape::unescape(&value).expect("failed to unescape");
+
+                                        // trim leading/trailing quotes around the literal
+                                        value[1..value.len() - 1].to_string()
+                                    })
+                                    .collect();
+                                inserted |= args.insert(ExternArgs::Set(name.to_string(), values));
+                            }
+                            inserted
+                        }
+                        _ => false,
+                    },
                     "error" => {
                         let _punc = itr.next().unwrap();
                         let literal = itr.next().unwrap();
@@ -326,6 +419,29 @@ pub fn parse_extern_attributes(attr: TokenStream) -> HashSet<ExternArgs> {
                         let name = name[1..name.len() - 1].to_string();
                         args.insert(ExternArgs::Name(name.to_string()))
                     }
+                    "support" => {
+                        let _punc = itr.next().unwrap();
+                        let mut path = String::new();
+                        let mut name = None;
+                        for t in itr.by_ref() {
+                            match t {
+                                TokenTree::Punct(p) if p.as_char() == ',' => break,
+                                TokenTree::Literal(literal) => {
+                                    let literal = literal.to_string();
+                                    let literal =
+                                        unescape::unescape(&literal).expect("failed to unescape");
+
+                                    // trim leading/trailing quotes around the literal
+                                    name = Some(literal[1..literal.len() - 1].to_string());
+                                }
+                                other => path.push_str(&other.to_string()),
+                            }
+                        }
+                        args.insert(ExternArgs::Support(match name {
+                            Some(name) => PositioningRef::Name(name),
+                            None => PositioningRef::FullPath(path),
+                        }))
+                    }
                     // Recognized, but not handled as an extern argument
                     "sql" => {
                         let _punc = itr.next().unwrap();
@@ -342,6 +458,70 @@ pub fn parse_extern_attributes(attr: TokenStream) -> HashSet<ExternArgs> {
     args
 }
 
+/// The `T` of a `Result<T, E>` type, including aliases like `eyre::Result<T>`
+pub fn result_ok_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(ty) => {
+            let segment = ty.path.segments.last()?;
+            if segment.ident != "Result" {
+                return None;
+            }
+            match &segment.arguments {
+                PathArguments::AngleBracketed(a) => match a.args.first()? {
+                    GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Is `ty` a trigger function's `PgTrigger`, or a reference to one?
+pub fn is_pg_trigger(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(ty) => is_pg_trigger(&ty.elem),
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "PgTrigger"),
+        _ => false,
+    }
+}
+
+/// Is `ty` an event trigger function's `PgEventTrigger`, or a reference to one?
+pub fn is_pg_event_trigger(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(ty) => is_pg_event_trigger(&ty.elem),
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "PgEventTrigger"),
+        _ => false,
+    }
+}
+
+/// Is `ty` a window function's `pgx::WindowObject`, rather than the raw `pg_sys::WindowObject`?
+pub fn is_window_object(ty: &Type) -> bool {
+    match ty {
+        Type::Path(ty) => {
+            !ty.path
+                .segments
+                .iter()
+                .any(|segment| segment.ident == "pg_sys")
+                && ty
+                    .path
+                    .segments
+                    .last()
+                    .map_or(false, |segment| segment.ident == "WindowObject")
+        }
+        _ => false,
+    }
+}
+
 pub fn categorize_return_type(func: &ItemFn) -> CategorizedType {
     let rt = &func.sig.output;
 
@@ -380,7 +560,8 @@ pub fn categorize_type(ty: &Type) -> CategorizedType {
                         }
                     }
                 }
-                if segment_ident == "Box" {
+                // a `Result`'s `Err` is raised as an ERROR, so it's categorized by its `Ok` type
+                if segment_ident == "Box" || segment_ident == "Result" {
                     match &segment.arguments {
                         PathArguments::AngleBracketed(a) => match a.args.first().unwrap() {
                             GenericArgument::Type(ty) => return categorize_type(ty),
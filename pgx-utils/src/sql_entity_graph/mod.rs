@@ -6,9 +6,11 @@ pub(crate) mod pg_extern;
 pub(crate) mod pgx_attribute;
 pub(crate) mod pgx_sql;
 pub(crate) mod positioning_ref;
+pub(crate) mod postgres_composite;
 pub(crate) mod postgres_enum;
 pub(crate) mod postgres_hash;
 pub(crate) mod postgres_ord;
+pub(crate) mod postgres_range;
 pub(crate) mod postgres_type;
 pub(crate) mod schema;
 pub(crate) mod to_sql;
@@ -24,15 +26,23 @@ pub use extension_sql::{
 };
 pub use mapping::{RustSourceOnlySqlMapping, RustSqlMapping};
 pub use pg_extern::{
-    entity::{PgExternArgumentEntity, PgExternEntity, PgExternReturnEntity, PgOperatorEntity},
-    NameMacro, PgExtern, PgExternArgument, PgOperator,
+    entity::{
+        PgEventTriggerEntity, PgExternArgumentEntity, PgExternEntity, PgExternReturnEntity,
+        PgOperatorEntity, PgTriggerEntity,
+    },
+    NameMacro, PgEventTrigger, PgExtern, PgExternArgument, PgOperator, PgTrigger,
 };
 pub use pgx_sql::PgxSql;
 pub use positioning_ref::PositioningRef;
+pub use postgres_composite::{
+    entity::{PostgresCompositeEntity, PostgresCompositeFieldEntity},
+    PostgresComposite,
+};
 pub use postgres_enum::{entity::PostgresEnumEntity, PostgresEnum};
 pub use postgres_hash::{entity::PostgresHashEntity, PostgresHash};
 pub use postgres_ord::{entity::PostgresOrdEntity, PostgresOrd};
-pub use postgres_type::{entity::PostgresTypeEntity, PostgresType};
+pub use postgres_range::{entity::PostgresRangeEntity, PostgresRange};
+pub use postgres_type::{entity::PostgresTypeEntity, PostgresType, PostgresTypeLayout, TypmodFns};
 pub use schema::{entity::SchemaEntity, Schema};
 pub use to_sql::{entity::ToSqlConfigEntity, ToSql, ToSqlConfig};
 
@@ -70,6 +80,8 @@ pub enum SqlGraphEntity {
     Enum(PostgresEnumEntity),
     Ord(PostgresOrdEntity),
     Hash(PostgresHashEntity),
+    Range(PostgresRangeEntity),
+    Composite(PostgresCompositeEntity),
     Aggregate(PgAggregateEntity),
 }
 
@@ -102,6 +114,8 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.dot_identifier(),
             SqlGraphEntity::Ord(item) => item.dot_identifier(),
             SqlGraphEntity::Hash(item) => item.dot_identifier(),
+            SqlGraphEntity::Range(item) => item.dot_identifier(),
+            SqlGraphEntity::Composite(item) => item.dot_identifier(),
             SqlGraphEntity::Aggregate(item) => item.dot_identifier(),
             SqlGraphEntity::ExtensionRoot(item) => item.dot_identifier(),
         }
@@ -117,6 +131,8 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.rust_identifier(),
             SqlGraphEntity::Ord(item) => item.rust_identifier(),
             SqlGraphEntity::Hash(item) => item.rust_identifier(),
+            SqlGraphEntity::Range(item) => item.rust_identifier(),
+            SqlGraphEntity::Composite(item) => item.rust_identifier(),
             SqlGraphEntity::Aggregate(item) => item.rust_identifier(),
             SqlGraphEntity::ExtensionRoot(item) => item.rust_identifier(),
         }
@@ -132,6 +148,8 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.file(),
             SqlGraphEntity::Ord(item) => item.file(),
             SqlGraphEntity::Hash(item) => item.file(),
+            SqlGraphEntity::Range(item) => item.file(),
+            SqlGraphEntity::Composite(item) => item.file(),
             SqlGraphEntity::Aggregate(item) => item.file(),
             SqlGraphEntity::ExtensionRoot(item) => item.file(),
         }
@@ -147,6 +165,8 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.line(),
             SqlGraphEntity::Ord(item) => item.line(),
             SqlGraphEntity::Hash(item) => item.line(),
+            SqlGraphEntity::Range(item) => item.line(),
+            SqlGraphEntity::Composite(item) => item.line(),
             SqlGraphEntity::Aggregate(item) => item.line(),
             SqlGraphEntity::ExtensionRoot(item) => item.line(),
         }
@@ -172,16 +192,12 @@ impl ToSql for SqlGraphEntity {
                 if context.graph.neighbors_undirected(context.externs.get(item).unwrap().clone()).any(|neighbor| {
                     let neighbor_item = &context.graph[neighbor];
                     match neighbor_item {
-                        SqlGraphEntity::Type(PostgresTypeEntity { in_fn, in_fn_module_path, out_fn, out_fn_module_path, .. }) => {
-                            let is_in_fn = item.full_path.starts_with(in_fn_module_path) && item.full_path.ends_with(in_fn);
-                            if is_in_fn {
-                                tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is an in_fn.");
-                            }
-                            let is_out_fn = item.full_path.starts_with(out_fn_module_path) && item.full_path.ends_with(out_fn);
-                            if is_out_fn {
-                                tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is an out_fn.");
+                        SqlGraphEntity::Type(ty) => {
+                            let is_support_fn = ty.is_support_fn(item);
+                            if is_support_fn {
+                                tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is emitted by the type.");
                             }
-                            is_in_fn || is_out_fn
+                            is_support_fn
                         },
                         _ => false,
                     }
@@ -208,6 +224,14 @@ impl ToSql for SqlGraphEntity {
                 .to_sql_config
                 .to_sql(self, context)
                 .unwrap_or_else(|| item.to_sql(context)),
+            SqlGraphEntity::Range(item) => item
+                .to_sql_config
+                .to_sql(self, context)
+                .unwrap_or_else(|| item.to_sql(context)),
+            SqlGraphEntity::Composite(item) => item
+                .to_sql_config
+                .to_sql(self, context)
+                .unwrap_or_else(|| item.to_sql(context)),
             SqlGraphEntity::Aggregate(item) => item
                 .to_sql_config
                 .to_sql(self, context)
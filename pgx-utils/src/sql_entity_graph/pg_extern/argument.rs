@@ -1,6 +1,7 @@
 use std::ops::Deref;
 
-use crate::anonymonize_lifetimes;
+use super::composite_type::composite_type_name;
+use crate::{anonymonize_lifetimes, is_pg_event_trigger, is_pg_trigger, is_window_object};
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens, TokenStreamExt};
 use syn::{
@@ -78,7 +79,12 @@ impl PgExternArgument {
             _ => None,
         };
 
-        // We special case ignore `*mut pg_sys::FunctionCallInfoData`
+        // We special case ignore `*mut pg_sys::FunctionCallInfoData`, a window function's
+        // `pgx::WindowObject`, and a trigger or event trigger function's `pgx::PgTrigger` or
+        // `pgx::PgEventTrigger`
+        if is_pg_trigger(&true_ty) || is_pg_event_trigger(&true_ty) || is_window_object(&true_ty) {
+            return Ok(None);
+        }
         match true_ty {
             syn::Type::Reference(ref mut ty_ref) => {
                 if let Some(ref mut lifetime) = &mut ty_ref.lifetime {
@@ -305,6 +311,7 @@ impl ToTokens for PgExternArgument {
             _ => (),
         };
         let ty_string = ty.to_token_stream().to_string().replace(" ", "");
+        let composite_type = composite_type_name(&ty).into_iter();
 
         let quoted = quote! {
             ::pgx::utils::sql_entity_graph::PgExternArgumentEntity {
@@ -321,6 +328,7 @@ impl ToTokens for PgExternArgument {
                 is_optional: #found_optional,
                 is_variadic: #found_variadic,
                 default: None #( .unwrap_or(Some(#default)) )*,
+                composite_type: None #( .unwrap_or(Some(#composite_type)) )*,
             }
         };
         tokens.append_all(quoted);
@@ -18,11 +18,20 @@ pub enum Attribute {
     ParallelSafe,
     ParallelUnsafe,
     ParallelRestricted,
+    Window,
+    SecurityDefiner,
+    SecurityInvoker,
+    Leakproof,
+    CalledOnNullInput,
     Error(syn::LitStr),
     Schema(syn::LitStr),
     Name(syn::LitStr),
     Cost(syn::Expr),
+    Rows(syn::Expr),
+    /// Each setting's value is one literal, or a list of them given as `name = ["a", "b"]`
+    Set(Vec<(syn::Ident, Vec<syn::LitStr>)>),
     Requires(Punctuated<PositioningRef, Token![,]>),
+    Support(PositioningRef),
     Sql(ToSqlConfig),
 }
 
@@ -44,6 +53,13 @@ impl Attribute {
             Attribute::ParallelRestricted => {
                 quote! { ::pgx::utils::ExternArgs::ParallelRestricted }
             }
+            Attribute::Window => quote! { ::pgx::utils::ExternArgs::Window },
+            Attribute::SecurityDefiner => quote! { ::pgx::utils::ExternArgs::SecurityDefiner },
+            Attribute::SecurityInvoker => quote! { ::pgx::utils::ExternArgs::SecurityInvoker },
+            Attribute::Leakproof => quote! { ::pgx::utils::ExternArgs::Leakproof },
+            Attribute::CalledOnNullInput => {
+                quote! { ::pgx::utils::ExternArgs::CalledOnNullInput }
+            }
             Attribute::Error(s) => {
                 quote! { ::pgx::utils::ExternArgs::Error(String::from(#s)) }
             }
@@ -56,6 +72,16 @@ impl Attribute {
             Attribute::Cost(s) => {
                 quote! { ::pgx::utils::ExternArgs::Cost(format!("{}", #s)) }
             }
+            Attribute::Rows(s) => {
+                quote! { ::pgx::utils::ExternArgs::Rows(format!("{}", #s)) }
+            }
+            Attribute::Set(clauses) => {
+                let clauses_iter = clauses.iter().map(|(name, values)| {
+                    let name = name.to_string();
+                    quote! { ::pgx::utils::ExternArgs::Set(String::from(#name), vec![#(String::from(#values)),*]) }
+                });
+                quote! { #(#clauses_iter),* }
+            }
             Attribute::Requires(items) => {
                 let items_iter = items
                     .iter()
@@ -63,6 +89,9 @@ impl Attribute {
                     .collect::<Vec<_>>();
                 quote! { ::pgx::utils::ExternArgs::Requires(vec![#(#items_iter),*],) }
             }
+            Attribute::Support(item) => {
+                quote! { ::pgx::utils::ExternArgs::Support(#item) }
+            }
             // This attribute is handled separately
             Attribute::Sql(_) => {
                 quote! {}
@@ -89,6 +118,11 @@ impl ToTokens for Attribute {
             Attribute::ParallelRestricted => {
                 quote! { parallel_restricted }
             }
+            Attribute::Window => quote! { window },
+            Attribute::SecurityDefiner => quote! { security_definer },
+            Attribute::SecurityInvoker => quote! { security_invoker },
+            Attribute::Leakproof => quote! { leakproof },
+            Attribute::CalledOnNullInput => quote! { called_on_null_input },
             Attribute::Error(s) => {
                 quote! { error = #s }
             }
@@ -101,6 +135,18 @@ impl ToTokens for Attribute {
             Attribute::Cost(s) => {
                 quote! { cost = #s }
             }
+            Attribute::Rows(s) => {
+                quote! { rows = #s }
+            }
+            Attribute::Set(clauses) => {
+                let clauses_iter = clauses
+                    .iter()
+                    .map(|(name, values)| match values.as_slice() {
+                        [value] => quote! { #name = #value },
+                        values => quote! { #name = [#(#values),*] },
+                    });
+                quote! { set(#(#clauses_iter),*) }
+            }
             Attribute::Requires(items) => {
                 let items_iter = items
                     .iter()
@@ -108,6 +154,9 @@ impl ToTokens for Attribute {
                     .collect::<Vec<_>>();
                 quote! { requires = [#(#items_iter),*] }
             }
+            Attribute::Support(item) => {
+                quote! { support = #item }
+            }
             // This attribute is handled separately
             Attribute::Sql(to_sql_config) => {
                 quote! { sql = #to_sql_config }
@@ -130,6 +179,11 @@ impl Parse for Attribute {
             "parallel_safe" => Self::ParallelSafe,
             "parallel_unsafe" => Self::ParallelUnsafe,
             "parallel_restricted" => Self::ParallelRestricted,
+            "window" => Self::Window,
+            "security_definer" => Self::SecurityDefiner,
+            "security_invoker" => Self::SecurityInvoker,
+            "leakproof" => Self::Leakproof,
+            "called_on_null_input" => Self::CalledOnNullInput,
             "error" => {
                 let _eq: Token![=] = input.parse()?;
                 let literal: syn::LitStr = input.parse()?;
@@ -150,12 +204,52 @@ impl Parse for Attribute {
                 let literal: syn::Expr = input.parse()?;
                 Self::Cost(literal)
             }
+            "rows" => {
+                let _eq: Token![=] = input.parse()?;
+                let literal: syn::Expr = input.parse()?;
+                Self::Rows(literal)
+            }
+            "set" => {
+                let content;
+                let _paren = syn::parenthesized!(content in input);
+                let clauses = content.parse_terminated::<_, Token![,]>(|input: ParseStream| {
+                    let name: syn::Ident = input.parse()?;
+                    let _eq: Token![=] = input.parse()?;
+                    let values = if input.peek(syn::token::Bracket) {
+                        let content;
+                        let bracket = syn::bracketed!(content in input);
+                        let values = content
+                            .parse_terminated::<_, Token![,]>(<syn::LitStr as Parse>::parse)?;
+                        if values.is_empty() {
+                            return Err(syn::Error::new(
+                                bracket.span,
+                                "a `set` list requires at least one value",
+                            ));
+                        }
+                        values.into_iter().collect()
+                    } else {
+                        vec![input.parse::<syn::LitStr>()?]
+                    };
+                    Ok((name, values))
+                })?;
+                if clauses.is_empty() {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "`set` requires at least one `name = \"value\"` clause",
+                    ));
+                }
+                Self::Set(clauses.into_iter().collect())
+            }
             "requires" => {
                 let _eq: syn::token::Eq = input.parse()?;
                 let content;
                 let _bracket = syn::bracketed!(content in input);
                 Self::Requires(content.parse_terminated(PositioningRef::parse)?)
             }
+            "support" => {
+                let _eq: Token![=] = input.parse()?;
+                Self::Support(input.parse()?)
+            }
             "sql" => {
                 use crate::sql_entity_graph::pgx_attribute::ArgValue;
                 use syn::Lit;
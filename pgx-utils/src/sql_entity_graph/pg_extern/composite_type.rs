@@ -0,0 +1,32 @@
+/// The SQL name given to a `composite_type!("name")` macro, if `ty` is one, or is an `Option` of
+/// one.
+pub(crate) fn composite_type_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Macro(type_macro) => {
+            let archetype = type_macro.mac.path.segments.last()?;
+            if archetype.ident == "composite_type" {
+                let name: syn::LitStr = type_macro
+                    .mac
+                    .parse_body()
+                    .expect("`composite_type!()` expects a string literal");
+                Some(name.value())
+            } else {
+                None
+            }
+        }
+        syn::Type::Path(type_path) => {
+            let last = type_path.path.segments.last()?;
+            if last.ident != "Option" {
+                return None;
+            }
+            match &last.arguments {
+                syn::PathArguments::AngleBracketed(args) => match args.args.first()? {
+                    syn::GenericArgument::Type(inner) => composite_type_name(inner),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
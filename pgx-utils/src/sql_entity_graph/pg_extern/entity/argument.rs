@@ -11,6 +11,8 @@ pub struct PgExternArgumentEntity {
     pub is_optional: bool,
     pub is_variadic: bool,
     pub default: Option<&'static str>,
+    /// The SQL name given to a `composite_type!()` argument
+    pub composite_type: Option<&'static str>,
 }
 
 impl SqlGraphIdentifier for PgExternArgumentEntity {
@@ -0,0 +1,37 @@
+/// The output of a [`PgEventTrigger`](crate::sql_entity_graph::PgEventTrigger) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PgEventTriggerEntity {
+    /// `ddl_command_start`, `ddl_command_end`, `sql_drop`, or `table_rewrite`
+    pub event: &'static str,
+    /// The command tags the event trigger fires for, or every command if empty
+    pub tags: Vec<&'static str>,
+}
+
+impl PgEventTriggerEntity {
+    /// The `CREATE EVENT TRIGGER` statement named `name`, which executes `function`, the
+    /// schema-qualified name of its event trigger function
+    pub(crate) fn create_event_trigger_sql(&self, name: &str, function: &str) -> String {
+        format!(
+            "\
+                CREATE EVENT TRIGGER {name} ON {event}\n\
+                {tags}\
+                \tEXECUTE PROCEDURE {function}();\
+            ",
+            name = name,
+            event = self.event,
+            tags = if self.tags.is_empty() {
+                String::default()
+            } else {
+                format!(
+                    "\tWHEN TAG IN ({})\n",
+                    self.tags
+                        .iter()
+                        .map(|tag| format!("'{}'", tag.replace('\'', "''")))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            },
+            function = function,
+        )
+    }
+}
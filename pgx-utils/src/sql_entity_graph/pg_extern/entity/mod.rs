@@ -1,15 +1,20 @@
 mod argument;
+mod event_trigger;
 mod operator;
 mod returning;
+mod trigger;
 
 pub use argument::PgExternArgumentEntity;
+pub use event_trigger::PgEventTriggerEntity;
 pub use operator::PgOperatorEntity;
 pub use returning::PgExternReturnEntity;
+pub use trigger::PgTriggerEntity;
 
 use crate::{
     sql_entity_graph::{
         extension_sql::SqlDeclared,
-        pgx_sql::PgxSql,
+        pgx_sql::{find_positioning_ref_target, PgxSql},
+        positioning_ref::PositioningRef,
         to_sql::{entity::ToSqlConfigEntity, ToSql},
         SqlGraphEntity, SqlGraphIdentifier,
     },
@@ -34,7 +39,13 @@ pub struct PgExternEntity {
     pub fn_args: Vec<PgExternArgumentEntity>,
     pub fn_return: PgExternReturnEntity,
     pub operator: Option<PgOperatorEntity>,
+    /// Created with `CREATE TRIGGER`, after the function
+    pub triggers: Vec<PgTriggerEntity>,
+    /// Created with `CREATE EVENT TRIGGER`, named like the function, after the function
+    pub event_trigger: Option<PgEventTriggerEntity>,
     pub to_sql_config: ToSqlConfigEntity,
+    /// Created with `CREATE PROCEDURE`, rather than `CREATE FUNCTION`
+    pub is_procedure: bool,
 }
 
 impl Ord for PgExternEntity {
@@ -84,7 +95,11 @@ impl ToSql for PgExternEntity {
         let self_index = context.externs[self];
         let mut extern_attrs = self.extern_attrs.clone();
         // if we already have a STRICT marker we do not need to add it
-        let mut strict_upgrade = !extern_attrs.iter().any(|i| i == &ExternArgs::Strict);
+        // procedures can't be STRICT, and CALLED ON NULL INPUT is explicitly not STRICT
+        let mut strict_upgrade = !self.is_procedure
+            && !extern_attrs
+                .iter()
+                .any(|i| i == &ExternArgs::Strict || i == &ExternArgs::CalledOnNullInput);
         if strict_upgrade {
             for arg in &self.fn_args {
                 if arg.is_optional {
@@ -97,14 +112,49 @@ impl ToSql for PgExternEntity {
             extern_attrs.push(ExternArgs::Strict);
         }
 
+        let support = match self.extern_attrs.iter().find_map(|attr| match attr {
+            ExternArgs::Support(support) => Some(support),
+            _ => None,
+        }) {
+            // a support function named by a string is used as-is
+            Some(PositioningRef::Name(name)) => format!("SUPPORT {}\n", name),
+            Some(support @ PositioningRef::FullPath(_)) => {
+                let support_index = find_positioning_ref_target(
+                    support,
+                    &context.types,
+                    &context.enums,
+                    &context.externs,
+                    &context.schemas,
+                    &context.extension_sqls,
+                )
+                .ok_or_else(|| eyre!("Could not find `support` function: {:?}", support))?;
+                match &context.graph[*support_index] {
+                    SqlGraphEntity::Function(support_fn) => format!(
+                        "SUPPORT {schema}\"{name}\"\n",
+                        schema = support_fn
+                            .schema
+                            .map(|schema| format!("{}.", schema))
+                            .unwrap_or_else(|| context.schema_prefix_for(support_index)),
+                        name = support_fn.name,
+                    ),
+                    _ => return Err(eyre!("`support` is not a function: {:?}", support)),
+                }
+            }
+            None => String::default(),
+        };
+
         let fn_sql = format!("\
-                                CREATE OR REPLACE FUNCTION {schema}\"{name}\"({arguments}) {returns}\n\
+                                CREATE OR REPLACE {kind} {schema}\"{name}\"({arguments}){returns}\n\
                                 {extern_attrs}\
+                                {support}\
                                 {search_path}\
+                                {set}\
                                 LANGUAGE c /* Rust */\n\
                                 AS 'MODULE_PATHNAME', '{unaliased_name}_wrapper';\
                             ",
                              schema = self.schema.map(|schema| format!("{}.", schema)).unwrap_or_else(|| context.schema_prefix_for(&self_index)),
+                             support = support,
+                             kind = if self.is_procedure { "PROCEDURE" } else { "FUNCTION" },
                              name = self.name,
                              unaliased_name = self.unaliased_name,
                              arguments = if !self.fn_args.is_empty() {
@@ -113,6 +163,8 @@ impl ToSql for PgExternEntity {
                                      let graph_index = context.graph.neighbors_undirected(self_index).find(|neighbor| match &context.graph[*neighbor] {
                                          SqlGraphEntity::Type(ty) => ty.id_matches(&arg.ty_id),
                                          SqlGraphEntity::Enum(en) => en.id_matches(&arg.ty_id),
+                                         SqlGraphEntity::Range(range) => range.id_matches(&arg.ty_id),
+                                         SqlGraphEntity::Composite(composite) => composite.id_matches(&arg.ty_id),
                                          SqlGraphEntity::BuiltinType(defined) => defined == &arg.full_path,
                                          _ => false,
                                      }).ok_or_else(|| eyre!("Could not find arg type in graph. Got: {:?}", arg))?;
@@ -121,14 +173,18 @@ impl ToSql for PgExternEntity {
                                             \t\"{pattern}\" {variadic}{schema_prefix}{sql_type}{default}{maybe_comma}/* {full_path} */\
                                         ",
                                             pattern = arg.pattern,
-                                            schema_prefix = context.schema_prefix_for(&graph_index),
+                                            // Composite types are named by the user and resolved through the `search_path`.
+                                            schema_prefix = if arg.composite_type.is_some() { String::default() } else { context.schema_prefix_for(&graph_index) },
                                             // First try to match on [`TypeId`] since it's most reliable.
-                                            sql_type = context.rust_to_sql(arg.ty_id, arg.ty_source, arg.full_path).ok_or_else(|| eyre!(
-                                                "Failed to map argument `{}` type `{}` to SQL type while building function `{}`.",
-                                                arg.pattern,
-                                                arg.full_path,
-                                                self.name
-                                            ))?,
+                                            sql_type = match arg.composite_type {
+                                                Some(composite_type) => composite_type.to_string(),
+                                                None => context.rust_to_sql(arg.ty_id, arg.ty_source, arg.full_path).ok_or_else(|| eyre!(
+                                                    "Failed to map argument `{}` type `{}` to SQL type while building function `{}`.",
+                                                    arg.pattern,
+                                                    arg.full_path,
+                                                    self.name
+                                                ))?,
+                                            },
                                             default = if let Some(def) = arg.default { format!(" DEFAULT {}", def) } else { String::from("") },
                                             variadic = if arg.is_variadic { "VARIADIC " } else { "" },
                                             maybe_comma = if needs_comma { ", " } else { " " },
@@ -138,17 +194,21 @@ impl ToSql for PgExternEntity {
                                  };
                                  String::from("\n") + &args.join("\n") + "\n"
                              } else { Default::default() },
-                             returns = match &self.fn_return {
+                             returns = if self.is_procedure { String::default() } else { String::from(" ") + &match &self.fn_return {
                                  PgExternReturnEntity::None => String::from("RETURNS void"),
-                                 PgExternReturnEntity::Type { id, source, full_path, .. } => {
+                                 PgExternReturnEntity::Type { id, source, full_path, composite_type, .. } => {
                                      let graph_index = context.graph.neighbors_undirected(self_index).find(|neighbor| match &context.graph[*neighbor] {
                                          SqlGraphEntity::Type(ty) => ty.id_matches(&id),
                                          SqlGraphEntity::Enum(en) => en.id_matches(&id),
+                                         SqlGraphEntity::Range(range) => range.id_matches(&id),
+                                         SqlGraphEntity::Composite(composite) => composite.id_matches(&id),
                                          SqlGraphEntity::BuiltinType(defined) => &*defined == full_path,
                                          _ => false,
                                      }).ok_or_else(|| eyre!("Could not find return type in graph."))?;
                                      format!("RETURNS {schema_prefix}{sql_type} /* {full_path} */",
-                                             sql_type = context.source_only_to_sql_type(source).or_else(|| {
+                                             sql_type = composite_type.map(|composite_type| composite_type.to_string()).or_else(|| {
+                                                 context.source_only_to_sql_type(source)
+                                             }).or_else(|| {
                                                  context.type_id_to_sql_type(*id)
                                              }).or_else(|| {
                                                     let pat = full_path.to_string();
@@ -160,19 +220,23 @@ impl ToSql for PgExternEntity {
                                                         None
                                                     }
                                                 }).ok_or_else(|| eyre!("Failed to map return type `{}` to SQL type while building function `{}`.", full_path, self.full_path))?,
-                                             schema_prefix = context.schema_prefix_for(&graph_index),
+                                             schema_prefix = if composite_type.is_some() { String::default() } else { context.schema_prefix_for(&graph_index) },
                                              full_path = full_path
                                      )
                                  },
-                                 PgExternReturnEntity::SetOf { id, source, full_path, .. } => {
+                                 PgExternReturnEntity::SetOf { id, source, full_path, composite_type, .. } => {
                                      let graph_index = context.graph.neighbors_undirected(self_index).find(|neighbor| match &context.graph[*neighbor] {
                                          SqlGraphEntity::Type(ty) => ty.id_matches(&id),
                                          SqlGraphEntity::Enum(en) => en.id_matches(&id),
+                                         SqlGraphEntity::Range(range) => range.id_matches(&id),
+                                         SqlGraphEntity::Composite(composite) => composite.id_matches(&id),
                                          SqlGraphEntity::BuiltinType(defined) => defined == full_path,
                                          _ => false,
                                      }).ok_or_else(|| eyre!("Could not find return type in graph."))?;
                                      format!("RETURNS SETOF {schema_prefix}{sql_type} /* {full_path} */",
-                                             sql_type = context.source_only_to_sql_type(source).or_else(|| {
+                                             sql_type = composite_type.map(|composite_type| composite_type.to_string()).or_else(|| {
+                                                 context.source_only_to_sql_type(source)
+                                             }).or_else(|| {
                                                  context.type_id_to_sql_type(*id)
                                              }).or_else(|| {
                                                     let pat = full_path.to_string();
@@ -184,7 +248,7 @@ impl ToSql for PgExternEntity {
                                                         None
                                                     }
                                                 }).ok_or_else(|| eyre!("Failed to map return type `{}` to SQL type while building function `{}`.", full_path, self.full_path))?,
-                                             schema_prefix = context.schema_prefix_for(&graph_index),
+                                             schema_prefix = if composite_type.is_some() { String::default() } else { context.schema_prefix_for(&graph_index) },
                                              full_path = full_path
                                      )
                                  },
@@ -194,6 +258,8 @@ impl ToSql for PgExternEntity {
                                          let graph_index = context.graph.neighbors_undirected(self_index).find(|neighbor| match &context.graph[*neighbor] {
                                              SqlGraphEntity::Type(ty) => ty.id_matches(&id),
                                              SqlGraphEntity::Enum(en) => en.id_matches(&id),
+                                             SqlGraphEntity::Range(range) => range.id_matches(&id),
+                                             SqlGraphEntity::Composite(composite) => composite.id_matches(&id),
                                              SqlGraphEntity::BuiltinType(defined) => defined == ty_name,
                                              _ => false,
                                          });
@@ -223,17 +289,24 @@ impl ToSql for PgExternEntity {
                                      format!("RETURNS TABLE ({}\n)", items)
                                  },
                                  PgExternReturnEntity::Trigger => String::from("RETURNS trigger"),
-                             },
+                                 PgExternReturnEntity::EventTrigger => String::from("RETURNS event_trigger"),
+                             } },
                              search_path = if let Some(search_path) = &self.search_path {
                                  let retval = format!("SET search_path TO {}", search_path.join(", "));
                                  retval + "\n"
                              } else { Default::default() },
-                             extern_attrs = if extern_attrs.is_empty() {
-                                 String::default()
-                             } else {
-                                 let mut retval = extern_attrs.iter().map(|attr| format!("{}", attr).to_uppercase()).collect::<Vec<_>>().join(" ");
-                                 retval.push('\n');
-                                 retval
+                             set = extern_attrs.iter().filter_map(|attr| match attr {
+                                 // each value is its own literal, so list settings like `search_path` work
+                                 ExternArgs::Set(name, values) => Some(format!("SET {} TO {}\n", name, values.iter().map(|v| format!("'{}'", v.replace('\'', "''"))).collect::<Vec<_>>().join(", "))),
+                                 _ => None,
+                             }).collect::<String>(),
+                             extern_attrs = {
+                                 let attrs = extern_attrs.iter().map(|attr| format!("{}", attr).to_uppercase()).filter(|attr| !attr.is_empty()).collect::<Vec<_>>();
+                                 if attrs.is_empty() {
+                                     String::default()
+                                 } else {
+                                     attrs.join(" ") + "\n"
+                                 }
                              },
         );
 
@@ -354,6 +427,56 @@ impl ToSql for PgExternEntity {
         } else {
             ext_sql
         };
-        Ok(rendered)
+
+        let function = format!(
+            "{schema}\"{name}\"",
+            schema = self
+                .schema
+                .map(|schema| format!("{}.", schema))
+                .unwrap_or_else(|| context.schema_prefix_for(&self_index)),
+            name = self.name,
+        );
+        let triggers_sql = self
+            .triggers
+            .iter()
+            .map(|trigger| {
+                format!(
+                    "\n\n\
+                        -- {file}:{line}\n\
+                        -- {module_path}::{unaliased_name}\n\
+                        {create_trigger}\
+                    ",
+                    file = self.file,
+                    line = self.line,
+                    module_path = self.module_path,
+                    unaliased_name = self.unaliased_name,
+                    create_trigger = trigger.create_trigger_sql(self.name, &function),
+                )
+            })
+            .collect::<String>();
+        tracing::trace!(sql = %triggers_sql);
+
+        let event_trigger_sql = match &self.event_trigger {
+            Some(event_trigger) => {
+                let event_trigger_sql = format!(
+                    "\n\n\
+                        -- {file}:{line}\n\
+                        -- {module_path}::{unaliased_name}\n\
+                        {create_event_trigger}\
+                    ",
+                    file = self.file,
+                    line = self.line,
+                    module_path = self.module_path,
+                    unaliased_name = self.unaliased_name,
+                    create_event_trigger =
+                        event_trigger.create_event_trigger_sql(self.name, &function),
+                );
+                tracing::trace!(sql = %event_trigger_sql);
+                event_trigger_sql
+            }
+            None => String::default(),
+        };
+
+        Ok(rendered + &triggers_sql + &event_trigger_sql)
     }
 }
@@ -8,12 +8,14 @@ pub enum PgExternReturnEntity {
         source: &'static str,
         full_path: &'static str,
         module_path: String,
+        composite_type: Option<&'static str>,
     },
     SetOf {
         id: TypeId,
         source: &'static str,
         full_path: &'static str,
         module_path: String,
+        composite_type: Option<&'static str>,
     },
     Iterated(
         Vec<(
@@ -25,4 +27,5 @@ pub enum PgExternReturnEntity {
         )>,
     ),
     Trigger,
+    EventTrigger,
 }
@@ -0,0 +1,62 @@
+/// The output of a [`PgTrigger`](crate::sql_entity_graph::PgTrigger) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PgTriggerEntity {
+    /// Defaults to the name of the trigger function
+    pub name: Option<&'static str>,
+    pub table: &'static str,
+    /// `BEFORE`, `AFTER`, or `INSTEAD OF`
+    pub timing: &'static str,
+    pub events: Vec<&'static str>,
+    /// `ROW` or `STATEMENT`
+    pub for_each: &'static str,
+    pub new_table: Option<&'static str>,
+    pub old_table: Option<&'static str>,
+    pub condition: Option<&'static str>,
+    pub args: Vec<&'static str>,
+}
+
+impl PgTriggerEntity {
+    /// The `CREATE TRIGGER` statement for the trigger function `function_name`, which is executed
+    /// as `function`, its schema-qualified name
+    pub(crate) fn create_trigger_sql(&self, function_name: &str, function: &str) -> String {
+        let mut referencing = vec![];
+        if let Some(new_table) = self.new_table {
+            referencing.push(format!("NEW TABLE AS {}", new_table));
+        }
+        if let Some(old_table) = self.old_table {
+            referencing.push(format!("OLD TABLE AS {}", old_table));
+        }
+
+        format!(
+            "\
+                CREATE TRIGGER {name}\n\
+                \t{timing} {events} ON {table}\n\
+                {referencing}\
+                \tFOR EACH {for_each}\n\
+                {condition}\
+                \tEXECUTE PROCEDURE {function}({args});\
+            ",
+            name = self.name.unwrap_or(function_name),
+            timing = self.timing,
+            events = self.events.join(" OR "),
+            table = self.table,
+            referencing = if referencing.is_empty() {
+                String::default()
+            } else {
+                format!("\tREFERENCING {}\n", referencing.join(" "))
+            },
+            for_each = self.for_each,
+            condition = match self.condition {
+                Some(condition) => format!("\tWHEN ({})\n", condition),
+                None => String::default(),
+            },
+            function = function,
+            args = self
+                .args
+                .iter()
+                .map(|arg| format!("'{}'", arg.replace('\'', "''")))
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+}
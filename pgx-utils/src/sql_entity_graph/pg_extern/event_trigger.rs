@@ -0,0 +1,83 @@
+use super::attribute::Attribute;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{parse::ParseStream, punctuated::Punctuated, Token};
+
+const EVENTS: &[&str] = &[
+    "ddl_command_start",
+    "ddl_command_end",
+    "sql_drop",
+    "table_rewrite",
+];
+
+/// A parsed `#[pg_event_trigger]` event trigger.
+///
+/// It is created during [`PgExtern`](crate::sql_entity_graph::PgExtern) parsing, along with the
+/// `#[pg_extern]` attributes given to `#[pg_event_trigger]` for its function.
+#[derive(Debug, Clone)]
+pub struct PgEventTrigger {
+    pub event: String,
+    pub tags: Vec<String>,
+}
+
+impl PgEventTrigger {
+    /// Splits the arguments of `#[pg_event_trigger]` into the event trigger and the `#[pg_extern]`
+    /// attributes of its function
+    pub(crate) fn parse_with_attributes(
+        input: ParseStream,
+    ) -> Result<(Self, Vec<Attribute>), syn::Error> {
+        let mut event = None;
+        let mut tags = Vec::new();
+        let mut attrs = Vec::new();
+
+        while !input.is_empty() {
+            let fork = input.fork();
+            let ident: syn::Ident = fork.parse()?;
+            match ident.to_string().as_str() {
+                "event" => {
+                    let _ident: syn::Ident = input.parse()?;
+                    let _eq: Token![=] = input.parse()?;
+                    let literal: syn::LitStr = input.parse()?;
+                    if !EVENTS.contains(&literal.value().as_str()) {
+                        return Err(syn::Error::new(
+                            literal.span(),
+                            format!("expected one of {}", EVENTS.join(", ")),
+                        ));
+                    }
+                    event = Some(literal.value());
+                }
+                "tags" => {
+                    let _ident: syn::Ident = input.parse()?;
+                    let _eq: Token![=] = input.parse()?;
+                    let content;
+                    let _bracket = syn::bracketed!(content in input);
+                    let parsed = Punctuated::<syn::LitStr, Token![,]>::parse_terminated(&content)?;
+                    tags = parsed.iter().map(|tag| tag.value()).collect();
+                }
+                _ => attrs.push(input.parse::<Attribute>()?),
+            }
+            if !input.is_empty() {
+                let _comma: Token![,] = input.parse()?;
+            }
+        }
+
+        let event = event.ok_or_else(|| {
+            syn::Error::new(Span::call_site(), "expected `event` to name the event")
+        })?;
+        Ok((PgEventTrigger { event, tags }, attrs))
+    }
+}
+
+impl ToTokens for PgEventTrigger {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let event = &self.event;
+        let tags = &self.tags;
+        let quoted = quote! {
+            ::pgx::utils::sql_entity_graph::PgEventTriggerEntity {
+                event: #event,
+                tags: vec![#(#tags),*],
+            }
+        };
+        tokens.append_all(quoted);
+    }
+}
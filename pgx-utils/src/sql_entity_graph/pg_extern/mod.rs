@@ -1,15 +1,24 @@
 mod argument;
 mod attribute;
+mod composite_type;
 pub mod entity;
+mod event_trigger;
 mod operator;
 mod returning;
 mod search_path;
+mod trigger;
 
 pub use argument::PgExternArgument;
+pub use event_trigger::PgEventTrigger;
 pub use operator::PgOperator;
 pub use returning::NameMacro;
+pub use trigger::PgTrigger;
 
 use crate::sql_entity_graph::ToSqlConfig;
+use crate::{
+    categorize_return_type, is_pg_event_trigger, is_pg_trigger, is_window_object, result_ok_type,
+    CategorizedType,
+};
 use attribute::Attribute;
 use operator::{PgxOperatorAttributeWithIdent, PgxOperatorOpName};
 use returning::Returning;
@@ -22,6 +31,7 @@ use std::convert::TryFrom;
 use syn::{
     parse::{Parse, ParseStream, Parser},
     punctuated::Punctuated,
+    spanned::Spanned,
     Meta, Token,
 };
 
@@ -51,6 +61,8 @@ pub struct PgExtern {
     attrs: Vec<Attribute>,
     func: syn::ItemFn,
     to_sql_config: ToSqlConfig,
+    procedure: bool,
+    event_trigger: Option<PgEventTrigger>,
 }
 
 impl PgExtern {
@@ -160,6 +172,21 @@ impl PgExtern {
         skel
     }
 
+    /// The triggers declared with `#[pg_trigger(...)]`
+    fn triggers(&self) -> Result<Vec<PgTrigger>, syn::Error> {
+        self.func
+            .attrs
+            .iter()
+            .filter(|attr| {
+                attr.path
+                    .segments
+                    .last()
+                    .map_or(false, |segment| segment.ident == "pg_trigger")
+            })
+            .map(|attr| attr.parse_args::<PgTrigger>())
+            .collect()
+    }
+
     fn search_path(&self) -> Option<SearchPathList> {
         self.func
             .attrs
@@ -187,17 +214,50 @@ impl PgExtern {
     }
 
     fn returns(&self) -> Result<Returning, eyre::Error> {
+        // a trigger function returns `trigger`, whatever it returns to Rust
+        if self.is_trigger() {
+            return Ok(Returning::Trigger);
+        }
+        if self.is_event_trigger() {
+            return Ok(Returning::EventTrigger);
+        }
         Returning::try_from(&self.func.sig.output)
     }
 
+    fn is_trigger(&self) -> bool {
+        self.func.sig.inputs.iter().any(|arg| match arg {
+            syn::FnArg::Typed(pat) => is_pg_trigger(&pat.ty),
+            syn::FnArg::Receiver(_) => false,
+        })
+    }
+
+    fn is_event_trigger(&self) -> bool {
+        self.event_trigger.is_some()
+            || self.func.sig.inputs.iter().any(|arg| match arg {
+                syn::FnArg::Typed(pat) => is_pg_event_trigger(&pat.ty),
+                syn::FnArg::Receiver(_) => false,
+            })
+    }
+
+    fn takes_window_object(&self) -> bool {
+        self.func.sig.inputs.iter().any(|arg| match arg {
+            syn::FnArg::Typed(pat) => is_window_object(&pat.ty),
+            syn::FnArg::Receiver(_) => false,
+        })
+    }
+
     pub fn new(attr: TokenStream2, item: TokenStream2) -> Result<Self, syn::Error> {
+        let parser = Punctuated::<Attribute, Token![,]>::parse_terminated;
+        let punctuated_attrs = parser.parse2(attr)?;
+        Self::from_attributes(punctuated_attrs.into_iter().collect(), item)
+    }
+
+    fn from_attributes(attributes: Vec<Attribute>, item: TokenStream2) -> Result<Self, syn::Error> {
         let mut attrs = Vec::new();
         let mut to_sql_config: Option<ToSqlConfig> = None;
 
-        let parser = Punctuated::<Attribute, Token![,]>::parse_terminated;
-        let punctuated_attrs = parser.parse2(attr)?;
-        for pair in punctuated_attrs.into_pairs() {
-            match pair.into_value() {
+        for attribute in attributes {
+            match attribute {
                 Attribute::Sql(config) => {
                     to_sql_config.get_or_insert(config);
                 }
@@ -220,11 +280,177 @@ impl PgExtern {
             }
         }
 
-        Ok(Self {
+        let pg_extern = Self {
             attrs,
             func,
             to_sql_config: to_sql_config.unwrap_or_default(),
-        })
+            procedure: false,
+            event_trigger: None,
+        };
+        pg_extern.validate()?;
+        Ok(pg_extern)
+    }
+
+    /// Rejects combinations of attributes that Postgres would refuse at `CREATE FUNCTION` time
+    fn validate(&self) -> Result<(), syn::Error> {
+        let count = |f: fn(&Attribute) -> bool| self.attrs.iter().filter(|a| f(a)).count();
+        let error = |message: &str| Err(syn::Error::new(Span::call_site(), message));
+
+        if count(|a| {
+            matches!(
+                a,
+                Attribute::Immutable | Attribute::Stable | Attribute::Volatile
+            )
+        }) > 1
+        {
+            return error("only one of `immutable`, `stable`, or `volatile` may be specified");
+        }
+        if count(|a| {
+            matches!(
+                a,
+                Attribute::ParallelSafe | Attribute::ParallelUnsafe | Attribute::ParallelRestricted
+            )
+        }) > 1
+        {
+            return error(
+                "only one of `parallel_safe`, `parallel_unsafe`, or `parallel_restricted` may be specified",
+            );
+        }
+        if count(|a| matches!(a, Attribute::Strict)) > 0
+            && count(|a| matches!(a, Attribute::CalledOnNullInput)) > 0
+        {
+            return error("`strict` and `called_on_null_input` are mutually exclusive");
+        }
+        if count(|a| matches!(a, Attribute::SecurityDefiner)) > 0
+            && count(|a| matches!(a, Attribute::SecurityInvoker)) > 0
+        {
+            return error("`security_definer` and `security_invoker` are mutually exclusive");
+        }
+        // functions are `VOLATILE` unless declared otherwise
+        if count(|a| matches!(a, Attribute::Leakproof)) > 0
+            && count(|a| matches!(a, Attribute::Immutable | Attribute::Stable)) == 0
+        {
+            return error("a `leakproof` function must be `immutable` or `stable`");
+        }
+        if count(|a| matches!(a, Attribute::Rows(_))) > 0 {
+            match categorize_return_type(&self.func) {
+                CategorizedType::Iterator(_) | CategorizedType::OptionalIterator(_) => (),
+                _ => return error("`rows` is only valid for a set-returning function"),
+            }
+        }
+        let sets_search_path = self.attrs.iter().any(|a| match a {
+            Attribute::Set(clauses) => clauses.iter().any(|(name, _)| name == "search_path"),
+            _ => false,
+        });
+        if sets_search_path && self.search_path().is_some() {
+            return error("`set(search_path = ...)` cannot be combined with `#[search_path(...)]`");
+        }
+        let is_window = count(|a| matches!(a, Attribute::Window)) > 0;
+        if is_window && !self.takes_window_object() {
+            return error("a `window` function must take a `WindowObject` argument");
+        }
+        if !is_window && self.takes_window_object() {
+            return error("a function taking a `WindowObject` must be declared `window`");
+        }
+        if self.is_trigger() && self.inputs().map_or(false, |inputs| !inputs.is_empty()) {
+            return error("a trigger function cannot take arguments other than its `PgTrigger`");
+        }
+        if !self.triggers()?.is_empty() && !self.is_trigger() {
+            return error("`#[pg_trigger]` can only be used on a function taking a `PgTrigger`");
+        }
+        if self.is_event_trigger() {
+            if self.inputs().map_or(false, |inputs| !inputs.is_empty()) {
+                return error(
+                    "an event trigger function cannot take arguments other than its `PgEventTrigger`",
+                );
+            }
+            let returns_unit = match &self.func.sig.output {
+                syn::ReturnType::Default => true,
+                syn::ReturnType::Type(_, ty) => {
+                    let ty = result_ok_type(ty).unwrap_or(ty);
+                    matches!(ty, syn::Type::Tuple(tuple) if tuple.elems.is_empty())
+                }
+            };
+            if !returns_unit {
+                return Err(syn::Error::new(
+                    self.func.sig.output.span(),
+                    "event trigger functions cannot return a value",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Like [`PgExtern::new`], but for a `#[pg_procedure]`, which is created with `CREATE PROCEDURE`
+    pub fn new_procedure(attr: TokenStream2, item: TokenStream2) -> Result<Self, syn::Error> {
+        let mut procedure = Self::new(attr, item)?;
+        procedure.procedure = true;
+
+        for attr in &procedure.attrs {
+            match attr {
+                Attribute::Schema(_)
+                | Attribute::Name(_)
+                | Attribute::Requires(_)
+                | Attribute::Error(_)
+                | Attribute::Sql(_)
+                | Attribute::NoGuard => (),
+                other => {
+                    return Err(syn::Error::new(
+                        Span::call_site(),
+                        format!("`{}` is not valid for a procedure", other.to_token_stream()),
+                    ))
+                }
+            }
+        }
+
+        match &procedure.func.sig.output {
+            syn::ReturnType::Default => (),
+            syn::ReturnType::Type(_, ty) => match ty.as_ref() {
+                syn::Type::Tuple(tuple) if tuple.elems.is_empty() => (),
+                other => {
+                    return Err(syn::Error::new(
+                        other.span(),
+                        "procedures cannot return a value",
+                    ))
+                }
+            },
+        }
+
+        Ok(procedure)
+    }
+
+    /// Like [`PgExtern::new`], but for a `#[pg_event_trigger]`, whose event trigger is created with
+    /// `CREATE EVENT TRIGGER` after its function
+    pub fn new_event_trigger(attr: TokenStream2, item: TokenStream2) -> Result<Self, syn::Error> {
+        let (event_trigger, attrs) = PgEventTrigger::parse_with_attributes.parse2(attr)?;
+        let mut pg_extern = Self::from_attributes(attrs, item)?;
+        pg_extern.event_trigger = Some(event_trigger);
+        pg_extern.validate()?;
+
+        for attr in &pg_extern.attrs {
+            match attr {
+                Attribute::Schema(_)
+                | Attribute::Name(_)
+                | Attribute::Requires(_)
+                | Attribute::Error(_)
+                | Attribute::Sql(_)
+                | Attribute::NoGuard
+                | Attribute::SecurityDefiner
+                | Attribute::SecurityInvoker
+                | Attribute::Set(_) => (),
+                other => {
+                    return Err(syn::Error::new(
+                        Span::call_site(),
+                        format!(
+                            "`{}` is not valid for an event trigger",
+                            other.to_token_stream()
+                        ),
+                    ))
+                }
+            }
+        }
+
+        Ok(pg_extern)
     }
 }
 
@@ -252,6 +478,9 @@ impl ToTokens for PgExtern {
             }
         };
         let operator = self.operator().into_iter();
+        let triggers = self.triggers().unwrap();
+        let event_trigger = self.event_trigger.iter();
+        let is_procedure = self.procedure;
         let to_sql_config = match self.overridden() {
             None => self.to_sql_config.clone(),
             Some(content) => {
@@ -284,7 +513,10 @@ impl ToTokens for PgExtern {
                     fn_args: vec![#(#inputs),*],
                     fn_return: #returns,
                     operator: None #( .unwrap_or(Some(#operator)) )*,
+                    triggers: vec![#(#triggers),*],
+                    event_trigger: None #( .unwrap_or(Some(#event_trigger)) )*,
                     to_sql_config: #to_sql_config,
+                    is_procedure: #is_procedure,
                 };
                 ::pgx::utils::sql_entity_graph::SqlGraphEntity::Function(submission)
             }
@@ -316,6 +548,33 @@ impl Parse for PgExtern {
             attrs,
             func,
             to_sql_config: to_sql_config.unwrap_or_default(),
+            procedure: false,
+            event_trigger: None,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::PgExtern;
+    use quote::quote;
+
+    #[test]
+    fn leakproof_requires_immutable_or_stable() {
+        let item = quote! {
+            fn is_positive(value: i32) -> bool {
+                value > 0
+            }
+        };
+
+        for attr in [quote! { leakproof }, quote! { leakproof, volatile }] {
+            let error = PgExtern::new(attr, item.clone()).err().unwrap();
+            assert_eq!(
+                error.to_string(),
+                "a `leakproof` function must be `immutable` or `stable`"
+            );
+        }
+        assert!(PgExtern::new(quote! { leakproof, immutable }, item.clone()).is_ok());
+        assert!(PgExtern::new(quote! { leakproof, stable }, item).is_ok());
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{anonymonize_lifetimes, anonymonize_lifetimes_in_type_path};
+use super::composite_type::composite_type_name;
+use crate::{anonymonize_lifetimes, anonymonize_lifetimes_in_type_path, result_ok_type};
 use eyre::eyre;
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens, TokenStreamExt};
@@ -12,10 +13,12 @@ use syn::{
 pub enum Returning {
     None,
     Type(syn::Type),
-    SetOf(syn::TypePath),
+    SetOf(syn::Type),
     Iterated(Vec<(syn::Type, Option<String>)>),
     /// `pgx_pg_sys::Datum`
     Trigger,
+    /// An event trigger function, whatever it returns to Rust
+    EventTrigger,
 }
 
 impl Returning {
@@ -26,13 +29,16 @@ impl Returning {
                 syn::PathArguments::AngleBracketed(args) => match args.args.first_mut().unwrap() {
                     syn::GenericArgument::Binding(binding) => match &mut binding.ty {
                         syn::Type::Tuple(tuple_type) => Self::parse_type_tuple(tuple_type),
-                        syn::Type::Path(path) => {
-                            Returning::SetOf(anonymonize_lifetimes_in_type_path(path.clone()))
+                        syn::Type::Path(path) => Returning::SetOf(syn::Type::Path(
+                            anonymonize_lifetimes_in_type_path(path.clone()),
+                        )),
+                        syn::Type::Macro(type_macro) => {
+                            Returning::SetOf(syn::Type::Macro(type_macro.clone()))
                         }
                         syn::Type::Reference(type_ref) => match &*type_ref.elem {
-                            syn::Type::Path(path) => {
-                                Returning::SetOf(anonymonize_lifetimes_in_type_path(path.clone()))
-                            }
+                            syn::Type::Path(path) => Returning::SetOf(syn::Type::Path(
+                                anonymonize_lifetimes_in_type_path(path.clone()),
+                            )),
                             _ => unimplemented!("Expected path"),
                         },
                         ty => unimplemented!("Only iters with tuples, got {:?}.", ty),
@@ -95,7 +101,15 @@ impl TryFrom<&syn::ReturnType> for Returning {
     fn try_from(value: &syn::ReturnType) -> Result<Self, Self::Error> {
         Ok(match &value {
             syn::ReturnType::Default => Returning::None,
-            syn::ReturnType::Type(_, ty) => {
+            syn::ReturnType::Type(arrow, ty) => {
+                // the `Err` of a `Result` is raised as an ERROR, so the function returns its `Ok` type
+                if let Some(ok_ty) = result_ok_type(ty) {
+                    return Returning::try_from(&syn::ReturnType::Type(
+                        *arrow,
+                        Box::new(ok_ty.clone()),
+                    ));
+                }
+
                 let mut ty = *ty.clone();
                 anonymonize_lifetimes(&mut ty);
 
@@ -180,6 +194,18 @@ impl TryFrom<&syn::ReturnType> for Returning {
                         }
                         Returning::Type(syn::Type::Reference(ty_ref))
                     }
+                    syn::Type::Macro(ref type_macro) => {
+                        let archetype = type_macro.mac.path.segments.last().unwrap();
+                        match archetype.ident.to_string().as_str() {
+                            "composite_type" => Returning::Type(ty.clone()),
+                            _ => {
+                                return Err(eyre!(
+                                    "Got unknown return type macro: {}",
+                                    &ty.to_token_stream()
+                                ))
+                            }
+                        }
+                    }
                     syn::Type::Tuple(ref mut tup) => {
                         if tup.elems.is_empty() {
                             Returning::Type(ty.clone())
@@ -202,6 +228,7 @@ impl ToTokens for Returning {
             },
             Returning::Type(ty) => {
                 let ty_string = ty.to_token_stream().to_string().replace(" ", "");
+                let composite_type = composite_type_name(ty).into_iter();
                 quote! {
                     ::pgx::utils::sql_entity_graph::PgExternReturnEntity::Type {
                         id: TypeId::of::<#ty>(),
@@ -213,11 +240,13 @@ impl ToTokens for Returning {
                             let _ = path_items.pop(); // Drop the one we don't want.
                             path_items.join("::")
                         },
+                        composite_type: None #( .unwrap_or(Some(#composite_type)) )*,
                     }
                 }
             }
             Returning::SetOf(ty) => {
                 let ty_string = ty.to_token_stream().to_string().replace(" ", "");
+                let composite_type = composite_type_name(ty).into_iter();
                 quote! {
                     ::pgx::utils::sql_entity_graph::PgExternReturnEntity::SetOf {
                         id: TypeId::of::<#ty>(),
@@ -228,7 +257,8 @@ impl ToTokens for Returning {
                             let mut path_items: Vec<_> = type_name.split("::").collect();
                             let _ = path_items.pop(); // Drop the one we don't want.
                             path_items.join("::")
-                        }
+                        },
+                        composite_type: None #( .unwrap_or(Some(#composite_type)) )*,
                     }
                 }
             }
@@ -263,6 +293,9 @@ impl ToTokens for Returning {
             Returning::Trigger => quote! {
                 ::pgx::utils::sql_entity_graph::PgExternReturnEntity::Trigger
             },
+            Returning::EventTrigger => quote! {
+                ::pgx::utils::sql_entity_graph::PgExternReturnEntity::EventTrigger
+            },
         };
         tokens.append_all(quoted);
     }
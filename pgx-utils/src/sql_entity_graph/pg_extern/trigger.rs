@@ -0,0 +1,221 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Token,
+};
+
+/// A parsed `#[pg_trigger]` trigger, declared on a `#[pg_extern]` trigger function.
+///
+/// It is created during [`PgExtern`](crate::sql_entity_graph::PgExtern) parsing.
+#[derive(Debug, Clone)]
+pub struct PgTrigger {
+    pub name: Option<String>,
+    pub table: String,
+    pub timing: &'static str,
+    pub events: Vec<&'static str>,
+    pub for_each: &'static str,
+    pub new_table: Option<String>,
+    pub old_table: Option<String>,
+    pub condition: Option<String>,
+    pub args: Vec<String>,
+}
+
+impl PgTrigger {
+    fn validate(&self) -> Result<(), String> {
+        let has_event = |event| self.events.contains(&event);
+        if self.timing == "INSTEAD OF" && self.for_each != "ROW" {
+            return Err("an `instead_of` trigger must be `for_each = row`".into());
+        }
+        if has_event("TRUNCATE") && self.for_each == "ROW" {
+            return Err("a `truncate` trigger must be `for_each = statement`".into());
+        }
+        if self.new_table.is_some() || self.old_table.is_some() {
+            if self.timing != "AFTER" {
+                return Err("only an `after` trigger can have `referencing`".into());
+            }
+            if self.events.len() > 1 {
+                return Err("a trigger with `referencing` can only have one event".into());
+            }
+            if has_event("TRUNCATE") {
+                return Err("a `truncate` trigger cannot have `referencing`".into());
+            }
+        }
+        if self.new_table.is_some() && !has_event("INSERT") && !has_event("UPDATE") {
+            return Err("`new_table` requires an `insert` or `update` event".into());
+        }
+        if self.old_table.is_some() && !has_event("UPDATE") && !has_event("DELETE") {
+            return Err("`old_table` requires an `update` or `delete` event".into());
+        }
+        Ok(())
+    }
+}
+
+impl Parse for PgTrigger {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let mut name = None;
+        let mut table = None;
+        let mut timing = None;
+        let mut events = Vec::new();
+        let mut for_each = "STATEMENT";
+        let mut new_table = None;
+        let mut old_table = None;
+        let mut condition = None;
+        let mut args = Vec::new();
+
+        while !input.is_empty() {
+            let ident: syn::Ident = input.parse()?;
+            match ident.to_string().as_str() {
+                "before" | "after" | "instead_of" => {
+                    if timing.is_some() {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            "only one of `before`, `after`, or `instead_of` may be specified",
+                        ));
+                    }
+                    timing = Some(match ident.to_string().as_str() {
+                        "before" => "BEFORE",
+                        "after" => "AFTER",
+                        _ => "INSTEAD OF",
+                    });
+                }
+                "name" => {
+                    let _eq: Token![=] = input.parse()?;
+                    name = Some(input.parse::<syn::LitStr>()?.value());
+                }
+                "table" => {
+                    let _eq: Token![=] = input.parse()?;
+                    table = Some(input.parse::<syn::LitStr>()?.value());
+                }
+                "when" => {
+                    let _eq: Token![=] = input.parse()?;
+                    condition = Some(input.parse::<syn::LitStr>()?.value());
+                }
+                "events" => {
+                    let _eq: Token![=] = input.parse()?;
+                    let content;
+                    let _bracket = syn::bracketed!(content in input);
+                    let parsed = Punctuated::<syn::Ident, Token![,]>::parse_terminated(&content)?;
+                    for event in parsed {
+                        events.push(match event.to_string().as_str() {
+                            "insert" => "INSERT",
+                            "update" => "UPDATE",
+                            "delete" => "DELETE",
+                            "truncate" => "TRUNCATE",
+                            _ => {
+                                return Err(syn::Error::new(
+                                    event.span(),
+                                    "expected `insert`, `update`, `delete`, or `truncate`",
+                                ))
+                            }
+                        });
+                    }
+                }
+                "for_each" => {
+                    let _eq: Token![=] = input.parse()?;
+                    let level: syn::Ident = input.parse()?;
+                    for_each = match level.to_string().as_str() {
+                        "row" => "ROW",
+                        "statement" => "STATEMENT",
+                        _ => {
+                            return Err(syn::Error::new(
+                                level.span(),
+                                "expected `row` or `statement`",
+                            ))
+                        }
+                    };
+                }
+                "referencing" => {
+                    let content;
+                    let _paren = syn::parenthesized!(content in input);
+                    while !content.is_empty() {
+                        let which: syn::Ident = content.parse()?;
+                        let _eq: Token![=] = content.parse()?;
+                        let alias = content.parse::<syn::LitStr>()?.value();
+                        match which.to_string().as_str() {
+                            "new_table" => new_table = Some(alias),
+                            "old_table" => old_table = Some(alias),
+                            _ => {
+                                return Err(syn::Error::new(
+                                    which.span(),
+                                    "expected `new_table` or `old_table`",
+                                ))
+                            }
+                        }
+                        if !content.is_empty() {
+                            let _comma: Token![,] = content.parse()?;
+                        }
+                    }
+                }
+                "args" => {
+                    let _eq: Token![=] = input.parse()?;
+                    let content;
+                    let _bracket = syn::bracketed!(content in input);
+                    let parsed = Punctuated::<syn::LitStr, Token![,]>::parse_terminated(&content)?;
+                    args = parsed.iter().map(|arg| arg.value()).collect();
+                }
+                other => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("Unknown pg_trigger attribute: {}", other),
+                    ))
+                }
+            }
+            if !input.is_empty() {
+                let _comma: Token![,] = input.parse()?;
+            }
+        }
+
+        let table = table.ok_or_else(|| input.error("expected `table` to be set"))?;
+        let timing = timing
+            .ok_or_else(|| input.error("expected one of `before`, `after`, or `instead_of`"))?;
+        if events.is_empty() {
+            return Err(input.error("expected `events` to name at least one event"));
+        }
+
+        let trigger = PgTrigger {
+            name,
+            table,
+            timing,
+            events,
+            for_each,
+            new_table,
+            old_table,
+            condition,
+            args,
+        };
+        trigger
+            .validate()
+            .map_err(|e| syn::Error::new(Span::call_site(), e))?;
+        Ok(trigger)
+    }
+}
+
+impl ToTokens for PgTrigger {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let name = self.name.iter();
+        let table = &self.table;
+        let timing = self.timing;
+        let events = &self.events;
+        let for_each = self.for_each;
+        let new_table = self.new_table.iter();
+        let old_table = self.old_table.iter();
+        let condition = self.condition.iter();
+        let args = &self.args;
+        let quoted = quote! {
+            ::pgx::utils::sql_entity_graph::PgTriggerEntity {
+                name: None #( .unwrap_or(Some(#name)) )*,
+                table: #table,
+                timing: #timing,
+                events: vec![#(#events),*],
+                for_each: #for_each,
+                new_table: None #( .unwrap_or(Some(#new_table)) )*,
+                old_table: None #( .unwrap_or(Some(#old_table)) )*,
+                condition: None #( .unwrap_or(Some(#condition)) )*,
+                args: vec![#(#args),*],
+            }
+        };
+        tokens.append_all(quoted);
+    }
+}
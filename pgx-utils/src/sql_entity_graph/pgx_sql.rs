@@ -14,9 +14,11 @@ use crate::sql_entity_graph::{
     mapping::{RustSourceOnlySqlMapping, RustSqlMapping},
     pg_extern::entity::{PgExternEntity, PgExternReturnEntity},
     positioning_ref::PositioningRef,
+    postgres_composite::entity::PostgresCompositeEntity,
     postgres_enum::entity::PostgresEnumEntity,
     postgres_hash::entity::PostgresHashEntity,
     postgres_ord::entity::PostgresOrdEntity,
+    postgres_range::entity::PostgresRangeEntity,
     postgres_type::entity::PostgresTypeEntity,
     schema::entity::SchemaEntity,
     to_sql::ToSql,
@@ -54,6 +56,8 @@ pub struct PgxSql {
     pub enums: HashMap<PostgresEnumEntity, NodeIndex>,
     pub ords: HashMap<PostgresOrdEntity, NodeIndex>,
     pub hashes: HashMap<PostgresHashEntity, NodeIndex>,
+    pub ranges: HashMap<PostgresRangeEntity, NodeIndex>,
+    pub composites: HashMap<PostgresCompositeEntity, NodeIndex>,
     pub aggregates: HashMap<PgAggregateEntity, NodeIndex>,
 }
 
@@ -84,6 +88,8 @@ impl PgxSql {
         let mut enums: Vec<PostgresEnumEntity> = Vec::default();
         let mut ords: Vec<PostgresOrdEntity> = Vec::default();
         let mut hashes: Vec<PostgresHashEntity> = Vec::default();
+        let mut ranges: Vec<PostgresRangeEntity> = Vec::default();
+        let mut composites: Vec<PostgresCompositeEntity> = Vec::default();
         let mut aggregates: Vec<PgAggregateEntity> = Vec::default();
         for entity in entities {
             match entity {
@@ -112,6 +118,12 @@ impl PgxSql {
                 SqlGraphEntity::Hash(input_hash) => {
                     hashes.push(input_hash);
                 }
+                SqlGraphEntity::Range(input_range) => {
+                    ranges.push(input_range);
+                }
+                SqlGraphEntity::Composite(input_composite) => {
+                    composites.push(input_composite);
+                }
                 SqlGraphEntity::Aggregate(input_hash) => {
                     aggregates.push(input_hash);
                 }
@@ -134,6 +146,9 @@ impl PgxSql {
         let mapped_schemas = initialize_schemas(&mut graph, bootstrap, finalize, schemas)?;
         let mapped_enums = initialize_enums(&mut graph, root, bootstrap, finalize, enums)?;
         let mapped_types = initialize_types(&mut graph, root, bootstrap, finalize, types)?;
+        let mapped_ranges = initialize_ranges(&mut graph, root, bootstrap, finalize, ranges)?;
+        let mapped_composites =
+            initialize_composites(&mut graph, root, bootstrap, finalize, composites)?;
         let (mapped_externs, mut mapped_builtin_types) = initialize_externs(
             &mut graph,
             root,
@@ -142,6 +157,8 @@ impl PgxSql {
             externs,
             &mapped_types,
             &mapped_enums,
+            &mapped_ranges,
+            &mapped_composites,
         )?;
         let mapped_ords = initialize_ords(&mut graph, root, bootstrap, finalize, ords)?;
         let mapped_hashes = initialize_hashes(&mut graph, root, bootstrap, finalize, hashes)?;
@@ -174,6 +191,8 @@ impl PgxSql {
             &mapped_schemas,
             &mapped_types,
             &mapped_enums,
+            &mapped_ranges,
+            &mapped_composites,
             &mapped_builtin_types,
             &mapped_extension_sqls,
         )?;
@@ -193,6 +212,21 @@ impl PgxSql {
             &mapped_enums,
             &mapped_externs,
         );
+        connect_ranges(
+            &mut graph,
+            &mapped_ranges,
+            &mapped_schemas,
+            &mapped_types,
+            &mapped_ords,
+        );
+        connect_composites(
+            &mut graph,
+            &mapped_composites,
+            &mapped_schemas,
+            &mapped_types,
+            &mapped_enums,
+            &mapped_ranges,
+        );
         connect_aggregates(
             &mut graph,
             &mapped_aggregates,
@@ -215,6 +249,8 @@ impl PgxSql {
             enums: mapped_enums,
             ords: mapped_ords,
             hashes: mapped_hashes,
+            ranges: mapped_ranges,
+            composites: mapped_composites,
             aggregates: mapped_aggregates,
             graph: graph,
             graph_root: root,
@@ -332,6 +368,14 @@ impl PgxSql {
                         "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#FFE4E0\", weight = 5, shape = \"diamond\"",
                         node.dot_identifier()
                     ),
+                    SqlGraphEntity::Range(_item) => format!(
+                        "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#AE9BBD\", weight = 5, shape = \"oval\"",
+                        node.dot_identifier()
+                    ),
+                    SqlGraphEntity::Composite(_item) => format!(
+                        "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#AE9BBD\", weight = 5, shape = \"box\"",
+                        node.dot_identifier()
+                    ),
                     SqlGraphEntity::Aggregate(_item) => format!(
                         "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#FFE4E0\", weight = 5, shape = \"diamond\"",
                         node.dot_identifier()
@@ -426,6 +470,28 @@ impl PgxSql {
                 );
             }
         }
+        for (item, _index) in self.ranges.clone() {
+            for mapping in &item.mappings {
+                assert_eq!(
+                    self.type_mappings
+                        .insert(mapping.id.clone(), mapping.clone()),
+                    None,
+                    "Cannot map `{}` twice.",
+                    item.full_path,
+                );
+            }
+        }
+        for (item, _index) in self.composites.clone() {
+            for mapping in &item.mappings {
+                assert_eq!(
+                    self.type_mappings
+                        .insert(mapping.id.clone(), mapping.clone()),
+                    None,
+                    "Cannot map `{}` twice.",
+                    item.full_path,
+                );
+            }
+        }
     }
 
     pub fn has_sql_declared_entity(&self, identifier: &SqlDeclared) -> Option<&SqlDeclaredEntity> {
@@ -770,6 +836,8 @@ fn initialize_externs(
     externs: Vec<PgExternEntity>,
     mapped_types: &HashMap<PostgresTypeEntity, NodeIndex>,
     mapped_enums: &HashMap<PostgresEnumEntity, NodeIndex>,
+    mapped_ranges: &HashMap<PostgresRangeEntity, NodeIndex>,
+    mapped_composites: &HashMap<PostgresCompositeEntity, NodeIndex>,
 ) -> eyre::Result<(
     HashMap<PgExternEntity, NodeIndex>,
     HashMap<String, NodeIndex>,
@@ -796,6 +864,18 @@ fn initialize_externs(
                     break;
                 }
             }
+            for (ty_item, &_ty_index) in mapped_ranges {
+                if ty_item.id_matches(&arg.ty_id) {
+                    found = true;
+                    break;
+                }
+            }
+            for (ty_item, &_ty_index) in mapped_composites {
+                if ty_item.id_matches(&arg.ty_id) {
+                    found = true;
+                    break;
+                }
+            }
             if !found {
                 mapped_builtin_types
                     .entry(arg.full_path.to_string())
@@ -806,7 +886,9 @@ fn initialize_externs(
         }
 
         match &item.fn_return {
-            PgExternReturnEntity::None | PgExternReturnEntity::Trigger => (),
+            PgExternReturnEntity::None
+            | PgExternReturnEntity::Trigger
+            | PgExternReturnEntity::EventTrigger => (),
             PgExternReturnEntity::Type { id, full_path, .. }
             | PgExternReturnEntity::SetOf { id, full_path, .. } => {
                 let mut found = false;
@@ -822,6 +904,18 @@ fn initialize_externs(
                         break;
                     }
                 }
+                for (ty_item, &_ty_index) in mapped_ranges {
+                    if ty_item.id_matches(id) {
+                        found = true;
+                        break;
+                    }
+                }
+                for (ty_item, &_ty_index) in mapped_composites {
+                    if ty_item.id_matches(id) {
+                        found = true;
+                        break;
+                    }
+                }
                 if !found {
                     mapped_builtin_types
                         .entry(full_path.to_string())
@@ -845,6 +939,18 @@ fn initialize_externs(
                             break;
                         }
                     }
+                    for (ty_item, &_ty_index) in mapped_ranges {
+                        if ty_item.id_matches(&iterated_return.0) {
+                            found = true;
+                            break;
+                        }
+                    }
+                    for (ty_item, &_ty_index) in mapped_composites {
+                        if ty_item.id_matches(&iterated_return.0) {
+                            found = true;
+                            break;
+                        }
+                    }
                     if !found {
                         mapped_builtin_types
                             .entry(iterated_return.1.to_string())
@@ -868,6 +974,8 @@ fn connect_externs(
     schemas: &HashMap<SchemaEntity, NodeIndex>,
     types: &HashMap<PostgresTypeEntity, NodeIndex>,
     enums: &HashMap<PostgresEnumEntity, NodeIndex>,
+    ranges: &HashMap<PostgresRangeEntity, NodeIndex>,
+    composites: &HashMap<PostgresCompositeEntity, NodeIndex>,
     builtin_types: &HashMap<String, NodeIndex>,
     extension_sqls: &HashMap<ExtensionSqlEntity, NodeIndex>,
 ) -> eyre::Result<()> {
@@ -900,6 +1008,25 @@ fn connect_externs(
                         }
                     }
                 }
+                // a support function named by a string is assumed to exist already
+                crate::ExternArgs::Support(support @ PositioningRef::FullPath(_)) => {
+                    match find_positioning_ref_target(
+                        support,
+                        types,
+                        enums,
+                        externs,
+                        schemas,
+                        extension_sqls,
+                    ) {
+                        Some(target) if matches!(graph[*target], SqlGraphEntity::Function(_)) => {
+                            tracing::debug!(from = %item.rust_identifier(), to = %graph[*target].rust_identifier(), "Adding Extern after support function edge");
+                            graph.add_edge(*target, index, SqlGraphRelationship::RequiredBy);
+                        }
+                        _ => {
+                            return Err(eyre!("Could not find `support` function: {:?}", support))
+                        }
+                    }
+                }
                 _ => (),
             }
         }
@@ -924,6 +1051,26 @@ fn connect_externs(
                     }
                 }
             }
+            if !found {
+                for (range_item, &range_index) in ranges {
+                    if range_item.id_matches(&arg.ty_id) {
+                        tracing::debug!(from = %item.rust_identifier(), to = %range_item.rust_identifier(), "Adding Extern after Range (due to argument) edge");
+                        graph.add_edge(range_index, index, SqlGraphRelationship::RequiredByArg);
+                        found = true;
+                        break;
+                    }
+                }
+            }
+            if !found {
+                for (composite_item, &composite_index) in composites {
+                    if composite_item.id_matches(&arg.ty_id) {
+                        tracing::debug!(from = %item.rust_identifier(), to = %composite_item.rust_identifier(), "Adding Extern after Composite (due to argument) edge");
+                        graph.add_edge(composite_index, index, SqlGraphRelationship::RequiredByArg);
+                        found = true;
+                        break;
+                    }
+                }
+            }
             if !found {
                 let builtin_index = builtin_types
                     .get(arg.full_path)
@@ -948,7 +1095,9 @@ fn connect_externs(
             }
         }
         match &item.fn_return {
-            PgExternReturnEntity::None | PgExternReturnEntity::Trigger => (),
+            PgExternReturnEntity::None
+            | PgExternReturnEntity::Trigger
+            | PgExternReturnEntity::EventTrigger => (),
             PgExternReturnEntity::Type { id, full_path, .. }
             | PgExternReturnEntity::SetOf { id, full_path, .. } => {
                 let mut found = false;
@@ -970,6 +1119,26 @@ fn connect_externs(
                         }
                     }
                 }
+                if !found {
+                    for (ty_item, &ty_index) in ranges {
+                        if ty_item.id_matches(id) {
+                            tracing::debug!(from = %item.rust_identifier(), to = %ty_item.rust_identifier(), "Adding Extern after Range (due to return) edge");
+                            graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredByReturn);
+                            found = true;
+                            break;
+                        }
+                    }
+                }
+                if !found {
+                    for (ty_item, &ty_index) in composites {
+                        if ty_item.id_matches(id) {
+                            tracing::debug!(from = %item.rust_identifier(), to = %ty_item.rust_identifier(), "Adding Extern after Composite (due to return) edge");
+                            graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredByReturn);
+                            found = true;
+                            break;
+                        }
+                    }
+                }
                 if !found {
                     let builtin_index = builtin_types
                         .get(&full_path.to_string())
@@ -1022,6 +1191,34 @@ fn connect_externs(
                             }
                         }
                     }
+                    if !found {
+                        for (ty_item, &ty_index) in ranges {
+                            if ty_item.id_matches(&iterated_return.0) {
+                                tracing::debug!(from = %item.rust_identifier(), to = %ty_item.rust_identifier(), "Adding Extern after Range (due to return) edge");
+                                graph.add_edge(
+                                    ty_index,
+                                    index,
+                                    SqlGraphRelationship::RequiredByReturn,
+                                );
+                                found = true;
+                                break;
+                            }
+                        }
+                    }
+                    if !found {
+                        for (ty_item, &ty_index) in composites {
+                            if ty_item.id_matches(&iterated_return.0) {
+                                tracing::debug!(from = %item.rust_identifier(), to = %ty_item.rust_identifier(), "Adding Extern after Composite (due to return) edge");
+                                graph.add_edge(
+                                    ty_index,
+                                    index,
+                                    SqlGraphRelationship::RequiredByReturn,
+                                );
+                                found = true;
+                                break;
+                            }
+                        }
+                    }
                     if !found {
                         let builtin_index = builtin_types
                             .get(&iterated_return.1.to_string())
@@ -1198,6 +1395,130 @@ fn connect_hashes(
     }
 }
 
+#[tracing::instrument(level = "error", skip_all)]
+fn initialize_ranges(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    ranges: Vec<PostgresRangeEntity>,
+) -> eyre::Result<HashMap<PostgresRangeEntity, NodeIndex>> {
+    let mut mapped_ranges = HashMap::default();
+    for item in ranges {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_ranges.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_ranges)
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn connect_ranges(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    ranges: &HashMap<PostgresRangeEntity, NodeIndex>,
+    schemas: &HashMap<SchemaEntity, NodeIndex>,
+    types: &HashMap<PostgresTypeEntity, NodeIndex>,
+    ords: &HashMap<PostgresOrdEntity, NodeIndex>,
+) {
+    for (item, &index) in ranges {
+        make_schema_connection(
+            graph,
+            "Range",
+            index,
+            &item.rust_identifier(),
+            item.module_path,
+            schemas,
+        );
+
+        for (ty_item, &ty_index) in types {
+            if ty_item.id_matches(&item.id) {
+                tracing::debug!(from = %item.rust_identifier(), to = %ty_item.rust_identifier(), "Adding Range after Type edge");
+                graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredBy);
+                break;
+            }
+        }
+
+        // A range's subtype must have a default btree operator class
+        for (ord_item, &ord_index) in ords {
+            if ord_item.id == item.id {
+                tracing::debug!(from = %item.rust_identifier(), to = %ord_item.rust_identifier(), "Adding Range after Ord edge");
+                graph.add_edge(ord_index, index, SqlGraphRelationship::RequiredBy);
+                break;
+            }
+        }
+    }
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn initialize_composites(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    composites: Vec<PostgresCompositeEntity>,
+) -> eyre::Result<HashMap<PostgresCompositeEntity, NodeIndex>> {
+    let mut mapped_composites = HashMap::default();
+    for item in composites {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_composites.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_composites)
+}
+
+#[tracing::instrument(level = "error", skip_all)]
+fn connect_composites(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    composites: &HashMap<PostgresCompositeEntity, NodeIndex>,
+    schemas: &HashMap<SchemaEntity, NodeIndex>,
+    types: &HashMap<PostgresTypeEntity, NodeIndex>,
+    enums: &HashMap<PostgresEnumEntity, NodeIndex>,
+    ranges: &HashMap<PostgresRangeEntity, NodeIndex>,
+) {
+    for (item, &index) in composites {
+        make_schema_connection(
+            graph,
+            "Composite",
+            index,
+            &item.rust_identifier(),
+            item.module_path,
+            schemas,
+        );
+
+        // Every attribute's type must exist before the composite type does
+        for field in &item.fields {
+            let required = types
+                .iter()
+                .find(|(ty_item, _)| ty_item.id_matches(&field.ty_id))
+                .map(|(ty_item, &ty_index)| (ty_item.rust_identifier(), ty_index))
+                .or_else(|| {
+                    enums
+                        .iter()
+                        .find(|(ty_item, _)| ty_item.id_matches(&field.ty_id))
+                        .map(|(ty_item, &ty_index)| (ty_item.rust_identifier(), ty_index))
+                })
+                .or_else(|| {
+                    ranges
+                        .iter()
+                        .find(|(ty_item, _)| ty_item.id_matches(&field.ty_id))
+                        .map(|(ty_item, &ty_index)| (ty_item.rust_identifier(), ty_index))
+                })
+                .or_else(|| {
+                    composites
+                        .iter()
+                        .find(|(ty_item, _)| ty_item.id_matches(&field.ty_id))
+                        .map(|(ty_item, &ty_index)| (ty_item.rust_identifier(), ty_index))
+                });
+            if let Some((ty_identifier, ty_index)) = required {
+                tracing::debug!(from = %item.rust_identifier(), to = %ty_identifier, "Adding Composite after field type edge");
+                graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredBy);
+            }
+        }
+    }
+}
+
 fn initialize_aggregates(
     graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
     root: NodeIndex,
@@ -0,0 +1,138 @@
+use crate::sql_entity_graph::{
+    mapping::RustSqlMapping,
+    pgx_sql::PgxSql,
+    to_sql::{entity::ToSqlConfigEntity, ToSql},
+    SqlGraphEntity, SqlGraphIdentifier,
+};
+use eyre::eyre;
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+/// The output of a [`PostgresComposite`](crate::sql_entity_graph::postgres_composite::PostgresComposite) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostgresCompositeEntity {
+    pub name: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+    pub mappings: std::collections::HashSet<RustSqlMapping>,
+    pub fields: Vec<PostgresCompositeFieldEntity>,
+    pub to_sql_config: ToSqlConfigEntity,
+}
+
+/// A single attribute of a [`PostgresCompositeEntity`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PostgresCompositeFieldEntity {
+    pub name: &'static str,
+    pub ty_source: &'static str,
+    pub ty_id: core::any::TypeId,
+    pub full_path: &'static str,
+}
+
+impl PostgresCompositeEntity {
+    pub fn id_matches(&self, candidate: &core::any::TypeId) -> bool {
+        self.mappings.iter().any(|tester| *candidate == tester.id)
+    }
+}
+
+impl Hash for PostgresCompositeEntity {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.full_path.hash(state);
+    }
+}
+
+impl Ord for PostgresCompositeEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.file
+            .cmp(other.file)
+            .then_with(|| self.file.cmp(other.file))
+    }
+}
+
+impl PartialOrd for PostgresCompositeEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Into<SqlGraphEntity> for PostgresCompositeEntity {
+    fn into(self) -> SqlGraphEntity {
+        SqlGraphEntity::Composite(self)
+    }
+}
+
+impl SqlGraphIdentifier for PostgresCompositeEntity {
+    fn dot_identifier(&self) -> String {
+        format!("composite {}", self.full_path)
+    }
+    fn rust_identifier(&self) -> String {
+        self.full_path.to_string()
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for PostgresCompositeEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &PgxSql) -> eyre::Result<String> {
+        let self_index = context.composites[self];
+        let mut fields = Vec::new();
+        for field in &self.fields {
+            let graph_index = context
+                .graph
+                .neighbors_undirected(self_index)
+                .find(|neighbor| match &context.graph[*neighbor] {
+                    SqlGraphEntity::Type(ty) => ty.id_matches(&field.ty_id),
+                    SqlGraphEntity::Enum(en) => en.id_matches(&field.ty_id),
+                    SqlGraphEntity::Range(range) => range.id_matches(&field.ty_id),
+                    SqlGraphEntity::Composite(composite) => composite.id_matches(&field.ty_id),
+                    _ => false,
+                });
+            let sql_type = context
+                .rust_to_sql(field.ty_id, field.ty_source, field.full_path)
+                .ok_or_else(|| {
+                    eyre!(
+                        "Failed to map field `{}` type `{}` to SQL type while building composite type `{}`.",
+                        field.name,
+                        field.full_path,
+                        self.name
+                    )
+                })?;
+            fields.push(format!(
+                "\t\"{name}\" {schema_prefix}{sql_type} /* {full_path} */",
+                name = field.name,
+                schema_prefix = graph_index
+                    .map(|graph_index| context.schema_prefix_for(&graph_index))
+                    .unwrap_or_default(),
+                sql_type = sql_type,
+                full_path = field.full_path,
+            ));
+        }
+        let sql = format!(
+            "\n\
+                    -- {file}:{line}\n\
+                    -- {full_path}\n\
+                    CREATE TYPE {schema}{name} AS (\n\
+                        {fields}\
+                    );\
+                ",
+            schema = context.schema_prefix_for(&self_index),
+            full_path = self.full_path,
+            file = self.file,
+            line = self.line,
+            name = self.name,
+            fields = fields.join(",\n") + "\n",
+        );
+        tracing::trace!(%sql);
+        Ok(sql)
+    }
+}
@@ -0,0 +1,158 @@
+pub mod entity;
+
+use crate::sql_entity_graph::ToSqlConfig;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    DeriveInput, Generics, Ident, ItemStruct,
+};
+
+/// A parsed `#[derive(PostgresComposite)]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the declaration for a `pgx::datum::sql_entity_graph::PostgresCompositeEntity`.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PostgresComposite;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PostgresComposite = parse_quote! {
+///     #[derive(PostgresComposite)]
+///     struct Example {
+///         demo: i32,
+///     }
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PostgresComposite {
+    name: Ident,
+    fields: Vec<(Ident, syn::Type)>,
+    to_sql_config: ToSqlConfig,
+}
+
+impl PostgresComposite {
+    pub fn new(
+        name: Ident,
+        generics: Generics,
+        fields: syn::Fields,
+        to_sql_config: ToSqlConfig,
+    ) -> Result<Self, syn::Error> {
+        // A composite value is built from, and read into, an owned struct
+        if !generics.params.is_empty() {
+            return Err(syn::Error::new(
+                name.span(),
+                "composite types cannot have generic parameters or lifetimes",
+            ));
+        }
+        let fields = match fields {
+            syn::Fields::Named(named) => named
+                .named
+                .into_iter()
+                .map(|field| (field.ident.unwrap(), field.ty))
+                .collect(),
+            syn::Fields::Unnamed(_) | syn::Fields::Unit => {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "expected a struct with named fields",
+                ))
+            }
+        };
+        Ok(Self {
+            name,
+            fields,
+            to_sql_config,
+        })
+    }
+
+    pub fn from_derive_input(derive_input: DeriveInput) -> Result<Self, syn::Error> {
+        let to_sql_config =
+            ToSqlConfig::from_attributes(derive_input.attrs.as_slice())?.unwrap_or_default();
+        let data_struct = match derive_input.data {
+            syn::Data::Struct(data_struct) => data_struct,
+            syn::Data::Union(_) | syn::Data::Enum(_) => {
+                return Err(syn::Error::new(
+                    derive_input.ident.span(),
+                    "expected struct",
+                ))
+            }
+        };
+        Self::new(
+            derive_input.ident,
+            derive_input.generics,
+            data_struct.fields,
+            to_sql_config,
+        )
+    }
+
+    /// The named fields of the struct, which become the attributes of the composite type
+    pub fn fields(&self) -> &[(Ident, syn::Type)] {
+        &self.fields
+    }
+}
+
+impl Parse for PostgresComposite {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let parsed: ItemStruct = input.parse()?;
+        let to_sql_config =
+            ToSqlConfig::from_attributes(parsed.attrs.as_slice())?.unwrap_or_default();
+        Self::new(parsed.ident, parsed.generics, parsed.fields, to_sql_config)
+    }
+}
+
+impl ToTokens for PostgresComposite {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let name = &self.name;
+        let fields = self.fields.iter().map(|(field_name, field_ty)| {
+            let ty_source = field_ty.to_token_stream().to_string().replace(" ", "");
+            quote! {
+                ::pgx::utils::sql_entity_graph::PostgresCompositeFieldEntity {
+                    name: stringify!(#field_name),
+                    ty_source: #ty_source,
+                    ty_id: TypeId::of::<#field_ty>(),
+                    full_path: core::any::type_name::<#field_ty>(),
+                }
+            }
+        });
+        let sql_graph_entity_fn_name = syn::Ident::new(
+            &format!("__pgx_internals_composite_{}", name),
+            Span::call_site(),
+        );
+
+        let to_sql_config = &self.to_sql_config;
+
+        let inv = quote! {
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn  #sql_graph_entity_fn_name() -> ::pgx::utils::sql_entity_graph::SqlGraphEntity {
+                use core::any::TypeId;
+                extern crate alloc;
+                use alloc::vec::Vec;
+                use alloc::vec;
+                let mut mappings = Default::default();
+                <#name as ::pgx::datum::WithTypeIds>::register_with_refs(&mut mappings, stringify!(#name).to_string());
+                ::pgx::datum::WithSizedTypeIds::<#name>::register_sized_with_refs(&mut mappings, stringify!(#name).to_string());
+                ::pgx::datum::WithArrayTypeIds::<#name>::register_array_with_refs(&mut mappings, stringify!(#name).to_string());
+
+                let submission = ::pgx::utils::sql_entity_graph::PostgresCompositeEntity {
+                    name: stringify!(#name),
+                    file: file!(),
+                    line: line!(),
+                    module_path: module_path!(),
+                    full_path: core::any::type_name::<#name>(),
+                    mappings,
+                    fields: vec![ #( #fields ),* ],
+                    to_sql_config: #to_sql_config,
+                };
+                ::pgx::utils::sql_entity_graph::SqlGraphEntity::Composite(submission)
+            }
+        };
+        tokens.append_all(inv);
+    }
+}
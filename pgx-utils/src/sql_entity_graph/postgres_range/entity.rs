@@ -0,0 +1,104 @@
+use crate::sql_entity_graph::{
+    mapping::RustSqlMapping,
+    pgx_sql::PgxSql,
+    to_sql::{entity::ToSqlConfigEntity, ToSql},
+    SqlGraphEntity, SqlGraphIdentifier,
+};
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+/// The output of a [`PostgresRange`](crate::sql_entity_graph::postgres_range::PostgresRange) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostgresRangeEntity {
+    pub name: &'static str,
+    pub range_name: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+    pub id: core::any::TypeId,
+    pub mappings: std::collections::HashSet<RustSqlMapping>,
+    pub to_sql_config: ToSqlConfigEntity,
+}
+
+impl PostgresRangeEntity {
+    pub fn id_matches(&self, candidate: &core::any::TypeId) -> bool {
+        self.mappings.iter().any(|tester| *candidate == tester.id)
+    }
+}
+
+impl Hash for PostgresRangeEntity {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.full_path.hash(state);
+    }
+}
+
+impl Ord for PostgresRangeEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.file
+            .cmp(other.file)
+            .then_with(|| self.file.cmp(other.file))
+    }
+}
+
+impl PartialOrd for PostgresRangeEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Into<SqlGraphEntity> for PostgresRangeEntity {
+    fn into(self) -> SqlGraphEntity {
+        SqlGraphEntity::Range(self)
+    }
+}
+
+impl SqlGraphIdentifier for PostgresRangeEntity {
+    fn dot_identifier(&self) -> String {
+        format!("range {}", self.full_path)
+    }
+    fn rust_identifier(&self) -> String {
+        self.full_path.to_string()
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for PostgresRangeEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &PgxSql) -> eyre::Result<String> {
+        let self_index = context.ranges[self];
+        let subtype_schema = context
+            .types
+            .iter()
+            .find(|(ty, _)| ty.id_matches(&self.id))
+            .map(|(_, ty_index)| context.schema_prefix_for(ty_index))
+            .unwrap_or_default();
+        let sql = format!(
+            "\n\
+                -- {file}:{line}\n\
+                -- {full_path}\n\
+                CREATE TYPE {schema}{range_name} AS RANGE (\n\
+                    \tSUBTYPE = {subtype_schema}{name}\n\
+                );\
+            ",
+            file = self.file,
+            line = self.line,
+            full_path = self.full_path,
+            schema = context.schema_prefix_for(&self_index),
+            range_name = self.range_name,
+            subtype_schema = subtype_schema,
+            name = self.name,
+        );
+        tracing::trace!(%sql);
+        Ok(sql)
+    }
+}
@@ -0,0 +1,123 @@
+pub mod entity;
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    DeriveInput, Ident,
+};
+
+use crate::sql_entity_graph::ToSqlConfig;
+
+/// A parsed `#[derive(PostgresRange)]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the declaration for a `pgx::datum::sql_entity_graph::InventoryPostgresRange`.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PostgresRange;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PostgresRange = parse_quote! {
+///     #[derive(PostgresRange)]
+///     struct Example {
+///         demo: i32,
+///     }
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PostgresRange {
+    pub name: Ident,
+    pub to_sql_config: ToSqlConfig,
+}
+
+impl PostgresRange {
+    pub fn new(name: Ident, to_sql_config: ToSqlConfig) -> Self {
+        Self {
+            name,
+            to_sql_config,
+        }
+    }
+
+    pub fn from_derive_input(derive_input: DeriveInput) -> Result<Self, syn::Error> {
+        let to_sql_config =
+            ToSqlConfig::from_attributes(derive_input.attrs.as_slice())?.unwrap_or_default();
+        Ok(Self::new(derive_input.ident, to_sql_config))
+    }
+
+    /// The name of the range type in SQL, which is the name of the subtype suffixed with `Range`
+    pub fn range_name(&self) -> String {
+        format!("{}Range", self.name)
+    }
+}
+
+impl Parse for PostgresRange {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        use syn::Item;
+
+        let parsed = input.parse()?;
+        let (ident, attrs) = match &parsed {
+            Item::Enum(item) => (item.ident.clone(), item.attrs.as_slice()),
+            Item::Struct(item) => (item.ident.clone(), item.attrs.as_slice()),
+            _ => return Err(syn::Error::new(input.span(), "expected enum or struct")),
+        };
+        let to_sql_config = ToSqlConfig::from_attributes(attrs)?.unwrap_or_default();
+        Ok(Self::new(ident, to_sql_config))
+    }
+}
+
+impl ToTokens for PostgresRange {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let name = &self.name;
+        let range_name = self.range_name();
+        let sql_graph_entity_fn_name = syn::Ident::new(
+            &format!("__pgx_internals_range_{}", self.name),
+            Span::call_site(),
+        );
+        let to_sql_config = &self.to_sql_config;
+        let inv = quote! {
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn  #sql_graph_entity_fn_name() -> ::pgx::utils::sql_entity_graph::SqlGraphEntity {
+                use core::any::TypeId;
+                extern crate alloc;
+                use alloc::vec::Vec;
+                use alloc::vec;
+                use alloc::string::{String, ToString};
+
+                let mut mappings = Default::default();
+                <::pgx::datum::Range<#name> as ::pgx::datum::WithTypeIds>::register_with_refs(
+                    &mut mappings,
+                    #range_name.to_string()
+                );
+                ::pgx::datum::WithSizedTypeIds::<::pgx::datum::Range<#name>>::register_sized_with_refs(
+                    &mut mappings,
+                    #range_name.to_string()
+                );
+                ::pgx::datum::WithArrayTypeIds::<::pgx::datum::Range<#name>>::register_array_with_refs(
+                    &mut mappings,
+                    #range_name.to_string()
+                );
+                let submission = ::pgx::utils::sql_entity_graph::PostgresRangeEntity {
+                    name: stringify!(#name),
+                    range_name: #range_name,
+                    file: file!(),
+                    line: line!(),
+                    full_path: core::any::type_name::<#name>(),
+                    module_path: module_path!(),
+                    id: TypeId::of::<#name>(),
+                    mappings,
+                    to_sql_config: #to_sql_config,
+                };
+                ::pgx::utils::sql_entity_graph::SqlGraphEntity::Range(submission)
+            }
+        };
+        tokens.append_all(inv);
+    }
+}
@@ -1,11 +1,13 @@
 use crate::sql_entity_graph::{
     mapping::RustSqlMapping,
+    pg_extern::entity::PgExternEntity,
     pgx_sql::PgxSql,
     to_sql::{entity::ToSqlConfigEntity, ToSql},
     SqlGraphEntity, SqlGraphIdentifier,
 };
 
 use eyre::eyre;
+use petgraph::stable_graph::NodeIndex;
 use std::{
     cmp::Ordering,
     hash::{Hash, Hasher},
@@ -24,6 +26,21 @@ pub struct PostgresTypeEntity {
     pub in_fn_module_path: String,
     pub out_fn: &'static str,
     pub out_fn_module_path: String,
+    /// The binary send function, which shares the type's module path
+    pub send_fn: Option<&'static str>,
+    /// The binary receive function, which shares the type's module path
+    pub recv_fn: Option<&'static str>,
+    /// The type modifier input function, which shares the type's module path
+    pub typmod_in_fn: Option<&'static str>,
+    /// The type modifier output function, which shares the type's module path
+    pub typmod_out_fn: Option<&'static str>,
+    /// The length coercion cast function, which shares the type's module path
+    pub typmod_coerce_fn: Option<&'static str>,
+    /// The `INTERNALLENGTH` of a fixed-size type, or `None` for `variable`
+    pub internal_length: Option<usize>,
+    pub passed_by_value: bool,
+    pub alignment: Option<&'static str>,
+    pub storage: &'static str,
     pub to_sql_config: ToSqlConfigEntity,
 }
 
@@ -51,6 +68,46 @@ impl PostgresTypeEntity {
     pub fn id_matches(&self, candidate: &core::any::TypeId) -> bool {
         self.mappings.iter().any(|tester| *candidate == tester.id)
     }
+
+    /// Is `func` one of the functions this type's `CREATE TYPE` statement emits itself
+    pub fn is_support_fn(&self, func: &PgExternEntity) -> bool {
+        let is_in_fn = func.full_path.starts_with(&self.in_fn_module_path)
+            && func.full_path.ends_with(self.in_fn);
+        let is_out_fn = func.full_path.starts_with(&self.out_fn_module_path)
+            && func.full_path.ends_with(self.out_fn);
+        let is_local_support_fn = [
+            self.send_fn,
+            self.recv_fn,
+            self.typmod_in_fn,
+            self.typmod_out_fn,
+            self.typmod_coerce_fn,
+        ]
+        .iter()
+        .flatten()
+        .any(|fn_name| func.full_path == format!("{}::{}", self.module_path, fn_name));
+        is_in_fn || is_out_fn || is_local_support_fn
+    }
+
+    /// Find the graph node of the function named `fn_name` in this type's module
+    fn find_fn<'a>(
+        &self,
+        context: &'a PgxSql,
+        self_index: NodeIndex,
+        fn_name: &str,
+    ) -> eyre::Result<(NodeIndex, &'a PgExternEntity, String)> {
+        let fn_path = format!("{}::{}", self.module_path, fn_name);
+        context
+            .graph
+            .neighbors_undirected(self_index)
+            .find_map(|neighbor| match &context.graph[neighbor] {
+                SqlGraphEntity::Function(func) if func.full_path == fn_path => {
+                    Some((neighbor, func))
+                }
+                _ => None,
+            })
+            .map(|(neighbor, func)| (neighbor, func, fn_path.clone()))
+            .ok_or_else(|| eyre!("Could not find `{}` graph entity.", fn_path))
+    }
 }
 
 impl Into<SqlGraphEntity> for PostgresTypeEntity {
@@ -90,7 +147,13 @@ impl ToSql for PostgresTypeEntity {
         // - CREATE TYPE;
         // - CREATE FUNCTION _in;
         // - CREATE FUNCTION _out;
+        // - CREATE FUNCTION _recv; (optional)
+        // - CREATE FUNCTION _send; (optional)
+        // - CREATE FUNCTION _typmod_in; (optional)
+        // - CREATE FUNCTION _typmod_out; (optional)
         // - CREATE TYPE (...);
+        // - CREATE FUNCTION _typmod_coerce; (optional)
+        // - CREATE CAST (optional)
 
         let in_fn_module_path = if !item.in_fn_module_path.is_empty() {
             item.in_fn_module_path.clone()
@@ -160,6 +223,76 @@ impl ToSql for PostgresTypeEntity {
         let out_fn_sql = out_fn.to_sql(context)?;
         tracing::trace!(%out_fn_sql);
 
+        let send_recv = match (item.send_fn, item.recv_fn) {
+            (Some(send_fn), Some(recv_fn)) => {
+                let (recv_fn_graph_index, recv_fn_entity, recv_fn_path) =
+                    item.find_fn(context, self_index, recv_fn)?;
+                let (send_fn_graph_index, send_fn_entity, send_fn_path) =
+                    item.find_fn(context, self_index, send_fn)?;
+                Some((
+                    recv_fn_entity.to_sql(context)? + "\n" + &send_fn_entity.to_sql(context)?,
+                    format!(
+                        "\tRECEIVE = {schema_prefix_recv_fn}{recv_fn}, /* {recv_fn_path} */\n\
+                         \tSEND = {schema_prefix_send_fn}{send_fn}, /* {send_fn_path} */\n",
+                        schema_prefix_recv_fn = context.schema_prefix_for(&recv_fn_graph_index),
+                        recv_fn = recv_fn,
+                        recv_fn_path = recv_fn_path,
+                        schema_prefix_send_fn = context.schema_prefix_for(&send_fn_graph_index),
+                        send_fn = send_fn,
+                        send_fn_path = send_fn_path,
+                    ),
+                ))
+            }
+            _ => None,
+        };
+
+        let typmod = match (item.typmod_in_fn, item.typmod_out_fn, item.typmod_coerce_fn) {
+            (Some(typmod_in_fn), Some(typmod_out_fn), Some(typmod_coerce_fn)) => {
+                let (typmod_in_fn_graph_index, typmod_in_fn_entity, typmod_in_fn_path) =
+                    item.find_fn(context, self_index, typmod_in_fn)?;
+                let (typmod_out_fn_graph_index, typmod_out_fn_entity, typmod_out_fn_path) =
+                    item.find_fn(context, self_index, typmod_out_fn)?;
+                let (typmod_coerce_fn_graph_index, typmod_coerce_fn_entity, typmod_coerce_fn_path) =
+                    item.find_fn(context, self_index, typmod_coerce_fn)?;
+                let cast = format!(
+                    "\n\
+                        -- {file}:{line}\n\
+                        -- {typmod_coerce_fn_path}\n\
+                        CREATE CAST ({schema}{name} AS {schema}{name})\n\
+                        \tWITH FUNCTION {schema_prefix_coerce_fn}{typmod_coerce_fn}({schema}{name}, integer, boolean)\n\
+                        \tAS IMPLICIT;\
+                    ",
+                    file = item.file,
+                    line = item.line,
+                    typmod_coerce_fn_path = typmod_coerce_fn_path,
+                    schema = context.schema_prefix_for(&self_index),
+                    name = item.name,
+                    schema_prefix_coerce_fn =
+                        context.schema_prefix_for(&typmod_coerce_fn_graph_index),
+                    typmod_coerce_fn = typmod_coerce_fn,
+                );
+                Some((
+                    typmod_in_fn_entity.to_sql(context)?
+                        + "\n"
+                        + &typmod_out_fn_entity.to_sql(context)?,
+                    format!(
+                        "\tTYPMOD_IN = {schema_prefix_typmod_in_fn}{typmod_in_fn}, /* {typmod_in_fn_path} */\n\
+                         \tTYPMOD_OUT = {schema_prefix_typmod_out_fn}{typmod_out_fn}, /* {typmod_out_fn_path} */\n",
+                        schema_prefix_typmod_in_fn =
+                            context.schema_prefix_for(&typmod_in_fn_graph_index),
+                        typmod_in_fn = typmod_in_fn,
+                        typmod_in_fn_path = typmod_in_fn_path,
+                        schema_prefix_typmod_out_fn =
+                            context.schema_prefix_for(&typmod_out_fn_graph_index),
+                        typmod_out_fn = typmod_out_fn,
+                        typmod_out_fn_path = typmod_out_fn_path,
+                    ),
+                    typmod_coerce_fn_entity.to_sql(context)? + "\n" + &cast,
+                ))
+            }
+            _ => None,
+        };
+
         let shell_type = format!(
             "\n\
                                 -- {file}:{line}\n\
@@ -178,10 +311,14 @@ impl ToSql for PostgresTypeEntity {
                                 -- {file}:{line}\n\
                                 -- {full_path}\n\
                                 CREATE TYPE {schema}{name} (\n\
-                                    \tINTERNALLENGTH = variable,\n\
+                                    \tINTERNALLENGTH = {internal_length},\n\
                                     \tINPUT = {schema_prefix_in_fn}{in_fn}, /* {in_fn_path} */\n\
                                     \tOUTPUT = {schema_prefix_out_fn}{out_fn}, /* {out_fn_path} */\n\
-                                    \tSTORAGE = extended\n\
+                                    {send_recv}\
+                                    {typmod}\
+                                    {passed_by_value}\
+                                    {alignment}\
+                                    \tSTORAGE = {storage}\n\
                                 );\
                             ",
                                         full_path = item.full_path,
@@ -195,9 +332,31 @@ impl ToSql for PostgresTypeEntity {
                                         schema_prefix_out_fn = context.schema_prefix_for(&out_fn_graph_index),
                                         out_fn = item.out_fn,
                                         out_fn_path = out_fn_path,
+                                        send_recv = send_recv.as_ref().map(|(_, sql)| sql.as_str()).unwrap_or_default(),
+                                        internal_length = item.internal_length.map(|len| len.to_string()).unwrap_or_else(|| "variable".to_string()),
+                                        passed_by_value = if item.passed_by_value { "\tPASSEDBYVALUE,\n" } else { "" },
+                                        alignment = item.alignment.map(|alignment| format!("\tALIGNMENT = {},\n", alignment)).unwrap_or_default(),
+                                        storage = item.storage,
+                                        typmod = typmod.as_ref().map(|(_, sql, _)| sql.as_str()).unwrap_or_default(),
         );
         tracing::trace!(sql = %materialized_type);
 
-        Ok(shell_type + "\n" + &in_fn_sql + "\n" + &out_fn_sql + "\n" + &materialized_type)
+        let send_recv_fn_sql = send_recv
+            .map(|(fn_sql, _)| fn_sql + "\n")
+            .unwrap_or_default();
+        let (typmod_fn_sql, typmod_cast_sql) = typmod
+            .map(|(fn_sql, _, cast_sql)| (fn_sql + "\n", "\n".to_string() + &cast_sql))
+            .unwrap_or_default();
+
+        Ok(shell_type
+            + "\n"
+            + &in_fn_sql
+            + "\n"
+            + &out_fn_sql
+            + "\n"
+            + &send_recv_fn_sql
+            + &typmod_fn_sql
+            + &materialized_type
+            + &typmod_cast_sql)
     }
 }
@@ -8,10 +8,14 @@ use std::{
 };
 use syn::{
     parse::{Parse, ParseStream},
-    DeriveInput, Generics, ItemStruct,
+    spanned::Spanned,
+    Attribute, DeriveInput, Generics, ItemStruct,
 };
 
-use crate::sql_entity_graph::ToSqlConfig;
+use crate::sql_entity_graph::{
+    pgx_attribute::{ArgValue, NameValueArg, PgxArg, PgxAttribute},
+    ToSqlConfig,
+};
 
 /// A parsed `#[derive(PostgresType)]` item.
 ///
@@ -41,6 +45,9 @@ pub struct PostgresType {
     generics: Generics,
     in_fn: Ident,
     out_fn: Ident,
+    send_recv_fns: Option<(Ident, Ident)>,
+    typmod_fns: Option<TypmodFns>,
+    layout: PostgresTypeLayout,
     to_sql_config: ToSqlConfig,
 }
 
@@ -57,10 +64,137 @@ impl PostgresType {
             name,
             in_fn,
             out_fn,
+            send_recv_fns: None,
+            typmod_fns: None,
+            layout: Default::default(),
             to_sql_config,
         }
     }
 
+    /// Also use binary send/receive functions, which are named after the type like the in/out functions
+    pub fn with_send_recv_fns(mut self) -> Self {
+        let funcname_send = Ident::new(
+            &format!("{}_send", self.name).to_lowercase(),
+            self.name.span(),
+        );
+        let funcname_recv = Ident::new(
+            &format!("{}_recv", self.name).to_lowercase(),
+            self.name.span(),
+        );
+        self.send_recv_fns = Some((funcname_send, funcname_recv));
+        self
+    }
+
+    /// The names of the `(send, receive)` functions, if the type has them
+    pub fn send_recv_fns(&self) -> Option<(&Ident, &Ident)> {
+        self.send_recv_fns.as_ref().map(|(send, recv)| (send, recv))
+    }
+
+    /// Also use type modifier functions, which are named after the type like the in/out functions
+    pub fn with_typmod_fns(mut self) -> Self {
+        let ident = |suffix: &str| {
+            Ident::new(
+                &format!("{}_{}", self.name, suffix).to_lowercase(),
+                self.name.span(),
+            )
+        };
+        self.typmod_fns = Some(TypmodFns {
+            typmod_in: ident("typmod_in"),
+            typmod_out: ident("typmod_out"),
+            coerce: ident("typmod_coerce"),
+        });
+        self
+    }
+
+    /// The names of the type modifier functions, if the type has them
+    pub fn typmod_fns(&self) -> Option<&TypmodFns> {
+        self.typmod_fns.as_ref()
+    }
+
+    /// How Postgres stores the type
+    pub fn layout(&self) -> &PostgresTypeLayout {
+        &self.layout
+    }
+
+    /// Apply the `#[pgx(...)]` arguments found in these attributes, such as `sendrecv`, `typmod`,
+    /// `fixed_size`, `passedbyvalue`, `alignment = ...`, and `storage = ...`
+    fn with_flags(self, attrs: &[Attribute]) -> Result<Self, syn::Error> {
+        let mut postgres_type = self;
+        let mut send_recv = false;
+        let mut typmod = false;
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("pgx")) {
+            let attr = attr.parse_args::<PgxAttribute>()?;
+            for arg in attr.args {
+                match arg {
+                    PgxArg::Path(path) if path.is_ident("sendrecv") => send_recv = true,
+                    PgxArg::Path(path) if path.is_ident("typmod") => typmod = true,
+                    PgxArg::Path(path) if path.is_ident("fixed_size") => {
+                        postgres_type.layout.fixed_size = true
+                    }
+                    PgxArg::Path(path) if path.is_ident("passedbyvalue") => {
+                        postgres_type.layout.fixed_size = true;
+                        postgres_type.layout.passed_by_value = true;
+                    }
+                    PgxArg::NameValue(arg) if arg.path.is_ident("alignment") => {
+                        postgres_type.layout.alignment = Some(Self::layout_option(
+                            &arg,
+                            &["char", "int2", "int4", "double"],
+                        )?)
+                    }
+                    PgxArg::NameValue(arg) if arg.path.is_ident("storage") => {
+                        postgres_type.layout.storage = Some(Self::layout_option(
+                            &arg,
+                            &["plain", "external", "extended", "main"],
+                        )?)
+                    }
+                    // Other arguments, like `sql`, are handled by `ToSqlConfig`
+                    _ => (),
+                }
+            }
+        }
+        if postgres_type.layout.fixed_size
+            && !matches!(
+                postgres_type.layout.storage.as_deref(),
+                None | Some("plain")
+            )
+        {
+            return Err(syn::Error::new(
+                postgres_type.name.span(),
+                "fixed-size types must use `storage = plain`",
+            ));
+        }
+        if send_recv {
+            postgres_type = postgres_type.with_send_recv_fns();
+        }
+        if typmod {
+            postgres_type = postgres_type.with_typmod_fns();
+        }
+        Ok(postgres_type)
+    }
+
+    /// The value of an `alignment = ...` or `storage = ...` argument, which must be one of `allowed`
+    fn layout_option(arg: &NameValueArg, allowed: &[&str]) -> Result<String, syn::Error> {
+        let value = match &arg.value {
+            ArgValue::Lit(syn::Lit::Str(value)) => value.value(),
+            ArgValue::Path(path) if path.get_ident().is_some() => {
+                path.get_ident().unwrap().to_string()
+            }
+            _ => String::default(),
+        };
+        if allowed.contains(&value.as_str()) {
+            Ok(value)
+        } else {
+            Err(syn::Error::new(
+                arg.path.span(),
+                format!(
+                    "expected `{}` to be one of: {}",
+                    arg.path.get_ident().unwrap(),
+                    allowed.join(", ")
+                ),
+            ))
+        }
+    }
+
     pub fn from_derive_input(derive_input: DeriveInput) -> Result<Self, syn::Error> {
         let _data_struct = match derive_input.data {
             syn::Data::Struct(data_struct) => data_struct,
@@ -81,13 +215,14 @@ impl PostgresType {
             &format!("{}_out", derive_input.ident).to_lowercase(),
             derive_input.ident.span(),
         );
-        Ok(Self::new(
+        Self::new(
             derive_input.ident,
             derive_input.generics,
             funcname_in,
             funcname_out,
             to_sql_config,
-        ))
+        )
+        .with_flags(derive_input.attrs.as_slice())
     }
 
     pub fn inventory_fn_name(&self) -> String {
@@ -105,6 +240,30 @@ impl PostgresType {
     }
 }
 
+/// How Postgres stores a `#[derive(PostgresType)]`, from its `#[pgx(...)]` attributes.
+#[derive(Debug, Clone, Default)]
+pub struct PostgresTypeLayout {
+    /// The type's `INTERNALLENGTH` is the size of the Rust type, rather than `variable`
+    pub fixed_size: bool,
+    /// The type is `PASSEDBYVALUE`, which implies `fixed_size`
+    pub passed_by_value: bool,
+    /// The type's `ALIGNMENT`, if specified
+    pub alignment: Option<String>,
+    /// The type's `STORAGE`, if specified
+    pub storage: Option<String>,
+}
+
+/// The functions generated for a `#[pgx(typmod)]` type.
+#[derive(Debug, Clone)]
+pub struct TypmodFns {
+    /// Parses `cstring[]` modifiers into a typmod
+    pub typmod_in: Ident,
+    /// Renders a typmod as text
+    pub typmod_out: Ident,
+    /// The length coercion cast, which applies a typmod to a value
+    pub coerce: Ident,
+}
+
 impl Parse for PostgresType {
     fn parse(input: ParseStream) -> Result<Self, syn::Error> {
         let parsed: ItemStruct = input.parse()?;
@@ -118,13 +277,14 @@ impl Parse for PostgresType {
             &format!("{}_out", parsed.ident).to_lowercase(),
             parsed.ident.span(),
         );
-        Ok(Self::new(
+        Self::new(
             parsed.ident,
             parsed.generics,
             funcname_in,
             funcname_out,
             to_sql_config,
-        ))
+        )
+        .with_flags(parsed.attrs.as_slice())
     }
 }
 
@@ -139,6 +299,50 @@ impl ToTokens for PostgresType {
 
         let in_fn = &self.in_fn;
         let out_fn = &self.out_fn;
+        let (send_fn, recv_fn) = match &self.send_recv_fns {
+            Some((send_fn, recv_fn)) => (
+                quote! { Some(stringify!(#send_fn)) },
+                quote! { Some(stringify!(#recv_fn)) },
+            ),
+            None => (quote! { None }, quote! { None }),
+        };
+        let (typmod_in_fn, typmod_out_fn, typmod_coerce_fn) = match &self.typmod_fns {
+            Some(TypmodFns {
+                typmod_in,
+                typmod_out,
+                coerce,
+            }) => (
+                quote! { Some(stringify!(#typmod_in)) },
+                quote! { Some(stringify!(#typmod_out)) },
+                quote! { Some(stringify!(#coerce)) },
+            ),
+            None => (quote! { None }, quote! { None }, quote! { None }),
+        };
+
+        let internal_length = if self.layout.fixed_size {
+            quote! { Some(core::mem::size_of::<#name #ty_generics>()) }
+        } else {
+            quote! { None }
+        };
+        let passed_by_value = self.layout.passed_by_value;
+        let alignment = match &self.layout.alignment {
+            Some(alignment) => quote! { Some(#alignment) },
+            // Fixed-size types are read straight out of the tuple, so default to Rust's alignment
+            None if self.layout.fixed_size => quote! {
+                Some(match core::mem::align_of::<#name #ty_generics>() {
+                    1 => "char",
+                    2 => "int2",
+                    4 => "int4",
+                    _ => "double",
+                })
+            },
+            None => quote! { None },
+        };
+        let storage = match &self.layout.storage {
+            Some(storage) => quote! { #storage },
+            None if self.layout.fixed_size => quote! { "plain" },
+            None => quote! { "extended" },
+        };
 
         let sql_graph_entity_fn_name = syn::Ident::new(
             &format!("__pgx_internals_type_{}", self.name),
@@ -194,6 +398,15 @@ impl ToTokens for PostgresType {
                         let _ = path_items.pop(); // Drop the one we don't want.
                         path_items.join("::")
                     },
+                    send_fn: #send_fn,
+                    recv_fn: #recv_fn,
+                    typmod_in_fn: #typmod_in_fn,
+                    typmod_out_fn: #typmod_out_fn,
+                    typmod_coerce_fn: #typmod_coerce_fn,
+                    internal_length: #internal_length,
+                    passed_by_value: #passed_by_value,
+                    alignment: #alignment,
+                    storage: #storage,
                     to_sql_config: #to_sql_config,
                 };
                 ::pgx::utils::sql_entity_graph::SqlGraphEntity::Type(submission)
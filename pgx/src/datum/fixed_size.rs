@@ -0,0 +1,58 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Conversions for `#[derive(PostgresType)]` types with the `#[pgx(fixed_size)]` or
+//! `#[pgx(passedbyvalue)]` attributes, which Postgres stores without a varlena header
+use crate::{pg_sys, PgMemoryContexts};
+
+/// Read a fixed-size `T` out of a `Datum`.
+///
+/// When `passed_by_value` is true the `Datum` itself holds the value, otherwise it points to a
+/// copy of it.
+///
+/// ## Safety
+///
+/// This function is unsafe as it cannot guarantee that `datum` actually represents a `T`
+#[doc(hidden)]
+#[inline]
+pub unsafe fn fixed_size_from_datum<T: Copy>(datum: pg_sys::Datum, passed_by_value: bool) -> T {
+    if passed_by_value {
+        match std::mem::size_of::<T>() {
+            1 => std::mem::transmute_copy(&(datum as u8)),
+            2 => std::mem::transmute_copy(&(datum as u16)),
+            4 => std::mem::transmute_copy(&(datum as u32)),
+            8 => std::mem::transmute_copy(&(datum as u64)),
+            size => panic!("a {} byte type cannot be passed by value", size),
+        }
+    } else {
+        // Postgres aligns the value according to the type's ALIGNMENT, which might not be
+        // Rust's alignment for `T`
+        std::ptr::read_unaligned(datum as *const T)
+    }
+}
+
+/// Convert a fixed-size `T` into a `Datum`.
+///
+/// When `passed_by_value` is false, the value is copied into memory allocated in the
+/// `CurrentMemoryContext`.  `#[derive(PostgresType)]` ensures `T` has no padding, whose bytes
+/// would be read here.
+#[doc(hidden)]
+#[inline]
+pub fn fixed_size_into_datum<T: Copy>(value: T, passed_by_value: bool) -> pg_sys::Datum {
+    unsafe {
+        if passed_by_value {
+            match std::mem::size_of::<T>() {
+                1 => std::mem::transmute_copy::<T, u8>(&value) as pg_sys::Datum,
+                2 => std::mem::transmute_copy::<T, u16>(&value) as pg_sys::Datum,
+                4 => std::mem::transmute_copy::<T, u32>(&value) as pg_sys::Datum,
+                8 => std::mem::transmute_copy::<T, u64>(&value) as pg_sys::Datum,
+                size => panic!("a {} byte type cannot be passed by value", size),
+            }
+        } else {
+            let ptr = PgMemoryContexts::CurrentMemoryContext
+                .palloc(std::mem::size_of::<T>()) as *mut T;
+            std::ptr::write_unaligned(ptr, value);
+            ptr as pg_sys::Datum
+        }
+    }
+}
@@ -1,24 +1,392 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
-use crate::{
-    direct_function_call, direct_function_call_as_datum, pg_sys, pg_try, void_mut_ptr, FromDatum,
-    IntoDatum,
-};
+use crate::{pg_sys, FromDatum, IntoDatum, PgMemoryContexts};
 use serde::de::{Error, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::ffi::CStr;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt;
-use std::ops::Deref;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
 
-#[derive(Debug, Ord, PartialOrd, Eq, PartialEq)]
-pub struct Inet(pub String);
+/// The reasons constructing or parsing an [`Inet`] or [`Cidr`] can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkAddressError {
+    /// The string isn't a valid IPv4 or IPv6 address, with an optional `/masklen` suffix
+    InvalidAddress(String),
+    /// The mask length is larger than the number of bits in the address
+    InvalidMaskLength(u8),
+    /// A [`Cidr`] has bits set to the right of its mask
+    HostBitsSet,
+}
+
+impl fmt::Display for NetworkAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkAddressError::InvalidAddress(s) => {
+                write!(f, "invalid network address: {}", s)
+            }
+            NetworkAddressError::InvalidMaskLength(masklen) => {
+                write!(f, "invalid mask length: {}", masklen)
+            }
+            NetworkAddressError::HostBitsSet => {
+                write!(f, "cidr value has bits set to right of mask")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetworkAddressError {}
+
+/// A Postgres `inet`: an IPv4 or IPv6 host address, along with the mask length of its subnet
+///
+/// Unlike a [`Cidr`], the address may have bits set to the right of the mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Inet {
+    addr: IpAddr,
+    masklen: u8,
+}
+
+/// A Postgres `cidr`: an IPv4 or IPv6 network, which never has bits set to the right of its mask
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    addr: IpAddr,
+    masklen: u8,
+}
+
+/// The number of bits in an address of `addr`'s family
+fn max_masklen(addr: &IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+fn addr_to_bits(addr: &IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(addr) => u32::from(*addr) as u128,
+        IpAddr::V6(addr) => u128::from(*addr),
+    }
+}
+
+fn bits_to_addr(like: &IpAddr, bits: u128) -> IpAddr {
+    match like {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from(bits as u32)),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(bits)),
+    }
+}
 
-impl Deref for Inet {
-    type Target = str;
+/// The bits of the netmask for a `masklen` bit prefix of `addr`'s family
+fn netmask_bits(addr: &IpAddr, masklen: u8) -> u128 {
+    let max = max_masklen(addr) as u32;
+    let all = if max == 128 {
+        u128::MAX
+    } else {
+        (1u128 << max) - 1
+    };
+    let host = all.checked_shr(masklen as u32).unwrap_or(0);
+    all & !host
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+/// Does the network `addr/masklen` contain `other`, or is it the same network, like Postgres' `>>=`
+fn network_contains(addr: &IpAddr, masklen: u8, other: &IpAddr, other_masklen: u8) -> bool {
+    if max_masklen(addr) != max_masklen(other) || masklen > other_masklen {
+        return false;
+    }
+
+    let mask = netmask_bits(addr, masklen);
+    addr_to_bits(addr) & mask == addr_to_bits(other) & mask
+}
+
+/// Order networks the same way as Postgres' `network_cmp_internal()`: by family, then by the
+/// network bits they have in common, then by mask length, then by the entire address
+fn network_cmp(addr: &IpAddr, masklen: u8, other: &IpAddr, other_masklen: u8) -> Ordering {
+    max_masklen(addr)
+        .cmp(&max_masklen(other))
+        .then_with(|| {
+            let mask = netmask_bits(addr, masklen.min(other_masklen));
+            (addr_to_bits(addr) & mask).cmp(&(addr_to_bits(other) & mask))
+        })
+        .then_with(|| masklen.cmp(&other_masklen))
+        .then_with(|| addr_to_bits(addr).cmp(&addr_to_bits(other)))
+}
+
+/// Parse `addr` or `addr/masklen`, defaulting the mask length to the full width of the address
+fn parse_network(s: &str) -> Result<(IpAddr, u8), NetworkAddressError> {
+    let error = || NetworkAddressError::InvalidAddress(s.to_string());
+    let trimmed = s.trim();
+
+    let (addr, masklen) = match trimmed.find('/') {
+        Some(idx) => (
+            &trimmed[..idx],
+            Some(u8::from_str(&trimmed[idx + 1..]).map_err(|_| error())?),
+        ),
+        None => (trimmed, None),
+    };
+
+    let addr = IpAddr::from_str(addr).map_err(|_| error())?;
+    let masklen = masklen.unwrap_or_else(|| max_masklen(&addr));
+    if masklen > max_masklen(&addr) {
+        return Err(NetworkAddressError::InvalidMaskLength(masklen));
+    }
+
+    Ok((addr, masklen))
+}
+
+/// Decode the `inet_struct` shared by `inet` and `cidr` Datums
+unsafe fn network_from_datum(datum: pg_sys::Datum) -> (IpAddr, u8) {
+    let inet = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena) as *const pg_sys::inet;
+    let inet_data = &(*inet).inet_data;
+
+    let addr = if inet_data.family as u32 == pg_sys::PGSQL_AF_INET {
+        let mut octets = [0u8; 4];
+        octets.copy_from_slice(&inet_data.ipaddr[..4]);
+        IpAddr::V4(Ipv4Addr::from(octets))
+    } else if inet_data.family as u32 == pg_sys::PGSQL_AF_INET6 {
+        IpAddr::V6(Ipv6Addr::from(inet_data.ipaddr))
+    } else {
+        panic!("invalid network address family: {}", inet_data.family);
+    };
+
+    (addr, inet_data.bits)
+}
+
+/// Encode an `inet_struct`, only including as many address bytes as the family needs, just like
+/// Postgres' `inet_in()`
+fn network_into_datum(addr: &IpAddr, masklen: u8) -> pg_sys::Datum {
+    let (family, octets) = match addr {
+        IpAddr::V4(addr) => (pg_sys::PGSQL_AF_INET, addr.octets().to_vec()),
+        IpAddr::V6(addr) => (pg_sys::PGSQL_AF_INET6, addr.octets().to_vec()),
+    };
+
+    let size = pg_sys::VARHDRSZ + 2 + octets.len();
+    let inet = PgMemoryContexts::CurrentMemoryContext.palloc0(size) as *mut pg_sys::inet;
+    unsafe {
+        crate::set_varsize(inet as *mut pg_sys::varlena, size as i32);
+        (*inet).inet_data.family = family as u8;
+        (*inet).inet_data.bits = masklen;
+        std::ptr::copy_nonoverlapping(
+            octets.as_ptr(),
+            (*inet).inet_data.ipaddr.as_mut_ptr(),
+            octets.len(),
+        );
+    }
+
+    inet as pg_sys::Datum
+}
+
+impl Inet {
+    /// Create an `Inet` for the host `addr` on a subnet with a `masklen` bit prefix
+    pub fn new(addr: IpAddr, masklen: u8) -> Result<Inet, NetworkAddressError> {
+        if masklen > max_masklen(&addr) {
+            Err(NetworkAddressError::InvalidMaskLength(masklen))
+        } else {
+            Ok(Inet { addr, masklen })
+        }
+    }
+
+    /// The host address
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// The number of bits in the subnet's prefix, like Postgres' `masklen()`
+    pub fn masklen(&self) -> u8 {
+        self.masklen
+    }
+
+    pub fn is_ipv4(&self) -> bool {
+        self.addr.is_ipv4()
+    }
+
+    pub fn is_ipv6(&self) -> bool {
+        self.addr.is_ipv6()
+    }
+
+    /// The subnet's netmask, like Postgres' `netmask()`
+    pub fn netmask(&self) -> IpAddr {
+        bits_to_addr(&self.addr, netmask_bits(&self.addr, self.masklen))
+    }
+
+    /// The subnet's host mask, like Postgres' `hostmask()`
+    pub fn hostmask(&self) -> IpAddr {
+        let all = netmask_bits(&self.addr, max_masklen(&self.addr));
+        bits_to_addr(&self.addr, all & !netmask_bits(&self.addr, self.masklen))
+    }
+
+    /// The subnet this address is on, like Postgres' `network()`
+    pub fn network(&self) -> Cidr {
+        Cidr {
+            addr: bits_to_addr(
+                &self.addr,
+                addr_to_bits(&self.addr) & netmask_bits(&self.addr, self.masklen),
+            ),
+            masklen: self.masklen,
+        }
+    }
+
+    /// The broadcast address of the subnet this address is on, like Postgres' `broadcast()`
+    pub fn broadcast(&self) -> IpAddr {
+        bits_to_addr(
+            &self.addr,
+            addr_to_bits(&self.addr) | addr_to_bits(&self.hostmask()),
+        )
+    }
+
+    /// Is `other` within, or the same as, this subnet, like Postgres' `>>=`
+    pub fn contains(&self, other: &Inet) -> bool {
+        network_contains(&self.addr, self.masklen, &other.addr, other.masklen)
+    }
+
+    /// Is the host `addr` within this subnet
+    pub fn contains_addr(&self, addr: &IpAddr) -> bool {
+        network_contains(&self.addr, self.masklen, addr, max_masklen(addr))
+    }
+}
+
+impl Cidr {
+    /// Create a `Cidr` for the network `addr` with a `masklen` bit prefix, erroring if `addr` has
+    /// bits set to the right of the mask
+    pub fn new(addr: IpAddr, masklen: u8) -> Result<Cidr, NetworkAddressError> {
+        if masklen > max_masklen(&addr) {
+            Err(NetworkAddressError::InvalidMaskLength(masklen))
+        } else if addr_to_bits(&addr) & !netmask_bits(&addr, masklen) != 0 {
+            Err(NetworkAddressError::HostBitsSet)
+        } else {
+            Ok(Cidr { addr, masklen })
+        }
+    }
+
+    /// The network address
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// The number of bits in the network's prefix, like Postgres' `masklen()`
+    pub fn masklen(&self) -> u8 {
+        self.masklen
+    }
+
+    pub fn is_ipv4(&self) -> bool {
+        self.addr.is_ipv4()
+    }
+
+    pub fn is_ipv6(&self) -> bool {
+        self.addr.is_ipv6()
+    }
+
+    /// The network's netmask, like Postgres' `netmask()`
+    pub fn netmask(&self) -> IpAddr {
+        Inet::from(*self).netmask()
+    }
+
+    /// The network's host mask, like Postgres' `hostmask()`
+    pub fn hostmask(&self) -> IpAddr {
+        Inet::from(*self).hostmask()
+    }
+
+    /// The network's broadcast address, like Postgres' `broadcast()`
+    pub fn broadcast(&self) -> IpAddr {
+        Inet::from(*self).broadcast()
+    }
+
+    /// Is `other` within, or the same as, this network, like Postgres' `>>=`
+    pub fn contains(&self, other: &Inet) -> bool {
+        network_contains(&self.addr, self.masklen, &other.addr, other.masklen)
+    }
+
+    /// Is the host `addr` within this network
+    pub fn contains_addr(&self, addr: &IpAddr) -> bool {
+        network_contains(&self.addr, self.masklen, addr, max_masklen(addr))
+    }
+}
+
+impl From<IpAddr> for Inet {
+    /// A host address, with a mask length covering the entire address
+    fn from(addr: IpAddr) -> Self {
+        Inet {
+            addr,
+            masklen: max_masklen(&addr),
+        }
+    }
+}
+
+impl From<Cidr> for Inet {
+    fn from(cidr: Cidr) -> Self {
+        Inet {
+            addr: cidr.addr,
+            masklen: cidr.masklen,
+        }
+    }
+}
+
+impl TryFrom<Inet> for Cidr {
+    type Error = NetworkAddressError;
+
+    /// Fails if the `Inet` has bits set to the right of its mask.  Use [`Inet::network()`] to
+    /// discard them instead.
+    fn try_from(inet: Inet) -> Result<Self, Self::Error> {
+        Cidr::new(inet.addr, inet.masklen)
+    }
+}
+
+impl PartialOrd for Inet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Inet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        network_cmp(&self.addr, self.masklen, &other.addr, other.masklen)
+    }
+}
+
+impl PartialOrd for Cidr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cidr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        network_cmp(&self.addr, self.masklen, &other.addr, other.masklen)
+    }
+}
+
+impl fmt::Display for Inet {
+    /// Formats like Postgres' `inet_out()`, which omits the mask length for a single host
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.masklen == max_masklen(&self.addr) {
+            write!(f, "{}", self.addr)
+        } else {
+            write!(f, "{}/{}", self.addr, self.masklen)
+        }
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.masklen)
+    }
+}
+
+impl FromStr for Inet {
+    type Err = NetworkAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, masklen) = parse_network(s)?;
+        Inet::new(addr, masklen)
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = NetworkAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, masklen) = parse_network(s)?;
+        Cidr::new(addr, masklen)
     }
 }
 
@@ -27,7 +395,7 @@ impl Serialize for Inet {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.0)
+        serializer.serialize_str(&self.to_string())
     }
 }
 
@@ -48,32 +416,45 @@ impl<'de> Deserialize<'de> for Inet {
             where
                 E: Error,
             {
-                self.visit_string(v.to_owned())
+                Inet::from_str(v).map_err(|_| Error::custom(format!("invalid inet value: {}", v)))
             }
+        }
+
+        deserializer.deserialize_str(InetVisitor)
+    }
+}
 
-            fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+impl Serialize for Cidr {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Cidr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CidrVisitor;
+        impl<'de> Visitor<'de> for CidrVisitor {
+            type Value = Cidr;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a quoted JSON string in proper cidr form")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
             where
                 E: Error,
             {
-                // try to convert the provided String value into a Postgres Numeric Datum
-                // if it doesn't raise an ERROR, then we're good
-                unsafe {
-                    pg_try(|| {
-                        // this might throw, but that's okay
-                        let datum = Inet(v.clone()).into_datum().unwrap();
-
-                        // and don't leak the 'inet' datum Postgres created
-                        pg_sys::pfree(datum as void_mut_ptr);
-
-                        // we have it as a valid String
-                        Ok(Inet(v.clone()))
-                    })
-                    .unwrap_or_else(|| Err(Error::custom(format!("invalid inet value: {}", v))))
-                }
+                Cidr::from_str(v).map_err(|_| Error::custom(format!("invalid cidr value: {}", v)))
             }
         }
 
-        deserializer.deserialize_str(InetVisitor)
+        deserializer.deserialize_str(CidrVisitor)
     }
 }
 
@@ -84,23 +465,15 @@ impl FromDatum for Inet {
         } else if datum == 0 {
             panic!("inet datum is declared non-null but Datum is zero");
         } else {
-            let cstr = direct_function_call::<&CStr>(pg_sys::inet_out, vec![Some(datum)]);
-            Some(Inet(
-                cstr.unwrap()
-                    .to_str()
-                    .expect("unable to convert &cstr inet into &str")
-                    .to_owned(),
-            ))
+            let (addr, masklen) = network_from_datum(datum);
+            Some(Inet { addr, masklen })
         }
     }
 }
 
 impl IntoDatum for Inet {
     fn into_datum(self) -> Option<pg_sys::Datum> {
-        let cstr = std::ffi::CString::new(self.0).expect("failed to convert inet into CString");
-        unsafe {
-            direct_function_call_as_datum(pg_sys::inet_in, vec![cstr.as_c_str().into_datum()])
-        }
+        Some(network_into_datum(&self.addr, self.masklen))
     }
 
     fn type_oid() -> u32 {
@@ -108,8 +481,26 @@ impl IntoDatum for Inet {
     }
 }
 
-impl Into<Inet> for String {
-    fn into(self) -> Inet {
-        Inet(self)
+impl FromDatum for Cidr {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Cidr> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("cidr datum is declared non-null but Datum is zero");
+        } else {
+            // Postgres guarantees a `cidr` has no bits set to the right of its mask
+            let (addr, masklen) = network_from_datum(datum);
+            Some(Cidr { addr, masklen })
+        }
+    }
+}
+
+impl IntoDatum for Cidr {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(network_into_datum(&self.addr, self.masklen))
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::CIDROID
     }
 }
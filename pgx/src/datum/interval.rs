@@ -0,0 +1,473 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::datum::time::{USECS_PER_HOUR, USECS_PER_MINUTE, USECS_PER_SEC};
+use crate::{
+    direct_function_call, pg_sys, pg_try, void_mut_ptr, Date, FromDatum, IntoDatum, PgBox,
+    Timestamp, TimestampWithTimeZone,
+};
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Neg, Sub};
+
+const USECS_PER_DAY: i128 = pg_sys::SECS_PER_DAY as i128 * USECS_PER_SEC as i128;
+const DAYS_PER_MONTH: i128 = pg_sys::DAYS_PER_MONTH as i128;
+const MONTHS_PER_YEAR: i32 = 12;
+
+/// A Postgres `interval`.
+///
+/// Like Postgres, the month, day, and microsecond components are kept separate as neither a month
+/// nor a day has a fixed length in microseconds.
+///
+/// Comparisons follow Postgres' rules, which assume a month is 30 days and a day is 24 hours.  As
+/// such, `'1 mon'` and `'30 days'` compare as equal, even though their components differ.
+#[derive(Debug, Clone, Copy)]
+pub struct Interval {
+    months: i32,
+    days: i32,
+    micros: i64,
+}
+
+/// The reasons converting to or from an [`Interval`] can fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalConversionError {
+    /// The `time::Duration` has sub-microsecond precision, which an `interval` can't represent
+    SubMicrosecondPrecision,
+    /// The value is too large to be represented by the target type
+    OutOfRange,
+    /// The `interval` has a non-zero month or day component, neither of which have a fixed length
+    HasMonthsOrDays,
+}
+
+impl fmt::Display for IntervalConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntervalConversionError::SubMicrosecondPrecision => {
+                write!(f, "duration has sub-microsecond precision")
+            }
+            IntervalConversionError::OutOfRange => write!(f, "interval out of range"),
+            IntervalConversionError::HasMonthsOrDays => {
+                write!(f, "interval has a month or day component")
+            }
+        }
+    }
+}
+
+impl std::error::Error for IntervalConversionError {}
+
+impl Interval {
+    pub fn new(months: i32, days: i32, micros: i64) -> Self {
+        Interval {
+            months,
+            days,
+            micros,
+        }
+    }
+
+    pub fn from_months(months: i32) -> Self {
+        Interval::new(months, 0, 0)
+    }
+
+    pub fn from_days(days: i32) -> Self {
+        Interval::new(0, days, 0)
+    }
+
+    pub fn from_micros(micros: i64) -> Self {
+        Interval::new(0, 0, micros)
+    }
+
+    pub fn months(&self) -> i32 {
+        self.months
+    }
+
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    pub fn micros(&self) -> i64 {
+        self.micros
+    }
+
+    /// The time component of this interval, excluding its months and days
+    pub fn time(&self) -> time::Duration {
+        time::Duration::microseconds(self.micros)
+    }
+
+    /// The length of this interval in microseconds, using the same 30 day month and 24 hour day
+    /// Postgres uses when comparing intervals
+    fn span(&self) -> i128 {
+        (self.months as i128 * DAYS_PER_MONTH + self.days as i128) * USECS_PER_DAY
+            + self.micros as i128
+    }
+
+    fn checked_add(self, rhs: Interval) -> Option<Interval> {
+        Some(Interval {
+            months: self.months.checked_add(rhs.months)?,
+            days: self.days.checked_add(rhs.days)?,
+            micros: self.micros.checked_add(rhs.micros)?,
+        })
+    }
+
+    fn checked_neg(self) -> Option<Interval> {
+        Some(Interval {
+            months: self.months.checked_neg()?,
+            days: self.days.checked_neg()?,
+            micros: self.micros.checked_neg()?,
+        })
+    }
+
+    /// Render this interval in the ISO 8601 "format with designators", the same as Postgres'
+    /// `intervalstyle = iso_8601`
+    pub fn to_iso_8601(&self) -> String {
+        let mut s = String::from("P");
+        let years = self.months / MONTHS_PER_YEAR;
+        let months = self.months % MONTHS_PER_YEAR;
+
+        if years != 0 {
+            write!(s, "{}Y", years).unwrap();
+        }
+        if months != 0 {
+            write!(s, "{}M", months).unwrap();
+        }
+        if self.days != 0 {
+            write!(s, "{}D", self.days).unwrap();
+        }
+
+        if self.micros != 0 {
+            let mut time = self.micros;
+            let hours = time / USECS_PER_HOUR;
+            time -= hours * USECS_PER_HOUR;
+            let minutes = time / USECS_PER_MINUTE;
+            time -= minutes * USECS_PER_MINUTE;
+            let seconds = time / USECS_PER_SEC;
+            let fraction = time - seconds * USECS_PER_SEC;
+
+            s.push('T');
+            if hours != 0 {
+                write!(s, "{}H", hours).unwrap();
+            }
+            if minutes != 0 {
+                write!(s, "{}M", minutes).unwrap();
+            }
+            if seconds != 0 || fraction != 0 {
+                if seconds == 0 && fraction < 0 {
+                    s.push('-');
+                }
+                write!(s, "{}", seconds).unwrap();
+                if fraction != 0 {
+                    let digits = format!("{:06}", fraction.abs());
+                    write!(s, ".{}", digits.trim_end_matches('0')).unwrap();
+                }
+                s.push('S');
+            }
+        }
+
+        if s.len() == 1 {
+            s.push_str("T0S");
+        }
+        s
+    }
+}
+
+impl FromDatum for Interval {
+    const NEEDS_TYPID: bool = false;
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Interval> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("interval datum is declared non-null but Datum is zero");
+        } else {
+            let interval = PgBox::from_pg(datum as *mut pg_sys::Interval);
+            Some(Interval {
+                months: interval.month,
+                days: interval.day,
+                micros: interval.time,
+            })
+        }
+    }
+}
+
+impl IntoDatum for Interval {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let mut interval = PgBox::<pg_sys::Interval>::alloc();
+        interval.month = self.months;
+        interval.day = self.days;
+        interval.time = self.micros;
+
+        Some(interval.into_pg() as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::INTERVALOID
+    }
+}
+
+impl TryFrom<time::Duration> for Interval {
+    type Error = IntervalConversionError;
+
+    fn try_from(duration: time::Duration) -> Result<Self, Self::Error> {
+        if duration.subsec_nanoseconds() % 1_000 != 0 {
+            return Err(IntervalConversionError::SubMicrosecondPrecision);
+        }
+
+        let micros = i64::try_from(duration.whole_microseconds())
+            .map_err(|_| IntervalConversionError::OutOfRange)?;
+        Ok(Interval::from_micros(micros))
+    }
+}
+
+impl TryFrom<Interval> for time::Duration {
+    type Error = IntervalConversionError;
+
+    fn try_from(interval: Interval) -> Result<Self, Self::Error> {
+        if interval.months != 0 || interval.days != 0 {
+            Err(IntervalConversionError::HasMonthsOrDays)
+        } else {
+            Ok(interval.time())
+        }
+    }
+}
+
+impl PartialEq for Interval {
+    fn eq(&self, other: &Self) -> bool {
+        self.span() == other.span()
+    }
+}
+
+impl Eq for Interval {}
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Interval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.span().cmp(&other.span())
+    }
+}
+
+impl Hash for Interval {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // must agree with our `PartialEq` implementation
+        self.span().hash(state)
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, rhs: Interval) -> Self::Output {
+        self.checked_add(rhs).expect("interval out of range")
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, rhs: Interval) -> Self::Output {
+        self.checked_add(-rhs).expect("interval out of range")
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+
+    fn neg(self) -> Self::Output {
+        self.checked_neg().expect("interval out of range")
+    }
+}
+
+impl Add<Interval> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, rhs: Interval) -> Self::Output {
+        unsafe {
+            direct_function_call(
+                pg_sys::timestamp_pl_interval,
+                vec![self.into_datum(), rhs.into_datum()],
+            )
+            .expect("timestamp_pl_interval returned NULL")
+        }
+    }
+}
+
+impl Sub<Interval> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, rhs: Interval) -> Self::Output {
+        unsafe {
+            direct_function_call(
+                pg_sys::timestamp_mi_interval,
+                vec![self.into_datum(), rhs.into_datum()],
+            )
+            .expect("timestamp_mi_interval returned NULL")
+        }
+    }
+}
+
+impl Sub for Timestamp {
+    type Output = Interval;
+
+    fn sub(self, rhs: Timestamp) -> Self::Output {
+        unsafe {
+            direct_function_call(
+                pg_sys::timestamp_mi,
+                vec![self.into_datum(), rhs.into_datum()],
+            )
+            .expect("timestamp_mi returned NULL")
+        }
+    }
+}
+
+impl Add<Interval> for TimestampWithTimeZone {
+    type Output = TimestampWithTimeZone;
+
+    fn add(self, rhs: Interval) -> Self::Output {
+        unsafe {
+            direct_function_call(
+                pg_sys::timestamptz_pl_interval,
+                vec![self.into_datum(), rhs.into_datum()],
+            )
+            .expect("timestamptz_pl_interval returned NULL")
+        }
+    }
+}
+
+impl Sub<Interval> for TimestampWithTimeZone {
+    type Output = TimestampWithTimeZone;
+
+    fn sub(self, rhs: Interval) -> Self::Output {
+        unsafe {
+            direct_function_call(
+                pg_sys::timestamptz_mi_interval,
+                vec![self.into_datum(), rhs.into_datum()],
+            )
+            .expect("timestamptz_mi_interval returned NULL")
+        }
+    }
+}
+
+impl Sub for TimestampWithTimeZone {
+    type Output = Interval;
+
+    fn sub(self, rhs: TimestampWithTimeZone) -> Self::Output {
+        // Postgres uses the same function for `timestamp - timestamp` and `timestamptz - timestamptz`
+        unsafe {
+            direct_function_call(
+                pg_sys::timestamp_mi,
+                vec![self.into_datum(), rhs.into_datum()],
+            )
+            .expect("timestamp_mi returned NULL")
+        }
+    }
+}
+
+/// Like in Postgres, adding an `interval` to a `date` produces a `timestamp`
+impl Add<Interval> for Date {
+    type Output = Timestamp;
+
+    fn add(self, rhs: Interval) -> Self::Output {
+        unsafe {
+            direct_function_call(
+                pg_sys::date_pl_interval,
+                vec![self.into_datum(), rhs.into_datum()],
+            )
+            .expect("date_pl_interval returned NULL")
+        }
+    }
+}
+
+/// Like in Postgres, subtracting an `interval` from a `date` produces a `timestamp`
+impl Sub<Interval> for Date {
+    type Output = Timestamp;
+
+    fn sub(self, rhs: Interval) -> Self::Output {
+        unsafe {
+            direct_function_call(
+                pg_sys::date_mi_interval,
+                vec![self.into_datum(), rhs.into_datum()],
+            )
+            .expect("date_mi_interval returned NULL")
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_iso_8601())
+    }
+}
+
+impl Serialize for Interval {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_iso_8601())
+    }
+}
+
+impl<'de> Deserialize<'de> for Interval {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct IntervalVisitor;
+        impl<'de> Visitor<'de> for IntervalVisitor {
+            type Value = Interval;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a quoted JSON string in proper interval form")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                self.visit_string(v.to_owned())
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                // let Postgres parse the value so we accept everything `interval_in` accepts
+                // if it doesn't raise an ERROR, then we're good
+                unsafe {
+                    pg_try(|| {
+                        let cstr = std::ffi::CString::new(v.clone())
+                            .expect("failed to convert interval into CString");
+
+                        // this might throw, but that's okay
+                        let datum = crate::direct_function_call_as_datum(
+                            pg_sys::interval_in,
+                            vec![
+                                cstr.as_c_str().into_datum(),
+                                pg_sys::InvalidOid.into_datum(),
+                                (-1i32).into_datum(),
+                            ],
+                        )
+                        .unwrap();
+                        let interval = Interval::from_datum(datum, false, pg_sys::INTERVALOID);
+
+                        // and don't leak the 'interval' datum Postgres created
+                        pg_sys::pfree(datum as void_mut_ptr);
+
+                        Ok(interval.unwrap())
+                    })
+                    .unwrap_or_else(|| Err(Error::custom(format!("invalid interval value: {}", v))))
+                }
+            }
+        }
+
+        deserializer.deserialize_str(IntervalVisitor)
+    }
+}
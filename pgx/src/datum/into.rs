@@ -28,6 +28,25 @@ pub trait IntoDatum {
     }
 }
 
+/// An object-safe version of [`IntoDatum`], so values of different types can be collected
+/// together, such as in a `&[&dyn IntoDatumDyn]` of SPI arguments.
+///
+/// It's implemented for every `IntoDatum` type that is also `Clone`.
+pub trait IntoDatumDyn {
+    fn to_datum_dyn(&self) -> Option<pg_sys::Datum>;
+    fn type_oid_dyn(&self) -> pg_sys::Oid;
+}
+
+impl<T: IntoDatum + Clone> IntoDatumDyn for T {
+    fn to_datum_dyn(&self) -> Option<pg_sys::Datum> {
+        self.clone().into_datum()
+    }
+
+    fn type_oid_dyn(&self) -> pg_sys::Oid {
+        T::type_oid()
+    }
+}
+
 /// for supporting NULL as the None value of an Option<T>
 impl<T> IntoDatum for Option<T>
 where
@@ -0,0 +1,255 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{pg_sys, FromDatum, IntoDatum, PgMemoryContexts};
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// The error returned when parsing a [`MacAddr`] or [`MacAddr8`] fails, or when converting a
+/// [`MacAddr8`] that isn't an EUI-48 derived address into a [`MacAddr`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacAddrError(String);
+
+impl fmt::Display for MacAddrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid MAC address: {}", self.0)
+    }
+}
+
+impl std::error::Error for MacAddrError {}
+
+/// A Postgres `macaddr`, a 6 byte EUI-48 MAC address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddr(pub [u8; 6]);
+
+/// A Postgres `macaddr8`, an 8 byte EUI-64 MAC address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddr8(pub [u8; 8]);
+
+/// Parse `N` hex octets separated by `:` or `-`, or with no separators at all
+fn parse_octets<const N: usize>(s: &str) -> Result<[u8; N], MacAddrError> {
+    let error = || MacAddrError(s.to_string());
+    let trimmed = s.trim();
+
+    let hex = if trimmed.contains(|c| c == ':' || c == '-') {
+        let parts = trimmed.split(|c| c == ':' || c == '-').collect::<Vec<_>>();
+        if parts.len() != N || parts.iter().any(|part| part.len() != 2) {
+            return Err(error());
+        }
+        parts.concat()
+    } else {
+        trimmed.to_string()
+    };
+
+    if hex.len() != N * 2 || !hex.is_ascii() {
+        return Err(error());
+    }
+
+    let mut octets = [0u8; N];
+    for (i, octet) in octets.iter_mut().enumerate() {
+        *octet = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| error())?;
+    }
+    Ok(octets)
+}
+
+fn fmt_octets(octets: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (i, octet) in octets.iter().enumerate() {
+        if i > 0 {
+            f.write_str(":")?;
+        }
+        write!(f, "{:02x}", octet)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_octets(&self.0, f)
+    }
+}
+
+impl fmt::Display for MacAddr8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_octets(&self.0, f)
+    }
+}
+
+impl FromStr for MacAddr {
+    type Err = MacAddrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(MacAddr(parse_octets(s)?))
+    }
+}
+
+impl FromStr for MacAddr8 {
+    type Err = MacAddrError;
+
+    /// Also accepts a 6 byte address, which is converted the same way as `From<MacAddr>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_octets::<8>(s) {
+            Ok(octets) => Ok(MacAddr8(octets)),
+            Err(e) => MacAddr::from_str(s).map(MacAddr8::from).map_err(|_| e),
+        }
+    }
+}
+
+impl From<MacAddr> for MacAddr8 {
+    /// Inserts `ff:fe` in the middle of the address, just like Postgres' `macaddrtomacaddr8()`
+    fn from(mac: MacAddr) -> Self {
+        let [a, b, c, d, e, f] = mac.0;
+        MacAddr8([a, b, c, 0xff, 0xfe, d, e, f])
+    }
+}
+
+impl TryFrom<MacAddr8> for MacAddr {
+    type Error = MacAddrError;
+
+    /// Fails unless the fourth and fifth bytes are `ff:fe`, just like Postgres' `macaddr8tomacaddr()`
+    fn try_from(mac: MacAddr8) -> Result<Self, Self::Error> {
+        match mac.0 {
+            [a, b, c, 0xff, 0xfe, d, e, f] => Ok(MacAddr([a, b, c, d, e, f])),
+            _ => Err(MacAddrError(mac.to_string())),
+        }
+    }
+}
+
+impl Serialize for MacAddr {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for MacAddr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct MacAddrVisitor;
+        impl<'de> Visitor<'de> for MacAddrVisitor {
+            type Value = MacAddr;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a quoted JSON string in proper macaddr form")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                MacAddr::from_str(v)
+                    .map_err(|_| Error::custom(format!("invalid macaddr value: {}", v)))
+            }
+        }
+
+        deserializer.deserialize_str(MacAddrVisitor)
+    }
+}
+
+impl Serialize for MacAddr8 {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for MacAddr8 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct MacAddr8Visitor;
+        impl<'de> Visitor<'de> for MacAddr8Visitor {
+            type Value = MacAddr8;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a quoted JSON string in proper macaddr8 form")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                MacAddr8::from_str(v)
+                    .map_err(|_| Error::custom(format!("invalid macaddr8 value: {}", v)))
+            }
+        }
+
+        deserializer.deserialize_str(MacAddr8Visitor)
+    }
+}
+
+impl FromDatum for MacAddr {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<MacAddr> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("macaddr datum is declared non-null but Datum is zero");
+        } else {
+            let mac = *(datum as *const pg_sys::macaddr);
+            Some(MacAddr([mac.a, mac.b, mac.c, mac.d, mac.e, mac.f]))
+        }
+    }
+}
+
+impl IntoDatum for MacAddr {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let [a, b, c, d, e, f] = self.0;
+        let mac = PgMemoryContexts::CurrentMemoryContext.palloc_struct::<pg_sys::macaddr>();
+        unsafe {
+            *mac = pg_sys::macaddr { a, b, c, d, e, f };
+        }
+        Some(mac as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::MACADDROID
+    }
+}
+
+impl FromDatum for MacAddr8 {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<MacAddr8> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("macaddr8 datum is declared non-null but Datum is zero");
+        } else {
+            let mac = *(datum as *const pg_sys::macaddr8);
+            Some(MacAddr8([
+                mac.a, mac.b, mac.c, mac.d, mac.e, mac.f, mac.g, mac.h,
+            ]))
+        }
+    }
+}
+
+impl IntoDatum for MacAddr8 {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        let [a, b, c, d, e, f, g, h] = self.0;
+        let mac = PgMemoryContexts::CurrentMemoryContext.palloc_struct::<pg_sys::macaddr8>();
+        unsafe {
+            *mac = pg_sys::macaddr8 {
+                a,
+                b,
+                c,
+                d,
+                e,
+                f,
+                g,
+                h,
+            };
+        }
+        Some(mac as pg_sys::Datum)
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::MACADDR8OID
+    }
+}
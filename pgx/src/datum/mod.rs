@@ -7,14 +7,18 @@ mod anyarray;
 mod anyelement;
 mod array;
 mod date;
+mod fixed_size;
 mod from;
 mod geo;
 mod inet;
 mod internal;
+mod interval;
 mod into;
 mod item_pointer_data;
 mod json;
+mod macaddr;
 mod numeric;
+mod range;
 mod time;
 mod time_stamp;
 mod time_stamp_with_timezone;
@@ -29,14 +33,18 @@ pub use anyarray::*;
 pub use anyelement::*;
 pub use array::*;
 pub use date::*;
+pub use fixed_size::*;
 pub use from::*;
 pub use geo::*;
 pub use inet::*;
 pub use internal::*;
+pub use interval::*;
 pub use into::*;
 pub use item_pointer_data::*;
 pub use json::*;
+pub use macaddr::*;
 pub use numeric::*;
+pub use range::*;
 use once_cell::sync::Lazy;
 use std::any::TypeId;
 pub use time_stamp::*;
@@ -1,17 +1,572 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
-use crate::{
-    direct_function_call, direct_function_call_as_datum, pg_sys, void_mut_ptr, FromDatum, IntoDatum,
-};
-use pgx_pg_sys::pg_try;
+use crate::{direct_function_call, pg_sys, FromDatum, IntoDatum, PgMemoryContexts};
 use serde::de::{Error, Visitor};
-use serde::{de, Deserialize, Deserializer, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Number;
-use std::fmt;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::str::FromStr;
 
-#[derive(Serialize)]
-pub struct Numeric(pub String);
+// These mirror the private #defines in Postgres' `utils/adt/numeric.c`, which describe the
+// on-disk format of a `numeric`
+const NBASE: i32 = 10000;
+const DEC_DIGITS: i32 = 4;
+
+const NUMERIC_SIGN_MASK: u16 = 0xC000;
+const NUMERIC_POS: u16 = 0x0000;
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_SHORT: u16 = 0x8000;
+const NUMERIC_SPECIAL: u16 = 0xC000;
+
+const NUMERIC_EXT_SIGN_MASK: u16 = 0xF000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+
+const NUMERIC_DSCALE_MASK: u16 = 0x3FFF;
+
+const NUMERIC_SHORT_SIGN_MASK: u16 = 0x2000;
+const NUMERIC_SHORT_DSCALE_MASK: u16 = 0x1F80;
+const NUMERIC_SHORT_DSCALE_SHIFT: u16 = 7;
+const NUMERIC_SHORT_DSCALE_MAX: u16 = NUMERIC_SHORT_DSCALE_MASK >> NUMERIC_SHORT_DSCALE_SHIFT;
+const NUMERIC_SHORT_WEIGHT_SIGN_MASK: u16 = 0x0040;
+const NUMERIC_SHORT_WEIGHT_MASK: u16 = 0x003F;
+const NUMERIC_SHORT_WEIGHT_MAX: i16 = NUMERIC_SHORT_WEIGHT_MASK as i16;
+const NUMERIC_SHORT_WEIGHT_MIN: i16 = -(NUMERIC_SHORT_WEIGHT_MASK as i16 + 1);
+
+const NUMERIC_MAX_PRECISION: i32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumericSign {
+    Positive,
+    Negative,
+    NaN,
+    PositiveInfinity,
+    NegativeInfinity,
+}
+
+/// A Postgres `numeric`, decoded from its on-disk representation.
+///
+/// Like Postgres, the value is stored as base-10000 digits along with the weight of the first
+/// digit and the display scale, so no precision is lost moving values in and out of Postgres.
+///
+/// Arithmetic and rounding are delegated to Postgres' own `numeric` routines, and so are only
+/// available inside a Postgres backend.  Parsing, formatting, comparisons, and conversion to and
+/// from Rust's primitive types are implemented natively.
+#[derive(Clone)]
+pub struct Numeric {
+    sign: NumericSign,
+    weight: i16,
+    dscale: u16,
+    digits: Vec<i16>,
+}
+
+/// The reasons converting to or from a [`Numeric`] can fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericConversionError {
+    /// The value is too large, or too small, to be represented by the target type
+    OutOfRange,
+    /// The `numeric` has a fractional part and the target type is an integer
+    NotAnInteger,
+    /// The `numeric` is `NaN` or infinite and the target type can't represent that
+    NotFinite,
+    /// The requested precision and scale aren't valid for a `numeric`
+    InvalidTypmod,
+}
+
+impl fmt::Display for NumericConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumericConversionError::OutOfRange => write!(f, "numeric value out of range"),
+            NumericConversionError::NotAnInteger => write!(f, "numeric value is not an integer"),
+            NumericConversionError::NotFinite => write!(f, "numeric value is not finite"),
+            NumericConversionError::InvalidTypmod => {
+                write!(f, "invalid numeric precision and scale")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NumericConversionError {}
+
+impl Numeric {
+    /// A `numeric` zero
+    pub fn zero() -> Self {
+        Numeric {
+            sign: NumericSign::Positive,
+            weight: 0,
+            dscale: 0,
+            digits: Vec::new(),
+        }
+    }
+
+    /// A `numeric` `NaN`
+    pub fn nan() -> Self {
+        Numeric::special(NumericSign::NaN)
+    }
+
+    /// A `numeric` `Infinity`, which requires Postgres 14 or later
+    pub fn infinity() -> Self {
+        Numeric::special(NumericSign::PositiveInfinity)
+    }
+
+    /// A `numeric` `-Infinity`, which requires Postgres 14 or later
+    pub fn negative_infinity() -> Self {
+        Numeric::special(NumericSign::NegativeInfinity)
+    }
+
+    fn special(sign: NumericSign) -> Self {
+        Numeric {
+            sign,
+            weight: 0,
+            dscale: 0,
+            digits: Vec::new(),
+        }
+    }
+
+    /// Build a normalized `Numeric`, the same way Postgres' `make_result()` does: leading and
+    /// trailing zero digits are stripped, and zero is always positive
+    fn from_parts(negative: bool, mut weight: i16, dscale: u16, mut digits: Vec<i16>) -> Self {
+        let leading_zeros = digits.iter().take_while(|d| **d == 0).count();
+        digits.drain(..leading_zeros);
+        weight -= leading_zeros as i16;
+
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+
+        if digits.is_empty() {
+            Numeric {
+                dscale,
+                ..Numeric::zero()
+            }
+        } else {
+            Numeric {
+                sign: if negative {
+                    NumericSign::Negative
+                } else {
+                    NumericSign::Positive
+                },
+                weight,
+                dscale,
+                digits,
+            }
+        }
+    }
+
+    pub fn is_nan(&self) -> bool {
+        self.sign == NumericSign::NaN
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        matches!(
+            self.sign,
+            NumericSign::PositiveInfinity | NumericSign::NegativeInfinity
+        )
+    }
+
+    pub fn is_negative(&self) -> bool {
+        matches!(
+            self.sign,
+            NumericSign::Negative | NumericSign::NegativeInfinity
+        )
+    }
+
+    /// The display scale, which is the number of digits after the decimal point
+    pub fn scale(&self) -> u16 {
+        self.dscale
+    }
+
+    /// The number of decimal digits before the decimal point, ignoring leading zeros
+    fn integral_digits(&self) -> i32 {
+        match self.digits.first() {
+            Some(first) if self.weight >= 0 => {
+                let mut leading_digits = 1;
+                let mut first = *first;
+                while first >= 10 {
+                    leading_digits += 1;
+                    first /= 10;
+                }
+                self.weight as i32 * DEC_DIGITS + leading_digits
+            }
+            _ => 0,
+        }
+    }
+
+    /// Round this value to `scale` digits after the decimal point, using Postgres' `round(numeric, int)`
+    pub fn round(&self, scale: i32) -> Numeric {
+        unsafe {
+            direct_function_call(
+                pg_sys::numeric_round,
+                vec![self.clone().into_datum(), scale.into_datum()],
+            )
+            .expect("numeric_round returned NULL")
+        }
+    }
+
+    /// Truncate this value to `scale` digits after the decimal point, using Postgres' `trunc(numeric, int)`
+    pub fn trunc(&self, scale: i32) -> Numeric {
+        unsafe {
+            direct_function_call(
+                pg_sys::numeric_trunc,
+                vec![self.clone().into_datum(), scale.into_datum()],
+            )
+            .expect("numeric_trunc returned NULL")
+        }
+    }
+
+    pub fn abs(&self) -> Numeric {
+        unsafe {
+            direct_function_call(pg_sys::numeric_abs, vec![self.clone().into_datum()])
+                .expect("numeric_abs returned NULL")
+        }
+    }
+
+    /// Coerce this value to a `numeric(precision, scale)`, as Postgres would when storing it in a
+    /// column of that type.
+    ///
+    /// The value is rounded to `scale` digits after the decimal point, and an error is returned if
+    /// it then has more than `precision - scale` digits before the decimal point.
+    pub fn rescale(&self, precision: i32, scale: i32) -> Result<Numeric, NumericConversionError> {
+        if precision < 1 || precision > NUMERIC_MAX_PRECISION || scale < 0 || scale > precision {
+            return Err(NumericConversionError::InvalidTypmod);
+        }
+
+        match self.sign {
+            NumericSign::NaN => Ok(self.clone()),
+            NumericSign::PositiveInfinity | NumericSign::NegativeInfinity => {
+                Err(NumericConversionError::OutOfRange)
+            }
+            NumericSign::Positive | NumericSign::Negative => {
+                let rounded = self.round(scale);
+                if rounded.integral_digits() > precision - scale {
+                    Err(NumericConversionError::OutOfRange)
+                } else {
+                    Ok(rounded)
+                }
+            }
+        }
+    }
+
+    /// Decode a detoasted `NumericData` varlena
+    unsafe fn from_varlena(varlena: *const pg_sys::varlena) -> Numeric {
+        let size = crate::varsize(varlena);
+        let data = varlena as *const u8;
+        let header = (data.add(pg_sys::VARHDRSZ) as *const u16).read_unaligned();
+
+        if header & NUMERIC_SIGN_MASK == NUMERIC_SPECIAL {
+            return match header & NUMERIC_EXT_SIGN_MASK {
+                NUMERIC_PINF => Numeric::infinity(),
+                NUMERIC_NINF => Numeric::negative_infinity(),
+                _ => Numeric::nan(),
+            };
+        }
+
+        let (negative, weight, dscale, header_size) = if header & NUMERIC_SIGN_MASK == NUMERIC_SHORT
+        {
+            let negative = header & NUMERIC_SHORT_SIGN_MASK != 0;
+            let dscale = (header & NUMERIC_SHORT_DSCALE_MASK) >> NUMERIC_SHORT_DSCALE_SHIFT;
+            let weight = if header & NUMERIC_SHORT_WEIGHT_SIGN_MASK != 0 {
+                (!NUMERIC_SHORT_WEIGHT_MASK | (header & NUMERIC_SHORT_WEIGHT_MASK)) as i16
+            } else {
+                (header & NUMERIC_SHORT_WEIGHT_MASK) as i16
+            };
+            (negative, weight, dscale, pg_sys::VARHDRSZ + 2)
+        } else {
+            let negative = header & NUMERIC_SIGN_MASK == NUMERIC_NEG;
+            let dscale = header & NUMERIC_DSCALE_MASK;
+            let weight = (data.add(pg_sys::VARHDRSZ + 2) as *const i16).read_unaligned();
+            (negative, weight, dscale, pg_sys::VARHDRSZ + 4)
+        };
+
+        let ndigits = (size - header_size) / 2;
+        let digits_ptr = data.add(header_size) as *const i16;
+        let digits = (0..ndigits)
+            .map(|i| digits_ptr.add(i).read_unaligned())
+            .collect::<Vec<_>>();
+
+        Numeric::from_parts(negative, weight, dscale, digits)
+    }
+
+    /// Encode this value as a `NumericData` varlena, the same way Postgres' `make_result()` does
+    fn to_varlena_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; pg_sys::VARHDRSZ];
+
+        let special = match self.sign {
+            NumericSign::NaN => Some(NUMERIC_NAN),
+            NumericSign::PositiveInfinity | NumericSign::NegativeInfinity => {
+                if cfg!(any(
+                    feature = "pg10",
+                    feature = "pg11",
+                    feature = "pg12",
+                    feature = "pg13"
+                )) {
+                    panic!("numeric infinity requires Postgres 14 or later");
+                }
+                if self.sign == NumericSign::PositiveInfinity {
+                    Some(NUMERIC_PINF)
+                } else {
+                    Some(NUMERIC_NINF)
+                }
+            }
+            NumericSign::Positive | NumericSign::Negative => None,
+        };
+
+        if let Some(header) = special {
+            bytes.extend_from_slice(&header.to_ne_bytes());
+        } else if self.dscale <= NUMERIC_SHORT_DSCALE_MAX
+            && self.weight <= NUMERIC_SHORT_WEIGHT_MAX
+            && self.weight >= NUMERIC_SHORT_WEIGHT_MIN
+        {
+            let mut header = NUMERIC_SHORT;
+            if self.sign == NumericSign::Negative {
+                header |= NUMERIC_SHORT_SIGN_MASK;
+            }
+            header |= self.dscale << NUMERIC_SHORT_DSCALE_SHIFT;
+            if self.weight < 0 {
+                header |= NUMERIC_SHORT_WEIGHT_SIGN_MASK;
+            }
+            header |= self.weight as u16 & NUMERIC_SHORT_WEIGHT_MASK;
+            bytes.extend_from_slice(&header.to_ne_bytes());
+        } else {
+            let sign = if self.sign == NumericSign::Negative {
+                NUMERIC_NEG
+            } else {
+                NUMERIC_POS
+            };
+            bytes.extend_from_slice(&(sign | (self.dscale & NUMERIC_DSCALE_MASK)).to_ne_bytes());
+            bytes.extend_from_slice(&self.weight.to_ne_bytes());
+        }
+
+        if special.is_none() {
+            for digit in &self.digits {
+                bytes.extend_from_slice(&digit.to_ne_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    fn from_integer(negative: bool, mut abs: u128) -> Numeric {
+        let mut digits = Vec::new();
+        while abs > 0 {
+            digits.push((abs % NBASE as u128) as i16);
+            abs /= NBASE as u128;
+        }
+        digits.reverse();
+
+        let weight = digits.len() as i16 - 1;
+        Numeric::from_parts(negative, weight, 0, digits)
+    }
+
+    /// Convert to an `i128`, erroring if this value isn't an integer or doesn't fit
+    fn to_i128(&self) -> Result<i128, NumericConversionError> {
+        if self.is_nan() || self.is_infinite() {
+            return Err(NumericConversionError::NotFinite);
+        }
+
+        let mut value: i128 = 0;
+        for (i, digit) in self.digits.iter().enumerate() {
+            if i as i32 > self.weight as i32 {
+                // this digit is after the decimal point, and `from_parts()` strips trailing zeros
+                return Err(NumericConversionError::NotAnInteger);
+            }
+
+            // accumulate negative values as negative so that `i128::MIN` doesn't overflow
+            let digit = if self.is_negative() {
+                -(*digit as i128)
+            } else {
+                *digit as i128
+            };
+            value = value
+                .checked_mul(NBASE as i128)
+                .and_then(|value| value.checked_add(digit))
+                .ok_or(NumericConversionError::OutOfRange)?;
+        }
+
+        // account for the stripped trailing zero digits before the decimal point
+        for _ in self.digits.len() as i32..=self.weight as i32 {
+            value = value
+                .checked_mul(NBASE as i128)
+                .ok_or(NumericConversionError::OutOfRange)?;
+        }
+
+        Ok(value)
+    }
+}
+
+impl fmt::Display for Numeric {
+    /// Formats the same as Postgres' `numeric_out()`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.sign {
+            NumericSign::NaN => return f.write_str("NaN"),
+            NumericSign::PositiveInfinity => return f.write_str("Infinity"),
+            NumericSign::NegativeInfinity => return f.write_str("-Infinity"),
+            NumericSign::Positive | NumericSign::Negative => {}
+        }
+
+        let digit_at = |i: i32| {
+            if i >= 0 && (i as usize) < self.digits.len() {
+                self.digits[i as usize]
+            } else {
+                0
+            }
+        };
+
+        let mut s = String::new();
+        if self.sign == NumericSign::Negative {
+            s.push('-');
+        }
+
+        if self.weight < 0 {
+            s.push('0');
+        } else {
+            for i in 0..=self.weight as i32 {
+                if i == 0 {
+                    write!(s, "{}", digit_at(i))?;
+                } else {
+                    write!(s, "{:04}", digit_at(i))?;
+                }
+            }
+        }
+
+        if self.dscale > 0 {
+            let mut fraction = String::new();
+            let mut i = self.weight as i32 + 1;
+            while fraction.len() < self.dscale as usize {
+                write!(fraction, "{:04}", digit_at(i))?;
+                i += 1;
+            }
+            fraction.truncate(self.dscale as usize);
+            s.push('.');
+            s.push_str(&fraction);
+        }
+
+        f.write_str(&s)
+    }
+}
+
+impl fmt::Debug for Numeric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Numeric")
+            .field(&format_args!("{}", self))
+            .finish()
+    }
+}
+
+/// The error returned when parsing a [`Numeric`] from a string fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseNumericError(String);
+
+impl fmt::Display for ParseNumericError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid Numeric value: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseNumericError {}
+
+impl FromStr for Numeric {
+    type Err = ParseNumericError;
+
+    /// Parses the same syntax as Postgres' `numeric_in()`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || ParseNumericError(input.to_string());
+        let s = input.trim();
+
+        match s.to_ascii_lowercase().as_str() {
+            "nan" => return Ok(Numeric::nan()),
+            "infinity" | "+infinity" | "inf" | "+inf" => return Ok(Numeric::infinity()),
+            "-infinity" | "-inf" => return Ok(Numeric::negative_infinity()),
+            _ => {}
+        }
+
+        let (negative, s) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+
+        let (mantissa, exponent) = match s.find(|c| c == 'e' || c == 'E') {
+            Some(idx) => {
+                let exponent = i32::from_str(&s[idx + 1..]).map_err(|_| error())?;
+                (&s[..idx], exponent)
+            }
+            None => (s, 0),
+        };
+
+        let (integral, fraction) = match mantissa.find('.') {
+            Some(idx) => (&mantissa[..idx], &mantissa[idx + 1..]),
+            None => (mantissa, ""),
+        };
+
+        if integral.is_empty() && fraction.is_empty()
+            || !integral.bytes().all(|b| b.is_ascii_digit())
+            || !fraction.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(error());
+        }
+
+        let decimal_digits = integral
+            .bytes()
+            .chain(fraction.bytes())
+            .map(|b| (b - b'0') as i32)
+            .collect::<Vec<_>>();
+
+        // `dweight` is the power of ten of the first decimal digit
+        let dweight = (integral.len() as i64 - 1) + exponent as i64;
+        let dscale = (fraction.len() as i64 - exponent as i64).max(0);
+        if dscale > NUMERIC_DSCALE_MASK as i64
+            || dweight.abs() > i16::MAX as i64 * DEC_DIGITS as i64
+        {
+            return Err(error());
+        }
+        let dweight = dweight as i32;
+
+        // line the decimal digits up with base-NBASE digits, like Postgres' `set_var_from_str()`
+        let weight = if dweight >= 0 {
+            (dweight + 1 + DEC_DIGITS - 1) / DEC_DIGITS - 1
+        } else {
+            -((-dweight - 1) / DEC_DIGITS + 1)
+        };
+        let offset = ((weight + 1) * DEC_DIGITS - (dweight + 1)) as usize;
+
+        let mut padded = vec![0; offset];
+        padded.extend(decimal_digits);
+        while padded.len() % DEC_DIGITS as usize != 0 {
+            padded.push(0);
+        }
+
+        let digits = padded
+            .chunks(DEC_DIGITS as usize)
+            .map(|chunk| chunk.iter().fold(0, |acc, d| acc * 10 + d) as i16)
+            .collect::<Vec<_>>();
+
+        if weight > i16::MAX as i32 || weight < i16::MIN as i32 {
+            return Err(error());
+        }
+
+        Ok(Numeric::from_parts(
+            negative,
+            weight as i16,
+            dscale as u16,
+            digits,
+        ))
+    }
+}
+
+impl Serialize for Numeric {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
 
 impl<'de> Deserialize<'de> for Numeric {
     fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
@@ -55,29 +610,7 @@ impl<'de> Deserialize<'de> for Numeric {
             where
                 E: Error,
             {
-                self.visit_string(v.to_owned())
-            }
-
-            #[inline]
-            fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
-            where
-                E: Error,
-            {
-                // try to convert the provided String value into a Postgres Numeric Datum
-                // if it doesn't raise an ERROR, then we're good
-                unsafe {
-                    pg_try(|| {
-                        // this might throw, but that's okay
-                        let datum = Numeric(v.clone()).into_datum().unwrap();
-
-                        // and don't leak the NumericData datum Postgres created
-                        pg_sys::pfree(datum as void_mut_ptr);
-
-                        // we have it as a valid String
-                        Ok(Numeric(v.clone()))
-                    })
-                    .unwrap_or(Err(Error::custom(format!("invalid Numeric value: {}", v))))
-                }
+                Numeric::from_str(v).map_err(Error::custom)
             }
         }
 
@@ -85,63 +618,206 @@ impl<'de> Deserialize<'de> for Numeric {
     }
 }
 
-impl Into<Numeric> for i8 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
-    }
+macro_rules! numeric_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Numeric {
+                fn from(value: $t) -> Numeric {
+                    // work with the absolute value as a `u128` so that `MIN` values don't overflow
+                    Numeric::from_integer(value < 0 as $t, (value as i128).unsigned_abs())
+                }
+            }
+        )*
+    };
 }
 
-impl Into<Numeric> for i16 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+numeric_from_int!(i8, i16, i32, i64, i128, u8, u16, u32, u64);
+
+impl From<u128> for Numeric {
+    fn from(value: u128) -> Numeric {
+        Numeric::from_integer(false, value)
     }
 }
 
-impl Into<Numeric> for i32 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
-    }
+macro_rules! numeric_from_float {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Numeric {
+                /// Converts to the shortest decimal representation that round-trips back to the
+                /// same float
+                fn from(value: $t) -> Numeric {
+                    if value.is_nan() {
+                        Numeric::nan()
+                    } else if value.is_infinite() {
+                        if value.is_sign_positive() {
+                            Numeric::infinity()
+                        } else {
+                            Numeric::negative_infinity()
+                        }
+                    } else {
+                        // Rust's `Display` for a finite float never uses exponent notation
+                        Numeric::from_str(&value.to_string()).expect(concat!(
+                            "failed to convert ",
+                            stringify!($t),
+                            " into Numeric"
+                        ))
+                    }
+                }
+            }
+        )*
+    };
 }
 
-impl Into<Numeric> for i64 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
-    }
+numeric_from_float!(f32, f64);
+
+macro_rules! int_try_from_numeric {
+    ($($t:ty),*) => {
+        $(
+            impl TryFrom<Numeric> for $t {
+                type Error = NumericConversionError;
+
+                fn try_from(value: Numeric) -> Result<Self, Self::Error> {
+                    let value = value.to_i128()?;
+                    <$t>::try_from(value).map_err(|_| NumericConversionError::OutOfRange)
+                }
+            }
+        )*
+    };
 }
 
-impl Into<Numeric> for u8 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+int_try_from_numeric!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+impl TryFrom<Numeric> for i128 {
+    type Error = NumericConversionError;
+
+    fn try_from(value: Numeric) -> Result<Self, Self::Error> {
+        value.to_i128()
     }
 }
 
-impl Into<Numeric> for u16 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
-    }
+macro_rules! float_try_from_numeric {
+    ($($t:ident),*) => {
+        $(
+            impl TryFrom<Numeric> for $t {
+                type Error = NumericConversionError;
+
+                /// Converts to the nearest float, erroring if the value is too large to be
+                /// represented
+                fn try_from(value: Numeric) -> Result<Self, Self::Error> {
+                    match value.sign {
+                        NumericSign::NaN => Ok($t::NAN),
+                        NumericSign::PositiveInfinity => Ok($t::INFINITY),
+                        NumericSign::NegativeInfinity => Ok($t::NEG_INFINITY),
+                        NumericSign::Positive | NumericSign::Negative => {
+                            let float = $t::from_str(&value.to_string())
+                                .map_err(|_| NumericConversionError::OutOfRange)?;
+                            if float.is_infinite() {
+                                Err(NumericConversionError::OutOfRange)
+                            } else {
+                                Ok(float)
+                            }
+                        }
+                    }
+                }
+            }
+        )*
+    };
 }
 
-impl Into<Numeric> for u32 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+float_try_from_numeric!(f32, f64);
+
+impl PartialEq for Numeric {
+    /// Compares the same as Postgres' `numeric_cmp()`, so `1.0 == 1.00` and `NaN == NaN`
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
     }
 }
 
-impl Into<Numeric> for u64 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+impl Eq for Numeric {}
+
+impl PartialOrd for Numeric {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-impl Into<Numeric> for f32 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+impl Ord for Numeric {
+    /// Compares the same as Postgres' `numeric_cmp()`, which orders `NaN` after all other values
+    fn cmp(&self, other: &Self) -> Ordering {
+        // NaN sorts above Infinity, which sorts above every finite value
+        let rank = |sign: NumericSign| match sign {
+            NumericSign::NegativeInfinity => 0,
+            NumericSign::Negative | NumericSign::Positive => 1,
+            NumericSign::PositiveInfinity => 2,
+            NumericSign::NaN => 3,
+        };
+
+        match rank(self.sign).cmp(&rank(other.sign)) {
+            Ordering::Equal if rank(self.sign) == 1 => {}
+            ordering => return ordering,
+        }
+
+        // `from_parts()` keeps zero positive and strips leading and trailing zero digits, so a
+        // larger weight always means a larger magnitude
+        let abs_cmp = || match (self.digits.is_empty(), other.digits.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => self
+                .weight
+                .cmp(&other.weight)
+                .then_with(|| self.digits.cmp(&other.digits)),
+        };
+
+        match (self.sign, other.sign) {
+            (NumericSign::Positive, NumericSign::Positive) => abs_cmp(),
+            (NumericSign::Negative, NumericSign::Negative) => abs_cmp().reverse(),
+            (NumericSign::Negative, _) => Ordering::Less,
+            _ => Ordering::Greater,
+        }
     }
 }
 
-impl Into<Numeric> for f64 {
-    fn into(self) -> Numeric {
-        Numeric(format!("{}", self))
+macro_rules! numeric_binary_op {
+    ($trait:ident, $fn:ident, $pg_func:ident) => {
+        impl $trait for Numeric {
+            type Output = Numeric;
+
+            fn $fn(self, rhs: Numeric) -> Self::Output {
+                unsafe {
+                    direct_function_call(
+                        pg_sys::$pg_func,
+                        vec![self.into_datum(), rhs.into_datum()],
+                    )
+                    .expect(concat!(stringify!($pg_func), " returned NULL"))
+                }
+            }
+        }
+
+        impl<'a> $trait<&'a Numeric> for &'a Numeric {
+            type Output = Numeric;
+
+            fn $fn(self, rhs: &'a Numeric) -> Self::Output {
+                $trait::$fn(self.clone(), rhs.clone())
+            }
+        }
+    };
+}
+
+numeric_binary_op!(Add, add, numeric_add);
+numeric_binary_op!(Sub, sub, numeric_sub);
+numeric_binary_op!(Mul, mul, numeric_mul);
+numeric_binary_op!(Div, div, numeric_div);
+numeric_binary_op!(Rem, rem, numeric_mod);
+
+impl Neg for Numeric {
+    type Output = Numeric;
+
+    fn neg(self) -> Self::Output {
+        unsafe {
+            direct_function_call(pg_sys::numeric_uminus, vec![self.into_datum()])
+                .expect("numeric_uminus returned NULL")
+        }
     }
 }
 
@@ -152,30 +828,26 @@ impl FromDatum for Numeric {
     {
         if is_null {
             None
+        } else if datum == 0 {
+            panic!("numeric datum is declared non-null but Datum is zero");
         } else {
-            let cstr =
-                direct_function_call::<&std::ffi::CStr>(pg_sys::numeric_out, vec![Some(datum)])
-                    .expect("numeric_out returned null");
-            Some(Numeric(cstr.to_str().unwrap().into()))
+            let varlena = pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena);
+            Some(Numeric::from_varlena(varlena))
         }
     }
 }
 
 impl IntoDatum for Numeric {
     fn into_datum(self) -> Option<pg_sys::Datum> {
-        let cstring =
-            std::ffi::CString::new(self.0).expect("failed to convert numeric string into CString");
-        let cstr = cstring.as_c_str();
+        let mut bytes = self.to_varlena_bytes();
 
         unsafe {
-            direct_function_call_as_datum(
-                pg_sys::numeric_in,
-                vec![
-                    cstr.into_datum(),
-                    pg_sys::InvalidOid.into_datum(),
-                    0i32.into_datum(),
-                ],
-            )
+            let varlena = PgMemoryContexts::CurrentMemoryContext
+                .copy_ptr_into(bytes.as_mut_ptr(), bytes.len())
+                as *mut pg_sys::varlena;
+            crate::set_varsize(varlena, bytes.len() as i32);
+
+            Some(varlena as pg_sys::Datum)
         }
     }
 
@@ -0,0 +1,305 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{pg_sys, Date, FromDatum, IntoDatum, Numeric, Timestamp, TimestampWithTimeZone};
+
+/// A type which can be used as the subtype of a Postgres range type.
+///
+/// Implemented for the subtypes of Postgres' builtin range types, and by `#[derive(PostgresRange)]`
+/// for custom types.
+pub trait RangeSubType: FromDatum + IntoDatum {
+    /// The oid of the range type whose subtype is `Self`
+    fn range_type_oid() -> pg_sys::Oid;
+}
+
+impl RangeSubType for i32 {
+    fn range_type_oid() -> pg_sys::Oid {
+        pg_sys::INT4RANGEOID
+    }
+}
+
+impl RangeSubType for i64 {
+    fn range_type_oid() -> pg_sys::Oid {
+        pg_sys::INT8RANGEOID
+    }
+}
+
+impl RangeSubType for Numeric {
+    fn range_type_oid() -> pg_sys::Oid {
+        pg_sys::NUMRANGEOID
+    }
+}
+
+impl RangeSubType for Timestamp {
+    fn range_type_oid() -> pg_sys::Oid {
+        pg_sys::TSRANGEOID
+    }
+}
+
+impl RangeSubType for TimestampWithTimeZone {
+    fn range_type_oid() -> pg_sys::Oid {
+        pg_sys::TSTZRANGEOID
+    }
+}
+
+impl RangeSubType for Date {
+    fn range_type_oid() -> pg_sys::Oid {
+        pg_sys::DATERANGEOID
+    }
+}
+
+/// The lower or upper bound of a [`Range`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeBound<T> {
+    /// The range is unbounded on this side
+    Infinite,
+    /// The bound's value is included in the range
+    Inclusive(T),
+    /// The bound's value is excluded from the range
+    Exclusive(T),
+}
+
+impl<T> RangeBound<T> {
+    /// The value of this bound, or `None` if it is infinite
+    pub fn get(&self) -> Option<&T> {
+        match self {
+            RangeBound::Infinite => None,
+            RangeBound::Inclusive(value) | RangeBound::Exclusive(value) => Some(value),
+        }
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        matches!(self, RangeBound::Infinite)
+    }
+
+    pub fn is_inclusive(&self) -> bool {
+        matches!(self, RangeBound::Inclusive(_))
+    }
+
+    unsafe fn from_pg(bound: pg_sys::RangeBound) -> RangeBound<T>
+    where
+        T: FromDatum,
+    {
+        if bound.infinite {
+            RangeBound::Infinite
+        } else {
+            let value = T::from_datum(bound.val, false, pg_sys::InvalidOid)
+                .expect("range bound value was NULL");
+            if bound.inclusive {
+                RangeBound::Inclusive(value)
+            } else {
+                RangeBound::Exclusive(value)
+            }
+        }
+    }
+
+    fn into_pg(self, is_lower: bool) -> pg_sys::RangeBound
+    where
+        T: IntoDatum,
+    {
+        let (val, infinite, inclusive) = match self {
+            RangeBound::Infinite => (0, true, false),
+            RangeBound::Inclusive(value) => (
+                value
+                    .into_datum()
+                    .expect("range bound value cannot be NULL"),
+                false,
+                true,
+            ),
+            RangeBound::Exclusive(value) => (
+                value
+                    .into_datum()
+                    .expect("range bound value cannot be NULL"),
+                false,
+                false,
+            ),
+        };
+
+        pg_sys::RangeBound {
+            val,
+            infinite,
+            inclusive,
+            lower: is_lower,
+        }
+    }
+}
+
+/// A Postgres range type, such as `int4range` or `tstzrange`, over the subtype `T`.
+///
+/// Ranges are normalized by Postgres when converted into a Datum, so a `Range` read back from
+/// Postgres may not have the same bounds it was created with.  For example, discrete ranges such as
+/// `int4range` are canonicalized to have an inclusive lower and exclusive upper bound, and a range
+/// whose bounds don't include any values becomes empty.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn one_to_ten() -> Range<i32> {
+///     Range::new(RangeBound::Inclusive(1), RangeBound::Inclusive(10))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Range<T: RangeSubType> {
+    lower: RangeBound<T>,
+    upper: RangeBound<T>,
+    is_empty: bool,
+}
+
+impl<T: RangeSubType> Range<T> {
+    pub fn new(lower: RangeBound<T>, upper: RangeBound<T>) -> Self {
+        Range {
+            lower,
+            upper,
+            is_empty: false,
+        }
+    }
+
+    /// A range that contains no values
+    pub fn empty() -> Self {
+        Range {
+            lower: RangeBound::Infinite,
+            upper: RangeBound::Infinite,
+            is_empty: true,
+        }
+    }
+
+    /// A range that contains every value, ie, `(,)`
+    pub fn infinite() -> Self {
+        Range::new(RangeBound::Infinite, RangeBound::Infinite)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.is_empty
+    }
+
+    /// The lower bound of this range, or `None` if it is empty
+    pub fn lower(&self) -> Option<&RangeBound<T>> {
+        if self.is_empty {
+            None
+        } else {
+            Some(&self.lower)
+        }
+    }
+
+    /// The upper bound of this range, or `None` if it is empty
+    pub fn upper(&self) -> Option<&RangeBound<T>> {
+        if self.is_empty {
+            None
+        } else {
+            Some(&self.upper)
+        }
+    }
+
+    /// Consume this range, returning its `(lower, upper)` bounds, or `None` if it is empty
+    pub fn into_bounds(self) -> Option<(RangeBound<T>, RangeBound<T>)> {
+        if self.is_empty {
+            None
+        } else {
+            Some((self.lower, self.upper))
+        }
+    }
+}
+
+impl<T: RangeSubType> From<std::ops::Range<T>> for Range<T> {
+    fn from(range: std::ops::Range<T>) -> Self {
+        Range::new(
+            RangeBound::Inclusive(range.start),
+            RangeBound::Exclusive(range.end),
+        )
+    }
+}
+
+impl<T: RangeSubType> From<std::ops::RangeInclusive<T>> for Range<T> {
+    fn from(range: std::ops::RangeInclusive<T>) -> Self {
+        let (start, end) = range.into_inner();
+        Range::new(RangeBound::Inclusive(start), RangeBound::Inclusive(end))
+    }
+}
+
+impl<T: RangeSubType> From<std::ops::RangeFrom<T>> for Range<T> {
+    fn from(range: std::ops::RangeFrom<T>) -> Self {
+        Range::new(RangeBound::Inclusive(range.start), RangeBound::Infinite)
+    }
+}
+
+impl<T: RangeSubType> From<std::ops::RangeTo<T>> for Range<T> {
+    fn from(range: std::ops::RangeTo<T>) -> Self {
+        Range::new(RangeBound::Infinite, RangeBound::Exclusive(range.end))
+    }
+}
+
+impl<T: RangeSubType> From<std::ops::RangeToInclusive<T>> for Range<T> {
+    fn from(range: std::ops::RangeToInclusive<T>) -> Self {
+        Range::new(RangeBound::Infinite, RangeBound::Inclusive(range.end))
+    }
+}
+
+impl<T: RangeSubType> From<std::ops::RangeFull> for Range<T> {
+    fn from(_: std::ops::RangeFull) -> Self {
+        Range::infinite()
+    }
+}
+
+/// Lookup the type cache entry for the range type `range_type_oid`, ensuring it is a range type
+unsafe fn range_typcache(range_type_oid: pg_sys::Oid) -> *mut pg_sys::TypeCacheEntry {
+    let typcache = pg_sys::lookup_type_cache(
+        range_type_oid,
+        pg_sys::TYPECACHE_RANGE_INFO as std::os::raw::c_int,
+    );
+    if (*typcache).rngelemtype.is_null() {
+        panic!("type oid {} is not a range type", range_type_oid);
+    }
+    typcache
+}
+
+impl<T: RangeSubType> FromDatum for Range<T> {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _typoid: u32) -> Option<Range<T>> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("range datum is declared non-null but Datum is zero");
+        } else {
+            let range =
+                pg_sys::pg_detoast_datum(datum as *mut pg_sys::varlena) as *mut pg_sys::RangeType;
+            let typcache = range_typcache((*range).rangetypid);
+
+            let mut lower = pg_sys::RangeBound::default();
+            let mut upper = pg_sys::RangeBound::default();
+            let mut is_empty = false;
+            pg_sys::range_deserialize(typcache, range, &mut lower, &mut upper, &mut is_empty);
+
+            if is_empty {
+                Some(Range::empty())
+            } else {
+                Some(Range::new(
+                    RangeBound::from_pg(lower),
+                    RangeBound::from_pg(upper),
+                ))
+            }
+        }
+    }
+}
+
+impl<T: RangeSubType> IntoDatum for Range<T> {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        unsafe {
+            let typcache = range_typcache(T::range_type_oid());
+            let range = if self.is_empty {
+                pg_sys::make_empty_range(typcache)
+            } else {
+                let mut lower = self.lower.into_pg(true);
+                let mut upper = self.upper.into_pg(false);
+
+                // this will raise an ERROR if the lower bound is greater than the upper bound
+                pg_sys::make_range(typcache, &mut lower, &mut upper, false)
+            };
+
+            Some(range as pg_sys::Datum)
+        }
+    }
+
+    fn type_oid() -> u32 {
+        T::range_type_oid()
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Helper functions for working with custom Rust event trigger functions
+//!
+//! A `#[pg_event_trigger]` function is declared as `RETURNS event_trigger`, along with the
+//! `CREATE EVENT TRIGGER` that calls it.  The [`PgEventTrigger`] it takes describes the event that
+//! fired it:
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//!
+//! /// Logs every table created, or altered, in the database
+//! #[pg_event_trigger(event = "ddl_command_end", tags = ["CREATE TABLE", "ALTER TABLE"])]
+//! fn log_table_ddl(trigger: &PgEventTrigger) {
+//!     for command in trigger.ddl_commands() {
+//!         log!("{}: {} {}", trigger.tag(), command.object_type, command.object_identity);
+//!     }
+//! }
+//! ```
+//!
+//! A `ddl_command_end` event trigger reads the objects its command created or altered from
+//! [`PgEventTrigger::ddl_commands`], and a `sql_drop` event trigger reads the objects its command
+//! dropped from [`PgEventTrigger::dropped_objects`].
+
+use crate::{is_a, pg_sys, FromDatum, PgBox, Spi, SpiHeapTupleData};
+use std::ffi::CStr;
+use std::ptr::NonNull;
+
+#[inline]
+pub unsafe fn called_as_event_trigger(fcinfo: pg_sys::FunctionCallInfo) -> bool {
+    let fcinfo = fcinfo.as_ref().expect("fcinfo was null");
+    !fcinfo.context.is_null() && is_a(fcinfo.context, pg_sys::NodeTag_T_EventTriggerData)
+}
+
+/// The event that fired an event trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgEventTriggerEvent {
+    DdlCommandStart,
+    DdlCommandEnd,
+    SqlDrop,
+    TableRewrite,
+}
+
+/// The context of a call to an event trigger function, wrapping Postgres' `EventTriggerData`
+pub struct PgEventTrigger {
+    trigdata: NonNull<pg_sys::EventTriggerData>,
+}
+
+impl PgEventTrigger {
+    /// The event trigger `fcinfo`'s function was called for
+    ///
+    /// ## Safety
+    ///
+    /// `fcinfo` must be a valid `FunctionCallInfo`.  Panics if the function wasn't called by the
+    /// event trigger manager.
+    pub unsafe fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        if !called_as_event_trigger(fcinfo) {
+            panic!("not fired by event trigger manager");
+        }
+
+        PgEventTrigger {
+            trigdata: NonNull::new_unchecked((*fcinfo).context as *mut pg_sys::EventTriggerData),
+        }
+    }
+
+    /// The wrapped `EventTriggerData`
+    pub fn as_ptr(&self) -> *mut pg_sys::EventTriggerData {
+        self.trigdata.as_ptr()
+    }
+
+    /// The event that fired the event trigger
+    pub fn event(&self) -> PgEventTriggerEvent {
+        match self.event_name() {
+            "ddl_command_start" => PgEventTriggerEvent::DdlCommandStart,
+            "ddl_command_end" => PgEventTriggerEvent::DdlCommandEnd,
+            "sql_drop" => PgEventTriggerEvent::SqlDrop,
+            "table_rewrite" => PgEventTriggerEvent::TableRewrite,
+            other => panic!("unrecognized event trigger event: {}", other),
+        }
+    }
+
+    /// The name of the event that fired the event trigger, such as `ddl_command_end`
+    pub fn event_name(&self) -> &str {
+        unsafe { CStr::from_ptr(self.trigdata.as_ref().event) }
+            .to_str()
+            .expect("event name is not valid UTF8")
+    }
+
+    /// The command tag of the statement that fired the event trigger, such as `CREATE TABLE`
+    pub fn tag(&self) -> &str {
+        unsafe { CStr::from_ptr(command_tag_name(self.trigdata.as_ref())) }
+            .to_str()
+            .expect("command tag is not valid UTF8")
+    }
+
+    /// The parse tree of the statement that fired the event trigger, such as a `CreateStmt`.  Its
+    /// `type_` is the `NodeTag` of the statement.
+    pub fn parse_tree(&self) -> PgBox<pg_sys::Node> {
+        unsafe { PgBox::from_pg(self.trigdata.as_ref().parsetree) }
+    }
+
+    /// The objects created or altered by the statement that fired a `ddl_command_end` event
+    /// trigger, from `pg_event_trigger_ddl_commands()`
+    pub fn ddl_commands(&self) -> impl Iterator<Item = PgDdlCommand> {
+        if self.event() != PgEventTriggerEvent::DdlCommandEnd {
+            panic!("ddl_commands() is only available to a ddl_command_end event trigger");
+        }
+
+        let mut commands = Vec::new();
+        Spi::connect(|client| {
+            let table = client.select(
+                "SELECT classid, objid, objsubid, command_tag, object_type, schema_name, \
+                 object_identity, in_extension FROM pg_catalog.pg_event_trigger_ddl_commands()",
+                None,
+                None,
+            );
+            for row in table {
+                commands.push(PgDdlCommand {
+                    classid: column(&row, "classid"),
+                    objid: column(&row, "objid"),
+                    objsubid: column(&row, "objsubid"),
+                    command_tag: column(&row, "command_tag"),
+                    object_type: column(&row, "object_type"),
+                    schema_name: row["schema_name"].value(),
+                    object_identity: column(&row, "object_identity"),
+                    in_extension: column(&row, "in_extension"),
+                });
+            }
+            Ok(Some(()))
+        });
+        commands.into_iter()
+    }
+
+    /// The objects dropped by the statement that fired a `sql_drop` event trigger, from
+    /// `pg_event_trigger_dropped_objects()`
+    pub fn dropped_objects(&self) -> impl Iterator<Item = PgDroppedObject> {
+        if self.event() != PgEventTriggerEvent::SqlDrop {
+            panic!("dropped_objects() is only available to a sql_drop event trigger");
+        }
+
+        let mut objects = Vec::new();
+        Spi::connect(|client| {
+            let table = client.select(
+                "SELECT classid, objid, objsubid, original, normal, is_temporary, object_type, \
+                 schema_name, object_name, object_identity, address_names, address_args \
+                 FROM pg_catalog.pg_event_trigger_dropped_objects()",
+                None,
+                None,
+            );
+            for row in table {
+                objects.push(PgDroppedObject {
+                    classid: column(&row, "classid"),
+                    objid: column(&row, "objid"),
+                    objsubid: column(&row, "objsubid"),
+                    original: column(&row, "original"),
+                    normal: column(&row, "normal"),
+                    is_temporary: column(&row, "is_temporary"),
+                    object_type: column(&row, "object_type"),
+                    schema_name: row["schema_name"].value(),
+                    object_name: row["object_name"].value(),
+                    object_identity: column(&row, "object_identity"),
+                    address_names: column(&row, "address_names"),
+                    address_args: column(&row, "address_args"),
+                });
+            }
+            Ok(Some(()))
+        });
+        objects.into_iter()
+    }
+}
+
+/// An object created or altered by a DDL command, from [`PgEventTrigger::ddl_commands`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgDdlCommand {
+    /// The OID of the catalog the object is in
+    pub classid: pg_sys::Oid,
+    /// The OID of the object itself
+    pub objid: pg_sys::Oid,
+    /// The sub-object ID, such as a column's attribute number
+    pub objsubid: i32,
+    pub command_tag: String,
+    pub object_type: String,
+    pub schema_name: Option<String>,
+    /// The schema-qualified text rendering of the object's identity
+    pub object_identity: String,
+    /// Is the command part of an extension script?
+    pub in_extension: bool,
+}
+
+/// An object dropped by a DDL command, from [`PgEventTrigger::dropped_objects`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgDroppedObject {
+    /// The OID of the catalog the object was in
+    pub classid: pg_sys::Oid,
+    /// The OID of the object itself
+    pub objid: pg_sys::Oid,
+    /// The sub-object ID, such as a column's attribute number
+    pub objsubid: i32,
+    /// Was the object one of the root objects of the drop?
+    pub original: bool,
+    /// Was the object dropped through a normal dependency on a root object?
+    pub normal: bool,
+    /// Was the object a temporary object?
+    pub is_temporary: bool,
+    pub object_type: String,
+    pub schema_name: Option<String>,
+    pub object_name: Option<String>,
+    /// The schema-qualified text rendering of the object's identity
+    pub object_identity: String,
+    /// The object's address, for use with `pg_get_object_address()`
+    pub address_names: Vec<String>,
+    pub address_args: Vec<String>,
+}
+
+fn column<T: FromDatum>(row: &SpiHeapTupleData, name: &str) -> T {
+    row[name]
+        .value()
+        .unwrap_or_else(|| panic!("`{}` is NULL", name))
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12"))]
+unsafe fn command_tag_name(trigdata: &pg_sys::EventTriggerData) -> *const std::os::raw::c_char {
+    trigdata.tag
+}
+
+#[cfg(any(feature = "pg13", feature = "pg14"))]
+unsafe fn command_tag_name(trigdata: &pg_sys::EventTriggerData) -> *const std::os::raw::c_char {
+    pg_sys::GetCommandTagName(trigdata.tag)
+}
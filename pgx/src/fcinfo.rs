@@ -5,7 +5,9 @@
 //!
 //! Other than the exported macros, typically these functions are not necessary to call directly
 //! as they're used behind the scenes by the code generated by the `#[pg_extern]` macro.
-use crate::{pg_sys, void_mut_ptr, AllocatedByRust, FromDatum, PgBox, PgMemoryContexts};
+use crate::{
+    is_a, pg_sys, void_mut_ptr, AllocatedByRust, FromDatum, PgBox, PgList, PgMemoryContexts,
+};
 
 /// A macro for specifying default argument values so they get propery translated to SQL in
 /// `CREATE FUNCTION` statements
@@ -80,6 +82,33 @@ macro_rules! variadic {
     };
 }
 
+/// A macro for specifying the SQL name of the composite type a [`PgHeapTuple`](crate::PgHeapTuple)
+/// argument or return value represents, so it gets properly translated to SQL in `CREATE FUNCTION`
+/// statements
+///
+/// ## Examples
+///
+/// This example will create a SQL function like so:
+///
+/// ```sql
+/// CREATE OR REPLACE FUNCTION dog_name(dog dog) RETURNS text ...;
+/// ```
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn dog_name(dog: composite_type!("dog")) -> Option<String> {
+///     dog.get_by_name("name").unwrap()
+/// }
+/// ```
+#[macro_export]
+macro_rules! composite_type {
+    ($composite_type:expr) => {
+        $crate::PgHeapTuple
+    };
+}
+
 #[cfg(any(feature = "pg10", feature = "pg11"))]
 mod pg_10_11 {
     use crate::{pg_sys, FromDatum};
@@ -206,6 +235,34 @@ pub unsafe fn get_getarg_type(fcinfo: pg_sys::FunctionCallInfo, num: usize) -> p
     pg_sys::get_fn_expr_argtype(fcinfo.as_ref().unwrap().flinfo, num as std::os::raw::c_int)
 }
 
+/// Retrieve the declared typmod of the specified argument, such as the `3` in a `vector(3)`
+/// column, or `-1` if the argument has none or the calling expression isn't known.
+///
+/// # Safety
+///
+/// The provided `fcinfo` must be valid otherwise this function results in undefined behavior due
+/// to an out of bounds read.
+pub unsafe fn get_getarg_typmod(fcinfo: pg_sys::FunctionCallInfo, num: usize) -> i32 {
+    let flinfo = fcinfo.as_ref().unwrap().flinfo;
+    if flinfo.is_null() || (*flinfo).fn_expr.is_null() {
+        return -1;
+    }
+
+    let expr = (*flinfo).fn_expr;
+    let args = if is_a(expr, pg_sys::NodeTag_T_FuncExpr) {
+        (*(expr as *mut pg_sys::FuncExpr)).args
+    } else if is_a(expr, pg_sys::NodeTag_T_OpExpr) {
+        (*(expr as *mut pg_sys::OpExpr)).args
+    } else {
+        return -1;
+    };
+
+    match PgList::<pg_sys::Node>::from_pg(args).get_ptr(num) {
+        Some(arg) => pg_sys::exprTypmod(arg),
+        None => -1,
+    }
+}
+
 /// this is intended for Postgres functions that take an actual `cstring` argument, not for getting
 /// a varlena argument type as a CStr.
 #[inline]
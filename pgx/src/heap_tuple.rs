@@ -0,0 +1,324 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Provides a safe, owned wrapper around a Postgres composite type value
+use crate::{
+    heap_getattr_raw, name_data_to_str, pg_sys, regtypein, FromDatum, IntoDatum, PgBox, PgTupleDesc,
+};
+use std::num::NonZeroUsize;
+
+/// The reasons reading or writing a [`PgHeapTuple`] attribute can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgHeapTupleError {
+    /// The composite type has no attribute with this name
+    NoSuchAttributeName(String),
+    /// The composite type has no attribute with this (one-based) number
+    NoSuchAttributeNumber(NonZeroUsize),
+    /// The Rust type's Postgres type isn't binary coercible to the attribute's Postgres type
+    IncompatibleTypes {
+        attribute: String,
+        attribute_type: pg_sys::Oid,
+        value_type: pg_sys::Oid,
+    },
+}
+
+impl std::fmt::Display for PgHeapTupleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgHeapTupleError::NoSuchAttributeName(name) => {
+                write!(f, "no such attribute: {}", name)
+            }
+            PgHeapTupleError::NoSuchAttributeNumber(attno) => {
+                write!(f, "no such attribute number: {}", attno)
+            }
+            PgHeapTupleError::IncompatibleTypes {
+                attribute,
+                attribute_type,
+                value_type,
+            } => write!(
+                f,
+                "attribute `{}` has type oid {}, but the value has type oid {}",
+                attribute, attribute_type, value_type
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PgHeapTupleError {}
+
+impl From<PgHeapTupleError> for crate::PgError {
+    fn from(e: PgHeapTupleError) -> Self {
+        let code = match e {
+            PgHeapTupleError::IncompatibleTypes { .. } => {
+                crate::PgSqlErrorCode::ERRCODE_DATATYPE_MISMATCH
+            }
+            _ => crate::PgSqlErrorCode::ERRCODE_UNDEFINED_COLUMN,
+        };
+        crate::PgError::new(code, e.to_string())
+    }
+}
+
+/// An owned value of a Postgres composite type, such as a table's row type or a type created with
+/// `CREATE TYPE ... AS (...)`.
+///
+/// A `PgHeapTuple` can be used as a `#[pg_extern]` argument or return value.  Use the
+/// [`composite_type!`](crate::composite_type) macro to tell pgx the name of the SQL composite type,
+/// otherwise the function will be declared as taking or returning `record`.
+///
+/// Attributes can be read and written by name, or by their one-based attribute number, and are
+/// described by the backing [`PgTupleDesc`].
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// extension_sql!(
+///     "CREATE TYPE dog AS (name text, scritches integer);",
+///     name = "create_dog_type",
+///     bootstrap,
+/// );
+///
+/// #[pg_extern]
+/// fn scritch(mut dog: composite_type!("dog")) -> composite_type!("dog") {
+///     let scritches: i32 = dog.get_by_name("scritches").unwrap().unwrap_or_default();
+///     dog.set_by_name("scritches", scritches + 1).unwrap();
+///     dog
+/// }
+///
+/// #[pg_extern]
+/// fn make_dog(name: String) -> composite_type!("dog") {
+///     let mut dog = PgHeapTuple::new_composite_type("dog");
+///     dog.set_by_name("name", name).unwrap();
+///     dog
+/// }
+/// ```
+pub struct PgHeapTuple {
+    tuple: PgBox<pg_sys::HeapTupleData>,
+    tupdesc: PgTupleDesc<'static>,
+}
+
+impl PgHeapTuple {
+    /// Create a new value of the composite type named `type_name`, with every attribute `NULL`.
+    ///
+    /// Postgres will raise an ERROR if `type_name` isn't the name of a composite type.
+    pub fn new_composite_type(type_name: &str) -> PgHeapTuple {
+        let typoid = regtypein(type_name);
+
+        unsafe {
+            let tupdesc =
+                PgTupleDesc::from_pg_is_copy(pg_sys::lookup_rowtype_tupdesc_copy(typoid, -1));
+            let natts = tupdesc.len();
+            let mut values = vec![0 as pg_sys::Datum; natts];
+            let mut nulls = vec![true; natts];
+            let tuple =
+                pg_sys::heap_form_tuple(tupdesc.as_ptr(), values.as_mut_ptr(), nulls.as_mut_ptr());
+
+            PgHeapTuple {
+                tuple: PgBox::from_pg(tuple),
+                tupdesc,
+            }
+        }
+    }
+
+    /// Create a `PgHeapTuple` from a composite Datum, copying the tuple into the
+    /// `CurrentMemoryContext`
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as it cannot guarantee that the provided `pg_sys::Datum` actually
+    /// points to a composite type
+    pub unsafe fn from_composite_datum(composite: pg_sys::Datum) -> PgHeapTuple {
+        let htup_header =
+            pg_sys::pg_detoast_datum(composite as *mut pg_sys::varlena) as pg_sys::HeapTupleHeader;
+        let tup_type = crate::heap_tuple_header_get_type_id(htup_header);
+        let tup_typmod = crate::heap_tuple_header_get_typmod(htup_header);
+        let tupdesc =
+            PgTupleDesc::from_pg_is_copy(pg_sys::lookup_rowtype_tupdesc_copy(tup_type, tup_typmod));
+
+        let mut data = pg_sys::HeapTupleData::default();
+        data.t_len = crate::heap_tuple_header_get_datum_length(htup_header) as u32;
+        data.t_data = htup_header;
+
+        PgHeapTuple {
+            tuple: PgBox::from_pg(pg_sys::heap_copytuple(&mut data)),
+            tupdesc,
+        }
+    }
+
+    /// Create a `PgHeapTuple` from a `HeapTuple` described by `tupdesc`, copying both into the
+    /// `CurrentMemoryContext`
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as it cannot guarantee that `tuple` and `tupdesc` are valid, or that
+    /// `tupdesc` describes `tuple`
+    pub unsafe fn from_heap_tuple(
+        tuple: pg_sys::HeapTuple,
+        tupdesc: pg_sys::TupleDesc,
+    ) -> PgHeapTuple {
+        PgHeapTuple {
+            tuple: PgBox::from_pg(pg_sys::heap_copytuple(tuple)),
+            tupdesc: PgTupleDesc::from_pg_copy(tupdesc),
+        }
+    }
+
+    /// The [`PgTupleDesc`] describing this tuple's attributes
+    pub fn tupdesc(&self) -> &PgTupleDesc<'static> {
+        &self.tupdesc
+    }
+
+    /// The pg_type oid of this tuple's composite type
+    pub fn type_oid(&self) -> pg_sys::Oid {
+        self.tupdesc.oid()
+    }
+
+    /// How many attributes does this tuple have, including dropped attributes
+    pub fn len(&self) -> usize {
+        self.tupdesc.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the (one-based) number and description of each attribute, skipping dropped
+    /// attributes
+    pub fn attributes(
+        &self,
+    ) -> impl std::iter::Iterator<Item = (NonZeroUsize, &pg_sys::FormData_pg_attribute)> {
+        self.tupdesc
+            .iter()
+            .enumerate()
+            .filter(|(_, att)| !att.attisdropped)
+            .map(|(i, att)| (NonZeroUsize::new(i + 1).unwrap(), att))
+    }
+
+    /// Get the value of the attribute named `attname`.  Returns `Ok(None)` if it is `NULL`
+    pub fn get_by_name<T: FromDatum>(&self, attname: &str) -> Result<Option<T>, PgHeapTupleError> {
+        let attno = self.attno_by_name(attname)?;
+        self.get_by_index(attno)
+    }
+
+    /// Get the value of the attribute numbered `attno`, which is one-based.  Returns `Ok(None)` if
+    /// it is `NULL`
+    pub fn get_by_index<T: FromDatum>(
+        &self,
+        attno: NonZeroUsize,
+    ) -> Result<Option<T>, PgHeapTupleError> {
+        let att = self.attribute(attno)?;
+        unsafe {
+            match heap_getattr_raw(self.tuple.as_ptr(), attno.get(), self.tupdesc.as_ptr()) {
+                Some(datum) => Ok(T::from_datum(datum, false, att.atttypid)),
+                None => Ok(None),
+            }
+        }
+    }
+
+    /// Set the value of the attribute named `attname`.  Use `None` as the value to set it to `NULL`
+    pub fn set_by_name<T: IntoDatum>(
+        &mut self,
+        attname: &str,
+        value: T,
+    ) -> Result<(), PgHeapTupleError> {
+        let attno = self.attno_by_name(attname)?;
+        self.set_by_index(attno, value)
+    }
+
+    /// Set the value of the attribute numbered `attno`, which is one-based.  Use `None` as the
+    /// value to set it to `NULL`
+    pub fn set_by_index<T: IntoDatum>(
+        &mut self,
+        attno: NonZeroUsize,
+        value: T,
+    ) -> Result<(), PgHeapTupleError> {
+        let att = self.attribute(attno)?;
+        let value_type = T::type_oid();
+        if value_type != att.atttypid
+            && !unsafe { pg_sys::IsBinaryCoercible(value_type, att.atttypid) }
+        {
+            return Err(PgHeapTupleError::IncompatibleTypes {
+                attribute: name_data_to_str(&att.attname).to_string(),
+                attribute_type: att.atttypid,
+                value_type,
+            });
+        }
+
+        let mut cols = [attno.get() as std::os::raw::c_int];
+        let (mut values, mut nulls) = match value.into_datum() {
+            Some(datum) => ([datum], [false]),
+            None => ([0], [true]),
+        };
+
+        unsafe {
+            let tuple = pg_sys::heap_modify_tuple_by_cols(
+                self.tuple.as_ptr(),
+                self.tupdesc.as_ptr(),
+                1,
+                cols.as_mut_ptr(),
+                values.as_mut_ptr(),
+                nulls.as_mut_ptr(),
+            );
+            pg_sys::heap_freetuple(self.tuple.as_ptr());
+            self.tuple = PgBox::from_pg(tuple);
+        }
+
+        Ok(())
+    }
+
+    /// Convert this tuple into a composite Datum
+    pub fn into_composite_datum(self) -> pg_sys::Datum {
+        unsafe { pg_sys::heap_copy_tuple_as_datum(self.tuple.as_ptr(), self.tupdesc.as_ptr()) }
+    }
+
+    /// The `HeapTuple` this wraps
+    pub(crate) fn as_ptr(&self) -> pg_sys::HeapTuple {
+        self.tuple.as_ptr()
+    }
+
+    /// Convert this tuple into the `HeapTuple` it wraps, which is allocated by Postgres
+    pub fn into_pg(self) -> pg_sys::HeapTuple {
+        self.tuple.into_pg()
+    }
+
+    fn attribute(
+        &self,
+        attno: NonZeroUsize,
+    ) -> Result<&pg_sys::FormData_pg_attribute, PgHeapTupleError> {
+        match self.tupdesc.get(attno.get() - 1) {
+            Some(att) if !att.attisdropped => Ok(att),
+            _ => Err(PgHeapTupleError::NoSuchAttributeNumber(attno)),
+        }
+    }
+
+    fn attno_by_name(&self, attname: &str) -> Result<NonZeroUsize, PgHeapTupleError> {
+        self.attributes()
+            .find(|(_, att)| name_data_to_str(&att.attname) == attname)
+            .map(|(attno, _)| attno)
+            .ok_or_else(|| PgHeapTupleError::NoSuchAttributeName(attname.to_string()))
+    }
+}
+
+impl FromDatum for PgHeapTuple {
+    unsafe fn from_datum(
+        composite: pg_sys::Datum,
+        is_null: bool,
+        _typoid: u32,
+    ) -> Option<PgHeapTuple> {
+        if is_null {
+            None
+        } else if composite == 0 {
+            panic!("composite datum is declared non-null but Datum is zero");
+        } else {
+            Some(PgHeapTuple::from_composite_datum(composite))
+        }
+    }
+}
+
+impl IntoDatum for PgHeapTuple {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        Some(self.into_composite_datum())
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::RECORDOID
+    }
+}
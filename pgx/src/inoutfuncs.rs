@@ -2,7 +2,7 @@
 // governed by the MIT license that can be found in the LICENSE file.
 
 //! Helper trait for the `#[derive(PostgresType)]` proc macro for overriding custom Postgres type
-//! input/output functions.
+//! input/output functions, and for providing binary send/receive and type modifier functions.
 //!
 //! The default implementations use `serde_json` to serialize a custom type to human-readable strings,
 //! and `serde_cbor` to serialize internally as a `varlena *` for storage on disk.
@@ -54,3 +54,58 @@ pub trait JsonInOutFuncs<'de>: serde::de::Deserialize<'de> + serde::ser::Seriali
         serde_json::to_writer(buffer, self).expect("failed to serialize to json")
     }
 }
+
+/// `#[derive(PostgresType)]` types with the `#[pgx(sendrecv)]` attribute need to implement this
+/// trait to provide the binary send/receive functions for that type, which are used by binary
+/// `COPY` and by clients that request binary results
+pub trait SendRecvFuncs {
+    /// Convert `Self` into its binary representation
+    fn send(&self) -> Vec<u8>;
+
+    /// Given the binary representation of `Self`, as written by [`SendRecvFuncs::send`], parse it
+    /// into `Self`.
+    ///
+    /// It is expected that malformed input will raise an `error!()` or `panic!()`
+    fn recv(input: &[u8]) -> Self
+    where
+        Self: Sized;
+}
+
+/// Consume the unread bytes of the `StringInfo` that Postgres passes to a type's receive function.
+///
+/// ## Safety
+///
+/// This function is unsafe as it cannot guarantee that `internal` is a `pg_sys::StringInfo`
+#[doc(hidden)]
+pub unsafe fn consume_recv_buffer(internal: &Internal) -> &[u8] {
+    let buffer = internal
+        .get_mut::<pg_sys::StringInfoData>()
+        .expect("receive function called without a buffer");
+    let unread = std::slice::from_raw_parts(
+        buffer.data.offset(buffer.cursor as isize) as *const u8,
+        (buffer.len - buffer.cursor) as usize,
+    );
+    buffer.cursor = buffer.len;
+    unread
+}
+
+/// `#[derive(PostgresType)]` types with the `#[pgx(typmod)]` attribute need to implement this
+/// trait to accept a type modifier, such as the `3` in `vector(3)`
+pub trait TypmodInOutFuncs {
+    /// Given the modifiers from a type declaration like `vector(3)`, encode them into a
+    /// non-negative typmod.
+    ///
+    /// It is expected that malformed modifiers will raise an `error!()` or `panic!()`
+    fn typmod_in(modifiers: Array<&crate::cstr_core::CStr>) -> i32;
+
+    /// Convert a typmod produced by [`TypmodInOutFuncs::typmod_in`] back into its text form,
+    /// including the parentheses, such as `(3)`
+    fn typmod_out(typmod: i32) -> String;
+
+    /// Validate or coerce `self` to conform to `typmod`.  This is called with the value returned by
+    /// the type's input function and whenever a value is assigned or cast to a modified type.
+    ///
+    /// `is_explicit` is true for an explicit `CAST` or `::`.  The default implementation accepts
+    /// any value as-is
+    fn apply_typmod(&mut self, _typmod: i32, _is_explicit: bool) {}
+}
@@ -31,8 +31,10 @@ pub mod aggregate;
 pub mod callbacks;
 pub mod datum;
 pub mod enum_helper;
+pub mod event_trigger_support;
 pub mod fcinfo;
 pub mod guc;
+pub mod heap_tuple;
 pub mod hooks;
 pub mod htup;
 pub mod inoutfuncs;
@@ -52,9 +54,13 @@ pub mod rel;
 pub mod shmem;
 pub mod spi;
 pub mod stringinfo;
+pub mod subxact;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+pub mod support;
 pub mod trigger_support;
 pub mod tupdesc;
 pub mod varlena;
+pub mod window;
 pub mod wrappers;
 pub mod xid;
 
@@ -66,8 +72,10 @@ pub use atomics::*;
 pub use callbacks::*;
 pub use datum::*;
 pub use enum_helper::*;
+pub use event_trigger_support::*;
 pub use fcinfo::*;
 pub use guc::*;
+pub use heap_tuple::*;
 pub use hooks::*;
 pub use htup::*;
 pub use inoutfuncs::*;
@@ -83,9 +91,13 @@ pub use rel::*;
 pub use shmem::*;
 pub use spi::*;
 pub use stringinfo::*;
+pub use subxact::*;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+pub use support::*;
 pub use trigger_support::*;
 pub use tupdesc::*;
 pub use varlena::*;
+pub use window::*;
 pub use wrappers::*;
 pub use xid::*;
 
@@ -218,15 +230,30 @@ pub static DEFAULT_TYPEID_SQL_MAPPING: Lazy<HashSet<RustSqlMapping>> = Lazy::new
     map_type!(m, TimeWithTimeZone, "time with time zone");
     map_type!(m, Timestamp, "timestamp");
     map_type!(m, TimestampWithTimeZone, "timestamp with time zone");
+    map_type!(m, Interval, "interval");
     map_type!(m, pgx_pg_sys::PlannerInfo, "internal");
     map_type!(m, datum::Internal, "internal");
     map_type!(m, pgbox::PgBox<pgx_pg_sys::IndexAmRoutine>, "internal");
+    #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+    map_type!(m, support::SupportRequest, "internal");
+    #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+    map_type!(m, support::SupportResponse, "internal");
     map_type!(m, rel::PgRelation, "regclass");
     map_type!(m, datum::Numeric, "numeric");
     map_type!(m, datum::AnyElement, "anyelement");
     map_type!(m, datum::AnyArray, "anyarray");
     map_type!(m, datum::Inet, "inet");
+    map_type!(m, datum::Cidr, "cidr");
+    map_type!(m, datum::MacAddr, "macaddr");
+    map_type!(m, datum::MacAddr8, "macaddr8");
     map_type!(m, datum::Uuid, "uuid");
+    map_type!(m, heap_tuple::PgHeapTuple, "record");
+    map_type!(m, datum::Range<i32>, "int4range");
+    map_type!(m, datum::Range<i64>, "int8range");
+    map_type!(m, datum::Range<datum::Numeric>, "numrange");
+    map_type!(m, datum::Range<Timestamp>, "tsrange");
+    map_type!(m, datum::Range<TimestampWithTimeZone>, "tstzrange");
+    map_type!(m, datum::Range<Date>, "daterange");
 
     m
 });
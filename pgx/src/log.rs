@@ -5,7 +5,7 @@
 
 /// Postgres' various logging levels
 #[allow(dead_code)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PgLogLevel {
     /// Debugging messages, in categories of decreasing detail
     DEBUG5 = crate::pg_sys::DEBUG5 as isize,
@@ -51,10 +51,32 @@ pub enum PgLogLevel {
     PANIC = crate::pg_sys::PANIC as isize,
 }
 
-/// This list of SQL Error Codes is taken directly from Postgres 12's generated "utils/errcodes.h"
-#[allow(non_camel_case_types)]
-#[derive(Clone, Copy)]
-pub enum PgSqlErrorCode {
+macro_rules! sql_error_codes {
+    ($($(#[$attr:meta])* $name:ident = $value:expr,)*) => {
+        /// This list of SQL Error Codes is taken directly from Postgres 12's generated
+        /// "utils/errcodes.h"
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum PgSqlErrorCode {
+            $($(#[$attr])* $name = $value,)*
+        }
+
+        impl PgSqlErrorCode {
+            /// The code of an encoded SQLSTATE, such as a [`CaughtError`](crate::CaughtError)'s
+            /// `sqlerrcode`, if it's one of these
+            pub fn from_sqlerrcode(sqlerrcode: i32) -> Option<Self> {
+                $(
+                    if sqlerrcode == PgSqlErrorCode::$name as i32 {
+                        return Some(PgSqlErrorCode::$name);
+                    }
+                )*
+                None
+            }
+        }
+    };
+}
+
+sql_error_codes! {
     /// Class 00 - Successful Completion
     ERRCODE_SUCCESSFUL_COMPLETION = MAKE_SQLSTATE('0', '0', '0', '0', '0') as isize,
 
@@ -525,6 +547,331 @@ pub fn ereport(
     }
 }
 
+/// A Postgres `ereport()`, with all the fields of its `ErrorData`.
+///
+/// Unlike [`ereport()`], the report's context is only what's given to [`PgErrorReport::context`],
+/// and its location is that of the code calling [`PgErrorReport::new`], which is included in the
+/// server log with `log_error_verbosity = verbose`.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// PgErrorReport::new(
+///     PgLogLevel::ERROR,
+///     PgSqlErrorCode::ERRCODE_CHECK_VIOLATION,
+///     "value is out of range",
+/// )
+/// .detail("the value must be between 1 and 10")
+/// .table_name("my_table")
+/// .constraint_name("my_table_value_check")
+/// .report();
+/// ```
+///
+/// The [`ereport!`] macro is a shorthand for the same.
+#[derive(Debug, Clone)]
+pub struct PgErrorReport {
+    level: PgLogLevel,
+    sqlerrcode: i32,
+    message: String,
+    detail: Option<String>,
+    hint: Option<String>,
+    context: Option<String>,
+    position: Option<usize>,
+    schema_name: Option<String>,
+    table_name: Option<String>,
+    column_name: Option<String>,
+    datatype_name: Option<String>,
+    constraint_name: Option<String>,
+    file: &'static str,
+    line: u32,
+}
+
+impl PgErrorReport {
+    #[track_caller]
+    pub fn new<S: Into<String>>(level: PgLogLevel, code: PgSqlErrorCode, message: S) -> Self {
+        PgErrorReport::with_sqlerrcode(level, code as i32, message)
+    }
+
+    #[track_caller]
+    fn with_sqlerrcode<S: Into<String>>(level: PgLogLevel, sqlerrcode: i32, message: S) -> Self {
+        let location = std::panic::Location::caller();
+        PgErrorReport {
+            level,
+            sqlerrcode,
+            message: message.into(),
+            detail: None,
+            hint: None,
+            context: None,
+            position: None,
+            schema_name: None,
+            table_name: None,
+            column_name: None,
+            datatype_name: None,
+            constraint_name: None,
+            file: location.file(),
+            line: location.line(),
+        }
+    }
+
+    /// A secondary message, with more detail about the problem
+    pub fn detail<S: Into<String>>(mut self, detail: S) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// A suggestion of what to do about the problem
+    pub fn hint<S: Into<String>>(mut self, hint: S) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Where the problem happened, such as a function call stack
+    pub fn context<S: Into<String>>(mut self, context: S) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// The one-based character position of the problem in the original query string
+    pub fn position(mut self, position: usize) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// The name of the schema of the object the problem is about
+    pub fn schema_name<S: Into<String>>(mut self, schema_name: S) -> Self {
+        self.schema_name = Some(schema_name.into());
+        self
+    }
+
+    /// The name of the table the problem is about
+    pub fn table_name<S: Into<String>>(mut self, table_name: S) -> Self {
+        self.table_name = Some(table_name.into());
+        self
+    }
+
+    /// The name of the column the problem is about, which also requires a `table_name`
+    pub fn column_name<S: Into<String>>(mut self, column_name: S) -> Self {
+        self.column_name = Some(column_name.into());
+        self
+    }
+
+    /// The name of the data type the problem is about
+    pub fn datatype_name<S: Into<String>>(mut self, datatype_name: S) -> Self {
+        self.datatype_name = Some(datatype_name.into());
+        self
+    }
+
+    /// The name of the constraint the problem is about
+    pub fn constraint_name<S: Into<String>>(mut self, constraint_name: S) -> Self {
+        self.constraint_name = Some(constraint_name.into());
+        self
+    }
+
+    /// Send the report.  Reports of level `ERROR` or higher abort the current transaction, and
+    /// don't return.
+    pub fn report(self) {
+        use crate::pg_sys::{ereport_cstr_ptr as as_ptr, ereport_cstring as to_cstring};
+
+        let message = to_cstring(&self.message);
+        let detail = self.detail.as_deref().map(to_cstring);
+        let hint = self.hint.as_deref().map(to_cstring);
+        let context = self.context.as_deref().map(to_cstring);
+        let schema_name = self.schema_name.as_deref().map(to_cstring);
+        let table_name = self.table_name.as_deref().map(to_cstring);
+        let column_name = self.column_name.as_deref().map(to_cstring);
+        let datatype_name = self.datatype_name.as_deref().map(to_cstring);
+        let constraint_name = self.constraint_name.as_deref().map(to_cstring);
+
+        unsafe {
+            crate::pg_sys::pgx_ereport_report(
+                self.level as i32,
+                self.sqlerrcode,
+                message.as_ptr(),
+                as_ptr(&detail),
+                as_ptr(&hint),
+                as_ptr(&context),
+                self.position.unwrap_or(0) as i32,
+                as_ptr(&schema_name),
+                as_ptr(&table_name),
+                as_ptr(&column_name),
+                as_ptr(&datatype_name),
+                as_ptr(&constraint_name),
+                crate::pg_sys::ereport_static_cstr(self.file).as_ptr(),
+                self.line as i32,
+                std::ptr::null(),
+            );
+        }
+    }
+}
+
+/// An error to raise as a Postgres `ERROR`, with its SQLSTATE, message, and optional detail and hint.
+///
+/// `#[pg_extern]` functions can return `Result<T, E>` where `E: Into<PgError>`.  An `Err` is raised
+/// as a Postgres `ERROR` rather than as a Rust panic, so clients see its SQLSTATE, and no Rust
+/// code location:
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn checked_divide(a: i32, b: i32) -> Result<i32, PgError> {
+///     a.checked_div(b).ok_or_else(|| {
+///         PgError::new(PgSqlErrorCode::ERRCODE_DIVISION_BY_ZERO, "division by zero")
+///             .hint("don't divide by zero")
+///     })
+/// }
+/// ```
+///
+/// Any other error can be returned as a `Box<dyn std::error::Error>`, which is raised with
+/// `ERRCODE_RAISE_EXCEPTION`, like PL/pgSQL's `RAISE EXCEPTION`.
+#[derive(Debug, Clone)]
+pub struct PgError {
+    sqlerrcode: i32,
+    message: String,
+    detail: Option<String>,
+    hint: Option<String>,
+}
+
+impl PgError {
+    pub fn new<S: Into<String>>(code: PgSqlErrorCode, message: S) -> Self {
+        PgError {
+            sqlerrcode: code as i32,
+            message: message.into(),
+            detail: None,
+            hint: None,
+        }
+    }
+
+    /// Add a secondary message, with more detail about the error
+    pub fn detail<S: Into<String>>(mut self, detail: S) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Add a suggestion of what to do about the error
+    pub fn hint<S: Into<String>>(mut self, hint: S) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// The error's SQLSTATE, if it's a [`PgSqlErrorCode`].  [`PgError::sqlerrcode`] has it
+    /// either way.
+    pub fn code(&self) -> Option<PgSqlErrorCode> {
+        PgSqlErrorCode::from_sqlerrcode(self.sqlerrcode)
+    }
+
+    /// The encoded SQLSTATE, comparable to [`PgSqlErrorCode`] variants cast to an `i32`
+    pub fn sqlerrcode(&self) -> i32 {
+        self.sqlerrcode
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn detail_message(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    pub fn hint_message(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    /// Raise this error as a Postgres `ERROR`, aborting the current transaction
+    #[track_caller]
+    pub fn raise(self) -> ! {
+        let mut report =
+            PgErrorReport::with_sqlerrcode(PgLogLevel::ERROR, self.sqlerrcode, self.message);
+        if let Some(detail) = self.detail {
+            report = report.detail(detail);
+        }
+        if let Some(hint) = self.hint {
+            report = report.hint(hint);
+        }
+        report.report();
+        unreachable!("ereport(ERROR) returned")
+    }
+}
+
+impl std::fmt::Display for PgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for PgError {}
+
+impl From<Box<crate::CaughtError>> for PgError {
+    fn from(e: Box<crate::CaughtError>) -> Self {
+        PgError::from(*e)
+    }
+}
+
+impl From<crate::CaughtError> for PgError {
+    fn from(e: crate::CaughtError) -> Self {
+        PgError {
+            sqlerrcode: e.sqlerrcode,
+            message: e.message,
+            detail: e.detail,
+            hint: e.hint,
+        }
+    }
+}
+
+impl From<Box<dyn std::error::Error>> for PgError {
+    fn from(e: Box<dyn std::error::Error>) -> Self {
+        match e.downcast::<PgError>() {
+            Ok(e) => *e,
+            Err(e) => PgError::new(PgSqlErrorCode::ERRCODE_RAISE_EXCEPTION, e.to_string()),
+        }
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for PgError {
+    fn from(e: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        match e.downcast::<PgError>() {
+            Ok(e) => *e,
+            Err(e) => PgError::new(PgSqlErrorCode::ERRCODE_RAISE_EXCEPTION, e.to_string()),
+        }
+    }
+}
+
+/// Unwrap the `Result` returned by a `#[pg_extern]` function, raising its `Err` as a Postgres `ERROR`
+#[doc(hidden)]
+#[track_caller]
+pub fn unwrap_or_raise<T, E: Into<PgError>>(result: Result<T, E>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(e) => e.into().raise(),
+    }
+}
+
+/// Send a [`PgErrorReport`] with any of its optional fields, given as `field = value` after the
+/// message.  Reports of level `ERROR` or higher abort the current transaction.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let table = "my_table";
+/// ereport!(
+///     PgLogLevel::ERROR,
+///     PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION,
+///     format!("duplicate key in {}", table),
+///     detail = "key (id)=(1) already exists",
+///     table_name = table,
+/// );
+/// ```
+///
+/// [`error!`], [`warning!`] and [`notice!`] accept the same fields, given `code = ` first.
+#[macro_export]
+macro_rules! ereport {
+    ($level:expr, $code:expr, $message:expr $(, $field:ident = $value:expr)* $(,)?) => {
+        $crate::log::PgErrorReport::new($level, $code, $message)
+            $(.$field($value))*
+            .report()
+    };
+}
+
 /// Log to Postgres' `debug5` log level.
 ///
 /// This macro accepts arguments like the [`println`](std::println) and [`format`](std::format) macros.
@@ -686,8 +1033,24 @@ macro_rules! info {
 ///          6
 /// (1 row)
 /// ```
+///
+/// Given `code = ` and an SQLSTATE first, this instead sends a [`PgErrorReport`] with that code,
+/// the message, and any of its optional fields, like [`ereport!`]:
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// pgx::notice!(
+///     code = PgSqlErrorCode::ERRCODE_WARNING,
+///     "value was truncated",
+///     hint = "use a shorter value",
+/// );
+/// ```
 #[macro_export]
 macro_rules! notice {
+    (code = $code:expr, $message:expr $(, $field:ident = $value:expr)* $(,)?) => (
+        $crate::ereport!($crate::log::PgLogLevel::NOTICE, $code, $message $(, $field = $value)*);
+    );
     ($($arg:tt)*) => (
         $crate::log::elog($crate::log::PgLogLevel::NOTICE, format!($($arg)*).as_str());
     )
@@ -728,8 +1091,24 @@ macro_rules! notice {
 ///          6
 /// (1 row)
 /// ```
+///
+/// Given `code = ` and an SQLSTATE first, this instead sends a [`PgErrorReport`] with that code,
+/// the message, and any of its optional fields, like [`ereport!`]:
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// pgx::warning!(
+///     code = PgSqlErrorCode::ERRCODE_WARNING,
+///     "value was truncated",
+///     hint = "use a shorter value",
+/// );
+/// ```
 #[macro_export]
 macro_rules! warning {
+    (code = $code:expr, $message:expr $(, $field:ident = $value:expr)* $(,)?) => (
+        $crate::ereport!($crate::log::PgLogLevel::WARNING, $code, $message $(, $field = $value)*);
+    );
     ($($arg:tt)*) => (
         $crate::log::elog($crate::log::PgLogLevel::WARNING, format!($($arg)*).as_str());
     )
@@ -765,8 +1144,27 @@ macro_rules! warning {
 /// ERROR:  i=Some(1), sum=0
 /// CONTEXT:  src/lib.rs:37:9
 /// ```
+///
+/// Given `code = ` and an SQLSTATE first, this instead raises a [`PgErrorReport`] with that code,
+/// the message, and any of its optional fields, like [`ereport!`]:
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let table = "my_table";
+/// pgx::error!(
+///     code = PgSqlErrorCode::ERRCODE_UNIQUE_VIOLATION,
+///     format!("duplicate key in {}", table),
+///     detail = "key (id)=(1) already exists",
+///     table_name = table,
+/// );
+/// ```
 #[macro_export]
 macro_rules! error {
+    (code = $code:expr, $message:expr $(, $field:ident = $value:expr)* $(,)?) => ({
+        $crate::ereport!($crate::log::PgLogLevel::ERROR, $code, $message $(, $field = $value)*);
+        unreachable!("ERROR reports don't return")
+    });
     () => ({ panic!("explicit ERROR") });
     ($msg:expr) => ({ panic!($msg) });
     ($msg:expr,) => ({ panic!($msg) });
@@ -3,12 +3,38 @@
 
 //! Safe access to Postgres' *Server Programming Interface* (SPI).
 
-use crate::{pg_sys, FromDatum, IntoDatum, Json, PgMemoryContexts, PgOid};
+use crate::{
+    pg_sys, FromDatum, IntoDatum, IntoDatumDyn, Json, PgMemoryContexts, PgOid, PgTupleDesc,
+};
 use enum_primitive_derive::*;
-use num_traits::FromPrimitive;
+use num_traits::{FromPrimitive, ToPrimitive};
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::ops::{Index, IndexMut};
+use std::marker::PhantomData;
+use std::ops::{Deref, Index, IndexMut};
+use std::ptr::NonNull;
+
+/// How many rows an iterated [`SpiCursor`] fetches at a time, by default
+const DEFAULT_CURSOR_BATCH_SIZE: i64 = 100;
+
+thread_local! {
+    /// How many SPI connections [`Spi::connect`] currently has open
+    static CONNECTION_DEPTH: Cell<usize> = Cell::new(0);
+
+    /// Bumped by every [`Spi::commit`] and [`Spi::rollback`], both of which free every SPI result
+    /// and cursor.  `SpiTupleTable`s and `SpiCursor`s from an older generation can't be used
+    static TRANSACTION_GENERATION: Cell<u64> = Cell::new(0);
+
+    /// Set while a `#[pg_procedure]` that was `CALL`ed outside of a transaction block hasn't
+    /// connected to SPI yet.  Its first connection is then non-atomic
+    #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+    static NONATOMIC_CALL: Cell<bool> = Cell::new(false);
+}
+
+fn transaction_generation() -> u64 {
+    TRANSACTION_GENERATION.with(|generation| generation.get())
+}
 
 #[derive(Debug, Primitive)]
 pub enum SpiOk {
@@ -31,27 +57,151 @@ pub enum SpiOk {
     TdRegister = 17,
 }
 
-#[derive(Debug, Primitive)]
+#[derive(Debug)]
 pub enum SpiError {
-    // NB:  These are #define'd as negative, but we redefine them as positive so that
-    // #[derive(Primitive)] will work.  We just need to negate result codes from the
-    // various SPI_xxx functions when looking for errors
-    Connect = 1,
-    Copy = 2,
-    Opunknown = 3,
-    Unconnected = 4,
+    Connect,
+    Copy,
+    Opunknown,
+    Unconnected,
     #[allow(dead_code)]
-    Cursor = 5, /* not used anymore */
-    Argument = 6,
-    Param = 7,
-    Transaction = 8,
-    Noattribute = 9,
-    Nooutfunc = 10,
-    Typunknown = 11,
-    RelDuplicate = 12,
-    RelNotFound = 13,
+    Cursor, /* not used anymore */
+    Argument,
+    Param,
+    Transaction,
+    Noattribute,
+    Nooutfunc,
+    Typunknown,
+    RelDuplicate,
+    RelNotFound,
+
+    /// Postgres raised an `ERROR` while executing a query through one of the `try_xxx` functions,
+    /// such as [`SpiClient::try_select`].  The subtransaction the query ran in has been rolled
+    /// back, so the current transaction is still usable.
+    Postgres {
+        /// The five-character SQLSTATE code of the error, such as `"22012"`
+        sqlstate: String,
+        message: String,
+        detail: Option<String>,
+        hint: Option<String>,
+    },
+
+    /// A column's type can't be read as the Rust type of the [`FromSpiRow`] field it maps to
+    TypeMismatch {
+        column: String,
+        expected_type: String,
+        actual_type: String,
+    },
+
+    /// A column that maps to a [`FromSpiRow`] field which isn't an `Option<T>` was `NULL`
+    UnexpectedNull {
+        column: String,
+    },
+}
+
+impl SpiError {
+    /// Convert the result code of one of the various SPI_xxx functions, which are #define'd as
+    /// negative numbers, into a `SpiError`
+    fn from_status(status_code: i32) -> Option<SpiError> {
+        SpiError::from_i32(-status_code)
+    }
+
+    /// Copy the parts of a caught Postgres `ERROR` that we care about into a `SpiError`
+    unsafe fn from_error_data(edata: *const pg_sys::ErrorData) -> SpiError {
+        let string = |ptr: *const std::os::raw::c_char| {
+            if ptr.is_null() {
+                None
+            } else {
+                Some(std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned())
+            }
+        };
+
+        SpiError::Postgres {
+            sqlstate: string(pg_sys::unpack_sql_state((*edata).sqlerrcode))
+                .expect("unpack_sql_state() returned NULL"),
+            message: string((*edata).message).unwrap_or_default(),
+            detail: string((*edata).detail),
+            hint: string((*edata).hint),
+        }
+    }
+}
+
+// `SpiError` can't `#[derive(Primitive)]` because some of its variants carry data.  The others are
+// numbered like their SPI_ERROR_xxx result codes, but positive
+impl FromPrimitive for SpiError {
+    fn from_i64(n: i64) -> Option<Self> {
+        match n {
+            1 => Some(SpiError::Connect),
+            2 => Some(SpiError::Copy),
+            3 => Some(SpiError::Opunknown),
+            4 => Some(SpiError::Unconnected),
+            5 => Some(SpiError::Cursor),
+            6 => Some(SpiError::Argument),
+            7 => Some(SpiError::Param),
+            8 => Some(SpiError::Transaction),
+            9 => Some(SpiError::Noattribute),
+            10 => Some(SpiError::Nooutfunc),
+            11 => Some(SpiError::Typunknown),
+            12 => Some(SpiError::RelDuplicate),
+            13 => Some(SpiError::RelNotFound),
+            _ => None,
+        }
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        i64::try_from(n).ok().and_then(SpiError::from_i64)
+    }
+}
+
+impl ToPrimitive for SpiError {
+    fn to_i64(&self) -> Option<i64> {
+        match self {
+            SpiError::Connect => Some(1),
+            SpiError::Copy => Some(2),
+            SpiError::Opunknown => Some(3),
+            SpiError::Unconnected => Some(4),
+            SpiError::Cursor => Some(5),
+            SpiError::Argument => Some(6),
+            SpiError::Param => Some(7),
+            SpiError::Transaction => Some(8),
+            SpiError::Noattribute => Some(9),
+            SpiError::Nooutfunc => Some(10),
+            SpiError::Typunknown => Some(11),
+            SpiError::RelDuplicate => Some(12),
+            SpiError::RelNotFound => Some(13),
+            SpiError::Postgres { .. }
+            | SpiError::TypeMismatch { .. }
+            | SpiError::UnexpectedNull { .. } => None,
+        }
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.to_i64().map(|n| n as u64)
+    }
 }
 
+impl std::fmt::Display for SpiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpiError::Postgres { message, .. } => write!(f, "{}", message),
+            SpiError::TypeMismatch {
+                column,
+                expected_type,
+                actual_type,
+            } => write!(
+                f,
+                "column \"{}\" is of type {}, which can't be read as {}",
+                column, actual_type, expected_type
+            ),
+            SpiError::UnexpectedNull { column } => {
+                write!(f, "column \"{}\" is NULL, but is not an Option", column)
+            }
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+impl std::error::Error for SpiError {}
+
 pub struct Spi;
 
 pub struct SpiClient;
@@ -64,18 +214,24 @@ pub struct SpiTupleTable {
     size: usize,
     tupdesc: Option<pg_sys::TupleDesc>,
     current: isize,
+    generation: u64,
 }
 
 /// Represents a single `pg_sys::Datum` inside a `SpiHeapTupleData`
 pub struct SpiHeapTupleDataEntry {
     datum: Option<pg_sys::Datum>,
     type_oid: pg_sys::Oid,
+    generation: u64,
 }
 
 /// Represents the set of `pg_sys::Datum`s in a `pg_sys::HeapTuple`
+///
+/// Like the `SpiTupleTable` it came from, it is freed by [`Spi::commit`] and [`Spi::rollback`], and
+/// panics if it's used afterwards.
 pub struct SpiHeapTupleData {
     tupdesc: pg_sys::TupleDesc,
     entries: HashMap<usize, SpiHeapTupleDataEntry>,
+    generation: u64,
 }
 
 impl Spi {
@@ -166,6 +322,38 @@ impl Spi {
         })
     }
 
+    /// Like [`Spi::get_one`], but a Postgres `ERROR` raised by the query is returned as
+    /// [`SpiError::Postgres`] rather than aborting the transaction
+    pub fn try_get_one<A: FromDatum + IntoDatum>(
+        query: &str,
+    ) -> std::result::Result<Option<A>, SpiError> {
+        Spi::try_connect(|client| Ok(client.try_select(query, Some(1), None)?.first().get_one()))
+    }
+
+    /// Like [`Spi::get_one_with_args`], but a Postgres `ERROR` raised by the query is returned as
+    /// [`SpiError::Postgres`] rather than aborting the transaction
+    pub fn try_get_one_with_args<A: FromDatum + IntoDatum>(
+        query: &str,
+        args: Vec<(PgOid, Option<pg_sys::Datum>)>,
+    ) -> std::result::Result<Option<A>, SpiError> {
+        Spi::try_connect(|client| {
+            Ok(client
+                .try_select(query, Some(1), Some(args))?
+                .first()
+                .get_one())
+        })
+    }
+
+    /// Like [`Spi::run`], but a Postgres `ERROR` raised by the statement is returned as
+    /// [`SpiError::Postgres`] rather than aborting the transaction
+    pub fn try_run(query: &str) -> std::result::Result<(), SpiError> {
+        Spi::try_connect(|mut client| {
+            client.try_update(query, None, None)?;
+            Ok(Some(()))
+        })
+        .map(|_| ())
+    }
+
     /// explain a query, returning its result in json form
     pub fn explain(query: &str) -> Json {
         Spi::connect(|mut client| {
@@ -197,30 +385,85 @@ impl Spi {
     >(
         f: F,
     ) -> Option<R> {
+        match Spi::try_connect(f) {
+            Ok(result) => result,
+
+            // closure returned an error
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Like [`Spi::connect`], but an error returned by the closure is returned to the caller
+    /// rather than raised as a Postgres `ERROR`
+    pub fn try_connect<
+        R: FromDatum + IntoDatum,
+        F: FnOnce(SpiClient) -> std::result::Result<Option<R>, SpiError>,
+    >(
+        f: F,
+    ) -> std::result::Result<Option<R>, SpiError> {
         let outer_memory_context =
             PgMemoryContexts::For(PgMemoryContexts::CurrentMemoryContext.value());
 
         /// a struct to manage our SPI connection lifetime
-        struct SpiConnection;
+        struct SpiConnection {
+            #[allow(dead_code)]
+            nonatomic: bool,
+        }
         impl SpiConnection {
             /// Connect to Postgres' SPI system
-            fn connect() -> Self {
-                // connect to SPI
-                Spi::check_status(unsafe { pg_sys::SPI_connect() });
-                SpiConnection
+            fn connect() -> std::result::Result<Self, SpiError> {
+                // connect to SPI, non-atomically if we're the first connection of a procedure
+                // that's allowed to commit
+                #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+                let nonatomic = NONATOMIC_CALL.with(|nonatomic| nonatomic.replace(false));
+                #[cfg(feature = "pg10")]
+                let nonatomic = false;
+
+                #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+                let status = unsafe {
+                    if nonatomic {
+                        pg_sys::SPI_connect_ext(pg_sys::SPI_OPT_NONATOMIC as i32)
+                    } else {
+                        pg_sys::SPI_connect()
+                    }
+                };
+                #[cfg(feature = "pg10")]
+                let status = unsafe { pg_sys::SPI_connect() };
+
+                if let Err(e) = Spi::try_check_status(status) {
+                    #[cfg(any(
+                        feature = "pg11",
+                        feature = "pg12",
+                        feature = "pg13",
+                        feature = "pg14"
+                    ))]
+                    NONATOMIC_CALL.with(|call| call.set(nonatomic));
+                    return Err(e);
+                }
+                CONNECTION_DEPTH.with(|depth| depth.set(depth.get() + 1));
+                Ok(SpiConnection { nonatomic })
             }
         }
 
         impl Drop for SpiConnection {
             /// when SpiConnection is dropped, we make sure to disconnect from SPI
             fn drop(&mut self) {
+                CONNECTION_DEPTH.with(|depth| depth.set(depth.get() - 1));
+                #[cfg(any(
+                    feature = "pg11",
+                    feature = "pg12",
+                    feature = "pg13",
+                    feature = "pg14"
+                ))]
+                NONATOMIC_CALL.with(|nonatomic| nonatomic.set(self.nonatomic));
+
                 // disconnect from SPI
                 Spi::check_status(unsafe { pg_sys::SPI_finish() });
             }
         }
 
         // connect to SPI
-        let _connection = SpiConnection::connect();
+        let _connection = SpiConnection::connect()?;
 
         // run the provided closure within the memory context that SPI_connect()
         // just put us un.  We'll disconnect from SPI when the closure is finished.
@@ -252,25 +495,67 @@ impl Spi {
                     None => None,
                 };
 
-                copied_datum
+                Ok(copied_datum)
             }
 
             // closure returned an error
-            Err(e) => panic!("{:?}", e),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Commit the current transaction and start a new one.
+    ///
+    /// This is only allowed within a `#[pg_procedure]` that was `CALL`ed outside of an explicit
+    /// transaction block, and only through its outermost SPI connection.  Otherwise Postgres
+    /// raises an "invalid transaction termination" `ERROR`.
+    ///
+    /// Every `SpiTupleTable`, `SpiHeapTupleData` and `SpiCursor` obtained before the commit is
+    /// freed by it, and panics if it's used afterwards.
+    #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+    pub fn commit() {
+        Spi::end_transaction(|| unsafe { pg_sys::SPI_commit() })
+    }
+
+    /// Roll back the current transaction and start a new one.
+    ///
+    /// This has the same restrictions as [`Spi::commit`], and likewise invalidates every
+    /// `SpiTupleTable`, `SpiHeapTupleData` and `SpiCursor` obtained before it.
+    #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+    pub fn rollback() {
+        Spi::end_transaction(|| unsafe { pg_sys::SPI_rollback() })
+    }
+
+    #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+    fn end_transaction(f: impl FnOnce()) {
+        if CONNECTION_DEPTH.with(|depth| depth.get()) == 0 {
+            panic!("transaction control requires an SPI connection");
         }
+        f();
+        // like PL/pgSQL's COMMIT and ROLLBACK, as SPI_commit() and SPI_rollback() leave us with
+        // no transaction at all until Postgres 15
+        unsafe { pg_sys::SPI_start_transaction() };
+        TRANSACTION_GENERATION.with(|generation| generation.set(generation.get() + 1));
     }
 
     pub fn check_status(status_code: i32) -> SpiOk {
+        match Spi::try_check_status(status_code) {
+            Ok(ok) => ok,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    /// Like [`Spi::check_status`], but returns the `SpiError` rather than panicking
+    pub fn try_check_status(status_code: i32) -> std::result::Result<SpiOk, SpiError> {
         if status_code > 0 {
             let status_enum = SpiOk::from_i32(status_code);
             match status_enum {
-                Some(ok) => ok,
+                Some(ok) => Ok(ok),
                 None => panic!("unrecognized SPI status code {}", status_code),
             }
         } else {
-            let status_enum = SpiError::from_i32(-status_code);
+            let status_enum = SpiError::from_status(status_code);
             match status_enum {
-                Some(e) => panic!("{:?}", e),
+                Some(e) => Err(e),
                 None => panic!("unrecognized SPI status code {}", status_code),
             }
         }
@@ -279,12 +564,7 @@ impl Spi {
 
 impl SpiClient {
     /// perform a SELECT statement
-    pub fn select(
-        &self,
-        query: &str,
-        limit: Option<i64>,
-        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
-    ) -> SpiTupleTable {
+    pub fn select<A, Q: Query<A>>(&self, query: Q, limit: Option<i64>, args: A) -> SpiTupleTable {
         // Postgres docs say:
         //
         //    It is generally unwise to mix read-only and read-write commands within a single function
@@ -296,54 +576,335 @@ impl SpiClient {
         // TODO:  can we detect if the command counter (or something?) has incremented and if yes
         //        then we set read_only=false, else we can set it to true?
         //        Is this even a good idea?
-        SpiClient::execute(query, false, limit, args)
+        match query.execute(self, false, limit, args) {
+            Ok(table) => table,
+            Err(e) => panic!("{:?}", e),
+        }
     }
 
     /// perform any query (including utility statements) that modify the database in some way
-    pub fn update(
+    pub fn update<A, Q: Query<A>>(
         &mut self,
-        query: &str,
+        query: Q,
         limit: Option<i64>,
-        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+        args: A,
     ) -> SpiTupleTable {
-        SpiClient::execute(query, false, limit, args)
+        match query.execute(self, false, limit, args) {
+            Ok(table) => table,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    /// Like [`SpiClient::select`], but the query runs in an internal subtransaction.  If it raises
+    /// a Postgres `ERROR`, the subtransaction is rolled back and the error is returned as
+    /// [`SpiError::Postgres`], leaving the current transaction usable.
+    pub fn try_select<A, Q: Query<A>>(
+        &self,
+        query: Q,
+        limit: Option<i64>,
+        args: A,
+    ) -> std::result::Result<SpiTupleTable, SpiError> {
+        in_subtransaction(|| query.execute(self, false, limit, args))
+    }
+
+    /// Like [`SpiClient::update`], but the query runs in an internal subtransaction.  If it raises
+    /// a Postgres `ERROR`, the subtransaction is rolled back and the error is returned as
+    /// [`SpiError::Postgres`], leaving the current transaction usable.
+    pub fn try_update<A, Q: Query<A>>(
+        &mut self,
+        query: Q,
+        limit: Option<i64>,
+        args: A,
+    ) -> std::result::Result<SpiTupleTable, SpiError> {
+        let client = &*self;
+        in_subtransaction(|| query.execute(client, false, limit, args))
+    }
+
+    /// Parse and plan `query` once, via `SPI_prepare`, so it can be executed repeatedly with
+    /// [`SpiClient::select`] or [`SpiClient::update`].  `$1`, `$2`, etc in the query are
+    /// parameters of the types in `args`.
+    ///
+    /// It is executed with either an `Option<Vec<Option<Datum>>>` of the parameters' values, or
+    /// [`PreparedArgs`], such as a tuple, whose types must match `args`.
+    ///
+    /// The plan lives until this SPI connection is finished, unless [`PreparedStatement::keep`]
+    /// is called.
+    pub fn prepare(&self, query: &str, args: &[PgOid]) -> PreparedStatement<'_> {
+        let src = std::ffi::CString::new(query).expect("query contained a null byte");
+        let mut argtypes = args.iter().map(|oid| oid.value()).collect::<Vec<_>>();
+        let plan = unsafe {
+            pg_sys::SPI_prepare(src.as_ptr(), argtypes.len() as i32, argtypes.as_mut_ptr())
+        };
+        if plan.is_null() {
+            Spi::check_status(unsafe { pg_sys::SPI_result });
+        }
+        PreparedStatement {
+            plan,
+            _client: PhantomData,
+        }
+    }
+
+    /// Open a cursor for `query`, via `SPI_cursor_open`, so its results can be fetched in batches
+    /// rather than all at once
+    pub fn open_cursor<A, Q: Query<A>>(&self, query: Q, args: A) -> SpiCursor<'_> {
+        self.open_cursor_with_options(query, args, &SpiCursorOptions::default())
+    }
+
+    /// Open a cursor for `query`, like [`SpiClient::open_cursor`], with a name of our choosing
+    /// and/or the ability to scroll backwards
+    pub fn open_cursor_with_options<A, Q: Query<A>>(
+        &self,
+        query: Q,
+        args: A,
+        options: &SpiCursorOptions,
+    ) -> SpiCursor<'_> {
+        let portal = query.open_cursor(self, false, args, options);
+        SpiCursor::new(portal)
+    }
+
+    /// Find a cursor that was opened earlier in this transaction, perhaps by a previous function
+    /// call that used [`SpiCursor::detach_into_name`]
+    pub fn find_cursor(&self, name: &str) -> Option<SpiCursor<'_>> {
+        let name = std::ffi::CString::new(name).expect("cursor name contained a null byte");
+        let portal = unsafe { pg_sys::SPI_cursor_find(name.as_ptr()) };
+        if portal.is_null() {
+            None
+        } else {
+            Some(SpiCursor::new(portal))
+        }
+    }
+
+    /// Wrap the result of the last SPI command in a `SpiTupleTable`
+    fn prepare_tuple_table(status_code: SpiOk) -> SpiTupleTable {
+        SpiTupleTable {
+            status_code,
+            table: unsafe { pg_sys::SPI_tuptable },
+            size: unsafe { pg_sys::SPI_processed as usize },
+            tupdesc: if unsafe { pg_sys::SPI_tuptable }.is_null() {
+                None
+            } else {
+                Some(unsafe { (*pg_sys::SPI_tuptable).tupdesc })
+            },
+            current: -1,
+            generation: transaction_generation(),
+        }
+    }
+}
+
+/// Allows the SPI connection of a `#[pg_procedure]` to be non-atomic, for the duration of the call,
+/// when the procedure was `CALL`ed outside of a transaction block
+#[doc(hidden)]
+#[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+pub struct ProcedureCall {
+    nonatomic: bool,
+}
+
+#[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+impl ProcedureCall {
+    /// ## Safety
+    ///
+    /// `fcinfo` must be the valid `FunctionCallInfo` of the procedure being called
+    pub unsafe fn enter(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        let context = (*fcinfo).context;
+        let nonatomic = crate::is_a(context, pg_sys::NodeTag_T_CallContext)
+            && !(*(context as *mut pg_sys::CallContext)).atomic;
+
+        ProcedureCall {
+            nonatomic: NONATOMIC_CALL.with(|call| call.replace(nonatomic)),
+        }
+    }
+}
+
+#[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+impl Drop for ProcedureCall {
+    fn drop(&mut self) {
+        NONATOMIC_CALL.with(|call| call.set(self.nonatomic));
+    }
+}
+
+/// Run `f` in an internal subtransaction, as PL/pgSQL does for a block with an `EXCEPTION` clause.
+///
+/// If `f` raises a Postgres `ERROR` the subtransaction is rolled back and the error is returned
+/// as [`SpiError::Postgres`].  Rust panics are rethrown once the subtransaction is rolled back.
+fn in_subtransaction<R, F: FnOnce() -> std::result::Result<R, SpiError>>(
+    f: F,
+) -> std::result::Result<R, SpiError> {
+    unsafe {
+        let memory_context = pg_sys::CurrentMemoryContext;
+        let resource_owner = pg_sys::CurrentResourceOwner;
+
+        pg_sys::BeginInternalSubTransaction(std::ptr::null_mut());
+        // we want to run inside the caller's memory context, not the subtransaction's
+        pg_sys::CurrentMemoryContext = memory_context;
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+        match result {
+            Ok(result) => {
+                pg_sys::ReleaseCurrentSubTransaction();
+                pg_sys::CurrentMemoryContext = memory_context;
+                pg_sys::CurrentResourceOwner = resource_owner;
+                result
+            }
+
+            Err(e) => {
+                let error = if e.is::<pg_sys::JumpContext>() {
+                    // Postgres left us in the ErrorContext, which we can't copy the error into
+                    pg_sys::CurrentMemoryContext = memory_context;
+                    let edata = pg_sys::CopyErrorData();
+                    pg_sys::FlushErrorState();
+                    let error = SpiError::from_error_data(edata);
+                    pg_sys::FreeErrorData(edata);
+                    Some(error)
+                } else {
+                    None
+                };
+
+                pg_sys::RollbackAndReleaseCurrentSubTransaction();
+                pg_sys::CurrentMemoryContext = memory_context;
+                pg_sys::CurrentResourceOwner = resource_owner;
+
+                match error {
+                    Some(error) => Err(error),
+                    None => std::panic::resume_unwind(e),
+                }
+            }
+        }
     }
+}
+
+/// Split nullable arguments into the `Datum` and `nulls` arrays that the `SPI_xxx` functions expect
+fn args_to_datums(
+    args: impl IntoIterator<Item = Option<pg_sys::Datum>>,
+) -> (Vec<pg_sys::Datum>, Vec<std::os::raw::c_char>) {
+    let mut datums = vec![];
+    let mut nulls = vec![];
+
+    for datum in args {
+        match datum {
+            Some(datum) => {
+                // ' ' here means that the datum is not null
+                datums.push(datum);
+                nulls.push(' ' as std::os::raw::c_char);
+            }
+
+            None => {
+                // 'n' here means that the datum is null
+                datums.push(0);
+                nulls.push('n' as std::os::raw::c_char);
+            }
+        }
+    }
+
+    (datums, nulls)
+}
+
+/// Arguments for the `$1`, `$2`, etc parameters of a SQL query, whose types are inferred from
+/// the arguments.
+///
+/// This is implemented for tuples of up to twelve [`IntoDatum`] values, such as `(42i32, "x")`,
+/// for `&[&dyn IntoDatumDyn]`, and for explicitly typed `Option<Vec<(PgOid, Option<Datum>)>>`,
+/// where `None` means the query has no parameters.
+pub trait SpiArgs {
+    /// The type and value of each argument, or `None` if the query has no parameters
+    fn into_spi_args(self) -> Option<Vec<(PgOid, Option<pg_sys::Datum>)>>;
+}
+
+impl SpiArgs for Option<Vec<(PgOid, Option<pg_sys::Datum>)>> {
+    fn into_spi_args(self) -> Option<Vec<(PgOid, Option<pg_sys::Datum>)>> {
+        self
+    }
+}
+
+impl SpiArgs for Vec<(PgOid, Option<pg_sys::Datum>)> {
+    fn into_spi_args(self) -> Option<Vec<(PgOid, Option<pg_sys::Datum>)>> {
+        Some(self)
+    }
+}
+
+impl<'a, 'b> SpiArgs for &'a [&'b dyn IntoDatumDyn] {
+    fn into_spi_args(self) -> Option<Vec<(PgOid, Option<pg_sys::Datum>)>> {
+        Some(
+            self.iter()
+                .map(|arg| (PgOid::from(arg.type_oid_dyn()), arg.to_datum_dyn()))
+                .collect(),
+        )
+    }
+}
+
+impl SpiArgs for () {
+    fn into_spi_args(self) -> Option<Vec<(PgOid, Option<pg_sys::Datum>)>> {
+        None
+    }
+}
+
+macro_rules! impl_spi_args_for_tuple {
+    ($($arg:ident),+) => {
+        impl<$($arg: IntoDatum),+> SpiArgs for ($($arg,)+) {
+            #[allow(non_snake_case)]
+            fn into_spi_args(self) -> Option<Vec<(PgOid, Option<pg_sys::Datum>)>> {
+                let ($($arg,)+) = self;
+                Some(vec![$((PgOid::from($arg::type_oid()), $arg.into_datum())),+])
+            }
+        }
+    };
+}
 
+impl_spi_args_for_tuple!(A);
+impl_spi_args_for_tuple!(A, B);
+impl_spi_args_for_tuple!(A, B, C);
+impl_spi_args_for_tuple!(A, B, C, D);
+impl_spi_args_for_tuple!(A, B, C, D, E);
+impl_spi_args_for_tuple!(A, B, C, D, E, F);
+impl_spi_args_for_tuple!(A, B, C, D, E, F, G);
+impl_spi_args_for_tuple!(A, B, C, D, E, F, G, H);
+impl_spi_args_for_tuple!(A, B, C, D, E, F, G, H, I);
+impl_spi_args_for_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_spi_args_for_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_spi_args_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// A query that a [`SpiClient`] can execute, either SQL text or a [`PreparedStatement`], with
+/// `Args` for its parameters
+pub trait Query<Args> {
+    /// Execute the query on the connection of `client`, returning at most `limit` rows
     fn execute(
-        query: &str,
+        self,
+        client: &SpiClient,
         read_only: bool,
         limit: Option<i64>,
-        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
-    ) -> SpiTupleTable {
+        arguments: Args,
+    ) -> std::result::Result<SpiTupleTable, SpiError>;
+
+    /// Open a cursor for the query on the connection of `client`
+    fn open_cursor(
+        self,
+        client: &SpiClient,
+        read_only: bool,
+        arguments: Args,
+        options: &SpiCursorOptions,
+    ) -> pg_sys::Portal;
+}
+
+impl<'a, A: SpiArgs> Query<A> for &'a str {
+    fn execute(
+        self,
+        _client: &SpiClient,
+        read_only: bool,
+        limit: Option<i64>,
+        arguments: A,
+    ) -> std::result::Result<SpiTupleTable, SpiError> {
         unsafe {
             pg_sys::SPI_tuptable = std::ptr::null_mut();
         }
 
-        let src = std::ffi::CString::new(query).expect("query contained a null byte");
-        let status_code = match args {
+        let src = std::ffi::CString::new(self).expect("query contained a null byte");
+        let status_code = match arguments.into_spi_args() {
             Some(args) => {
                 let nargs = args.len();
-                let mut argtypes = vec![];
-                let mut datums = vec![];
-                let mut nulls = vec![];
-
-                for (argtype, datum) in args {
-                    argtypes.push(argtype.value());
-
-                    match datum {
-                        Some(datum) => {
-                            // ' ' here means that the datum is not null
-                            datums.push(datum);
-                            nulls.push(' ' as std::os::raw::c_char);
-                        }
-
-                        None => {
-                            // 'n' here means that the datum is null
-                            datums.push(0);
-                            nulls.push('n' as std::os::raw::c_char);
-                        }
-                    }
-                }
+                let (mut argtypes, args): (Vec<_>, Vec<_>) = args
+                    .into_iter()
+                    .map(|(argtype, datum)| (argtype.value(), datum))
+                    .unzip();
+                let (mut datums, nulls) = args_to_datums(args);
 
                 unsafe {
                     pg_sys::SPI_execute_with_args(
@@ -360,21 +921,538 @@ impl SpiClient {
             None => unsafe { pg_sys::SPI_execute(src.as_ptr(), read_only, limit.unwrap_or(0)) },
         };
 
-        SpiTupleTable {
-            status_code: Spi::check_status(status_code),
-            table: unsafe { pg_sys::SPI_tuptable },
-            size: unsafe { pg_sys::SPI_processed as usize },
-            tupdesc: if unsafe { pg_sys::SPI_tuptable }.is_null() {
-                None
-            } else {
-                Some(unsafe { (*pg_sys::SPI_tuptable).tupdesc })
-            },
-            current: -1,
+        Ok(SpiClient::prepare_tuple_table(Spi::try_check_status(
+            status_code,
+        )?))
+    }
+
+    fn open_cursor(
+        self,
+        _client: &SpiClient,
+        read_only: bool,
+        arguments: A,
+        options: &SpiCursorOptions,
+    ) -> pg_sys::Portal {
+        let src = std::ffi::CString::new(self).expect("query contained a null byte");
+        let name = options.name();
+        let (mut argtypes, args): (Vec<_>, Vec<_>) = arguments
+            .into_spi_args()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(argtype, datum)| (argtype.value(), datum))
+            .unzip();
+        let (mut datums, nulls) = args_to_datums(args);
+        let cursor_options = if options.scroll {
+            pg_sys::CURSOR_OPT_SCROLL
+        } else {
+            pg_sys::CURSOR_OPT_NO_SCROLL
+        };
+
+        unsafe {
+            pg_sys::SPI_cursor_open_with_args(
+                name.as_ref().map_or(std::ptr::null(), |name| name.as_ptr()),
+                src.as_ptr(),
+                argtypes.len() as i32,
+                argtypes.as_mut_ptr(),
+                datums.as_mut_ptr(),
+                nulls.as_ptr(),
+                read_only,
+                cursor_options as i32,
+            )
+        }
+    }
+}
+
+impl<'a, A: SpiArgs> Query<A> for &'a String {
+    fn execute(
+        self,
+        client: &SpiClient,
+        read_only: bool,
+        limit: Option<i64>,
+        arguments: A,
+    ) -> std::result::Result<SpiTupleTable, SpiError> {
+        self.as_str().execute(client, read_only, limit, arguments)
+    }
+
+    fn open_cursor(
+        self,
+        client: &SpiClient,
+        read_only: bool,
+        arguments: A,
+        options: &SpiCursorOptions,
+    ) -> pg_sys::Portal {
+        self.as_str()
+            .open_cursor(client, read_only, arguments, options)
+    }
+}
+
+/// A statement that has been parsed and planned by [`SpiClient::prepare`].
+///
+/// Its arguments are given as one `Option<pg_sys::Datum>` per parameter, as the parameter types
+/// were declared when it was prepared.
+pub struct PreparedStatement<'a> {
+    plan: pg_sys::SPIPlanPtr,
+    _client: PhantomData<&'a SpiClient>,
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Keep the plan beyond this SPI connection, via `SPI_keepplan`, so it can be reused by later
+    /// connections without being parsed and planned again
+    pub fn keep(self) -> OwnedPreparedStatement {
+        let status_code = unsafe { pg_sys::SPI_keepplan(self.plan) };
+        if status_code < 0 {
+            Spi::check_status(status_code);
+        }
+        OwnedPreparedStatement(PreparedStatement {
+            plan: self.plan,
+            _client: PhantomData,
+        })
+    }
+
+    /// How many parameters the statement has
+    pub fn len(&self) -> usize {
+        unsafe { pg_sys::SPI_getargcount(self.plan) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> PreparedStatement<'a> {
+    /// Check that `args` has one value for each of the statement's parameters
+    fn check_arg_count(&self, args: &[Option<pg_sys::Datum>]) {
+        if args.len() != self.len() {
+            panic!(
+                "prepared statement expects {} arguments, but {} were provided",
+                self.len(),
+                args.len()
+            );
+        }
+    }
+
+    /// Check that typed `args` match the statement's parameters, returning their values
+    fn check_typed_args(
+        &self,
+        args: Option<Vec<(PgOid, Option<pg_sys::Datum>)>>,
+    ) -> Vec<Option<pg_sys::Datum>> {
+        let args = args.unwrap_or_default();
+        let (types, values): (Vec<_>, Vec<_>) = args.into_iter().unzip();
+        self.check_arg_count(&values);
+
+        for (i, actual) in types.into_iter().enumerate() {
+            let actual = actual.value();
+            let expected = unsafe { pg_sys::SPI_getargtypeid(self.plan, i as i32) };
+            if actual != expected && !unsafe { pg_sys::IsBinaryCoercible(actual, expected) } {
+                let type_name = |oid| unsafe {
+                    std::ffi::CStr::from_ptr(pg_sys::format_type_be(oid))
+                        .to_string_lossy()
+                        .into_owned()
+                };
+                panic!(
+                    "prepared statement expects argument ${} to be of type {}, but it is of type {}",
+                    i + 1,
+                    type_name(expected),
+                    type_name(actual)
+                );
+            }
+        }
+        values
+    }
+
+    fn execute_plan(
+        &self,
+        args: Vec<Option<pg_sys::Datum>>,
+        read_only: bool,
+        limit: Option<i64>,
+    ) -> std::result::Result<SpiTupleTable, SpiError> {
+        unsafe {
+            pg_sys::SPI_tuptable = std::ptr::null_mut();
+        }
+
+        let (mut datums, nulls) = args_to_datums(args);
+        let status_code = unsafe {
+            pg_sys::SPI_execute_plan(
+                self.plan,
+                datums.as_mut_ptr(),
+                nulls.as_ptr(),
+                read_only,
+                limit.unwrap_or(0),
+            )
+        };
+
+        Ok(SpiClient::prepare_tuple_table(Spi::try_check_status(
+            status_code,
+        )?))
+    }
+
+    fn open_plan_cursor(
+        &self,
+        args: Vec<Option<pg_sys::Datum>>,
+        read_only: bool,
+        options: &SpiCursorOptions,
+    ) -> pg_sys::Portal {
+        // a plan's cursor options are decided when it is prepared
+        if options.scroll {
+            panic!("a prepared statement cannot be opened as a scroll cursor");
+        }
+
+        let name = options.name();
+        let (mut datums, nulls) = args_to_datums(args);
+        unsafe {
+            pg_sys::SPI_cursor_open(
+                name.as_ref().map_or(std::ptr::null(), |name| name.as_ptr()),
+                self.plan,
+                datums.as_mut_ptr(),
+                nulls.as_ptr(),
+                read_only,
+            )
+        }
+    }
+}
+
+impl<'a, 'b> Query<Option<Vec<Option<pg_sys::Datum>>>> for &'b PreparedStatement<'a> {
+    fn execute(
+        self,
+        _client: &SpiClient,
+        read_only: bool,
+        limit: Option<i64>,
+        arguments: Option<Vec<Option<pg_sys::Datum>>>,
+    ) -> std::result::Result<SpiTupleTable, SpiError> {
+        let args = arguments.unwrap_or_default();
+        self.check_arg_count(&args);
+        self.execute_plan(args, read_only, limit)
+    }
+
+    fn open_cursor(
+        self,
+        _client: &SpiClient,
+        read_only: bool,
+        arguments: Option<Vec<Option<pg_sys::Datum>>>,
+        options: &SpiCursorOptions,
+    ) -> pg_sys::Portal {
+        let args = arguments.unwrap_or_default();
+        self.check_arg_count(&args);
+        self.open_plan_cursor(args, read_only, options)
+    }
+}
+
+/// [`SpiArgs`] that can also be given to a [`PreparedStatement`], whose types must match the
+/// types its parameters were prepared with.
+///
+/// This is every [`SpiArgs`] but `Option<Vec<(PgOid, Option<Datum>)>>`, so that `None` still
+/// means an empty `Option<Vec<Option<Datum>>>` for a prepared statement.
+pub trait PreparedArgs: SpiArgs {}
+
+impl PreparedArgs for Vec<(PgOid, Option<pg_sys::Datum>)> {}
+impl<'a, 'b> PreparedArgs for &'a [&'b dyn IntoDatumDyn] {}
+impl PreparedArgs for () {}
+
+macro_rules! impl_prepared_args_for_tuple {
+    ($($arg:ident),+) => {
+        impl<$($arg: IntoDatum),+> PreparedArgs for ($($arg,)+) {}
+    };
+}
+
+impl_prepared_args_for_tuple!(A);
+impl_prepared_args_for_tuple!(A, B);
+impl_prepared_args_for_tuple!(A, B, C);
+impl_prepared_args_for_tuple!(A, B, C, D);
+impl_prepared_args_for_tuple!(A, B, C, D, E);
+impl_prepared_args_for_tuple!(A, B, C, D, E, F);
+impl_prepared_args_for_tuple!(A, B, C, D, E, F, G);
+impl_prepared_args_for_tuple!(A, B, C, D, E, F, G, H);
+impl_prepared_args_for_tuple!(A, B, C, D, E, F, G, H, I);
+impl_prepared_args_for_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_prepared_args_for_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_prepared_args_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+impl<'a, 'b, A: PreparedArgs> Query<A> for &'b PreparedStatement<'a> {
+    fn execute(
+        self,
+        _client: &SpiClient,
+        read_only: bool,
+        limit: Option<i64>,
+        arguments: A,
+    ) -> std::result::Result<SpiTupleTable, SpiError> {
+        let args = self.check_typed_args(arguments.into_spi_args());
+        self.execute_plan(args, read_only, limit)
+    }
+
+    fn open_cursor(
+        self,
+        _client: &SpiClient,
+        read_only: bool,
+        arguments: A,
+        options: &SpiCursorOptions,
+    ) -> pg_sys::Portal {
+        let args = self.check_typed_args(arguments.into_spi_args());
+        self.open_plan_cursor(args, read_only, options)
+    }
+}
+
+/// A [`PreparedStatement`] whose plan was kept by [`PreparedStatement::keep`], so it is not tied to
+/// a single SPI connection.  The plan is freed when this is dropped.
+pub struct OwnedPreparedStatement(PreparedStatement<'static>);
+
+// Postgres backends are single-threaded, so this only allows an `OwnedPreparedStatement` to be
+// cached in a `static`
+unsafe impl Send for OwnedPreparedStatement {}
+unsafe impl Sync for OwnedPreparedStatement {}
+
+impl Deref for OwnedPreparedStatement {
+    type Target = PreparedStatement<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for OwnedPreparedStatement {
+    fn drop(&mut self) {
+        unsafe {
+            pg_sys::SPI_freeplan(self.0.plan);
+        }
+    }
+}
+
+impl<'a> Query<Option<Vec<Option<pg_sys::Datum>>>> for &'a OwnedPreparedStatement {
+    fn execute(
+        self,
+        client: &SpiClient,
+        read_only: bool,
+        limit: Option<i64>,
+        arguments: Option<Vec<Option<pg_sys::Datum>>>,
+    ) -> std::result::Result<SpiTupleTable, SpiError> {
+        (&self.0).execute(client, read_only, limit, arguments)
+    }
+
+    fn open_cursor(
+        self,
+        client: &SpiClient,
+        read_only: bool,
+        arguments: Option<Vec<Option<pg_sys::Datum>>>,
+        options: &SpiCursorOptions,
+    ) -> pg_sys::Portal {
+        (&self.0).open_cursor(client, read_only, arguments, options)
+    }
+}
+
+impl<'a, A: PreparedArgs> Query<A> for &'a OwnedPreparedStatement {
+    fn execute(
+        self,
+        client: &SpiClient,
+        read_only: bool,
+        limit: Option<i64>,
+        arguments: A,
+    ) -> std::result::Result<SpiTupleTable, SpiError> {
+        (&self.0).execute(client, read_only, limit, arguments)
+    }
+
+    fn open_cursor(
+        self,
+        client: &SpiClient,
+        read_only: bool,
+        arguments: A,
+        options: &SpiCursorOptions,
+    ) -> pg_sys::Portal {
+        (&self.0).open_cursor(client, read_only, arguments, options)
+    }
+}
+
+/// Options for [`SpiClient::open_cursor_with_options`]
+#[derive(Debug, Default, Clone)]
+pub struct SpiCursorOptions {
+    /// The name of the cursor, which must not already be in use.  If `None`, Postgres picks a
+    /// unique name
+    pub name: Option<String>,
+    /// Allow fetching backwards, with [`SpiCursor::scroll_fetch`] and [`SpiCursor::scroll_move`],
+    /// at some cost to performance
+    pub scroll: bool,
+}
+
+impl SpiCursorOptions {
+    fn name(&self) -> Option<std::ffi::CString> {
+        self.name.as_ref().map(|name| {
+            std::ffi::CString::new(name.as_str()).expect("cursor name contained a null byte")
+        })
+    }
+}
+
+/// The direction of [`SpiCursor::scroll_fetch`] and [`SpiCursor::scroll_move`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchDirection {
+    /// The next `count` rows
+    Forward,
+    /// The previous `count` rows
+    Backward,
+    /// The row at position `count`, where `1` is the first row and `-1` is the last
+    Absolute,
+    /// The row `count` rows away from the current position
+    Relative,
+}
+
+impl FetchDirection {
+    fn value(self) -> pg_sys::FetchDirection {
+        match self {
+            FetchDirection::Forward => pg_sys::FetchDirection_FETCH_FORWARD,
+            FetchDirection::Backward => pg_sys::FetchDirection_FETCH_BACKWARD,
+            FetchDirection::Absolute => pg_sys::FetchDirection_FETCH_ABSOLUTE,
+            FetchDirection::Relative => pg_sys::FetchDirection_FETCH_RELATIVE,
+        }
+    }
+}
+
+/// An open cursor (a Postgres "portal") created by [`SpiClient::open_cursor`] or found by
+/// [`SpiClient::find_cursor`].
+///
+/// Iterating a `SpiCursor` fetches its rows in batches of [`SpiCursor::with_batch_size`] rows.
+/// Each batch is freed when the next one is fetched, so values borrowed from a row (such as a
+/// `&str`) must not be used after moving on to the next batch.  Owned values (such as a `String`)
+/// are copied and are unaffected.
+///
+/// The cursor is closed when this is dropped, unless [`SpiCursor::detach_into_name`] is used.
+pub struct SpiCursor<'client> {
+    portal: NonNull<pg_sys::PortalData>,
+    batch_size: i64,
+    batch: Option<SpiTupleTable>,
+    generation: u64,
+    _client: PhantomData<&'client SpiClient>,
+}
+
+impl<'client> SpiCursor<'client> {
+    fn new(portal: pg_sys::Portal) -> Self {
+        SpiCursor {
+            portal: NonNull::new(portal).expect("SPI returned a NULL cursor"),
+            batch_size: DEFAULT_CURSOR_BATCH_SIZE,
+            batch: None,
+            generation: transaction_generation(),
+            _client: PhantomData,
+        }
+    }
+
+    /// Panic if the cursor's portal was closed by [`Spi::commit`] or [`Spi::rollback`]
+    fn check_generation(&self) {
+        if self.generation != transaction_generation() {
+            panic!("SpiCursor used after its transaction ended");
+        }
+    }
+
+    /// Fetch this many rows at a time when iterating
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        if batch_size < 1 {
+            panic!("cursor batch size must be at least 1");
+        }
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// The cursor's name, which can be used to find it again with [`SpiClient::find_cursor`]
+    pub fn name(&self) -> &str {
+        self.check_generation();
+        unsafe { std::ffi::CStr::from_ptr(self.portal.as_ref().name) }
+            .to_str()
+            .expect("cursor name is not valid UTF8")
+    }
+
+    /// Fetch up to the next `count` rows
+    pub fn fetch(&mut self, count: i64) -> SpiTupleTable {
+        self.check_generation();
+        unsafe {
+            pg_sys::SPI_tuptable = std::ptr::null_mut();
+            pg_sys::SPI_cursor_fetch(self.portal.as_ptr(), true, count as std::os::raw::c_long);
+        }
+        SpiClient::prepare_tuple_table(SpiOk::Fetch)
+    }
+
+    /// Fetch up to `count` rows in the given direction.  Fetching in any direction but
+    /// [`FetchDirection::Forward`] requires a cursor opened with [`SpiCursorOptions::scroll`]
+    pub fn scroll_fetch(&mut self, direction: FetchDirection, count: i64) -> SpiTupleTable {
+        self.check_generation();
+        unsafe {
+            pg_sys::SPI_tuptable = std::ptr::null_mut();
+            pg_sys::SPI_scroll_cursor_fetch(
+                self.portal.as_ptr(),
+                direction.value(),
+                count as std::os::raw::c_long,
+            );
+        }
+        SpiClient::prepare_tuple_table(SpiOk::Fetch)
+    }
+
+    /// Move the cursor like [`SpiCursor::scroll_fetch`], without fetching any rows
+    pub fn scroll_move(&mut self, direction: FetchDirection, count: i64) {
+        self.check_generation();
+        unsafe {
+            pg_sys::SPI_scroll_cursor_move(
+                self.portal.as_ptr(),
+                direction.value(),
+                count as std::os::raw::c_long,
+            );
+        }
+    }
+
+    /// Leave the cursor open, returning its name, so a later function call in the same
+    /// transaction can resume it with [`SpiClient::find_cursor`]
+    pub fn detach_into_name(self) -> String {
+        let name = self.name().to_string();
+        std::mem::forget(self);
+        name
+    }
+
+    /// Free the batch we've finished iterating, and fetch the next one
+    fn next_batch(&mut self) -> Option<&mut SpiTupleTable> {
+        if let Some(batch) = self.batch.take() {
+            batch.check_generation();
+            let exhausted = (batch.len() as i64) < self.batch_size;
+            unsafe {
+                pg_sys::SPI_freetuptable(batch.table);
+            }
+            if exhausted {
+                return None;
+            }
+        }
+
+        let batch = self.fetch(self.batch_size);
+        if batch.is_empty() {
+            None
+        } else {
+            self.batch = Some(batch);
+            self.batch.as_mut()
+        }
+    }
+}
+
+impl<'client> Iterator for SpiCursor<'client> {
+    type Item = SpiHeapTupleData;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(row) = self.batch.as_mut().and_then(|batch| batch.next()) {
+            return Some(row);
+        }
+        self.next_batch().and_then(|batch| batch.next())
+    }
+}
+
+impl<'client> Drop for SpiCursor<'client> {
+    fn drop(&mut self) {
+        // ending the transaction already closed the portal
+        if self.generation == transaction_generation() {
+            unsafe {
+                pg_sys::SPI_cursor_close(self.portal.as_ptr());
+            }
         }
     }
 }
 
 impl SpiTupleTable {
+    /// Panic if the table was freed by [`Spi::commit`] or [`Spi::rollback`]
+    fn check_generation(&self) {
+        if self.generation != transaction_generation() {
+            panic!("SpiTupleTable used after its transaction ended");
+        }
+    }
+
     /// `SpiTupleTable`s are positioned before the start, for iteration purposes.
     ///
     /// This method moves the position to the first row.  If there are no rows, this
@@ -413,10 +1491,11 @@ impl SpiTupleTable {
     }
 
     pub fn get_heap_tuple(&self) -> Option<SpiHeapTupleData> {
+        self.check_generation();
         if self.current < 0 {
             panic!("SpiTupleTable positioned before start")
         }
-        if self.current as u64 >= unsafe { pg_sys::SPI_processed } {
+        if self.current as usize >= self.size {
             None
         } else {
             match self.tupdesc {
@@ -433,10 +1512,11 @@ impl SpiTupleTable {
     }
 
     pub fn get_datum<T: FromDatum>(&self, ordinal: i32) -> Option<T> {
+        self.check_generation();
         if self.current < 0 {
             panic!("SpiTupleTable positioned before start")
         }
-        if self.current as u64 >= unsafe { pg_sys::SPI_processed } {
+        if self.current as usize >= self.size {
             None
         } else {
             match self.tupdesc {
@@ -459,14 +1539,105 @@ impl SpiTupleTable {
             }
         }
     }
+
+    /// Decode each row into a `T`, usually a struct with `#[derive(FromSpiRow)]`.
+    ///
+    /// The columns of `T` are found, and their types checked, once before the first row.  If that
+    /// fails, its error is the only item.
+    pub fn rows<T: FromSpiRow>(self) -> impl Iterator<Item = std::result::Result<T, SpiError>> {
+        self.check_generation();
+        let columns = match self.tupdesc {
+            // SPI's tuple descriptors aren't reference counted, so dropping this doesn't release it
+            Some(tupdesc) => T::columns(&unsafe { PgTupleDesc::from_pg(tupdesc) }),
+            // a utility statement, with no rows to decode
+            None => Ok(vec![]),
+        };
+        let (columns, error) = match columns {
+            Ok(columns) => (columns, None),
+            Err(e) => (vec![], Some(e)),
+        };
+        let rows = if error.is_none() { Some(self) } else { None };
+
+        error.map(Err).into_iter().chain(
+            rows.into_iter()
+                .flatten()
+                .map(move |row| T::from_spi_row(&row, &columns)),
+        )
+    }
+}
+
+/// A type that can be decoded from the rows of a [`SpiTupleTable`] with [`SpiTupleTable::rows`].
+///
+/// This is usually implemented with `#[derive(FromSpiRow)]`.
+pub trait FromSpiRow: Sized {
+    /// Find the ordinal of the column each field is read from, in a result described by
+    /// `tupdesc`, checking that the column's type can be read as the field's type
+    fn columns(tupdesc: &PgTupleDesc) -> std::result::Result<Vec<usize>, SpiError>;
+
+    /// Decode a row, given the ordinals returned by [`FromSpiRow::columns`]
+    fn from_spi_row(
+        row: &SpiHeapTupleData,
+        columns: &[usize],
+    ) -> std::result::Result<Self, SpiError>;
+}
+
+/// Find the column named `name` in `tupdesc`, falling back to the column at `position` (1-based),
+/// if one is given, when there's no such name, and check that it can be read as a `T`.
+///
+/// Used by `#[derive(FromSpiRow)]`.
+#[doc(hidden)]
+pub fn find_spi_column<T: IntoDatum>(
+    tupdesc: &PgTupleDesc,
+    name: Option<&str>,
+    position: Option<usize>,
+) -> std::result::Result<usize, SpiError> {
+    use crate::pg_sys::AsPgCStr;
+
+    let natts = tupdesc.len();
+    let tupdesc = tupdesc.as_ptr();
+    unsafe {
+        let ordinal = match name {
+            // system columns have negative numbers, and aren't in the tuples SPI returns
+            Some(name) => match pg_sys::SPI_fnumber(tupdesc, name.as_pg_cstr()) {
+                fnumber if fnumber > 0 => Some(fnumber as usize),
+                _ => position,
+            },
+            None => position,
+        }
+        .ok_or(SpiError::Noattribute)?;
+        if ordinal < 1 || ordinal > natts {
+            return Err(SpiError::Noattribute);
+        }
+
+        let actual = pg_sys::SPI_gettypeid(tupdesc, ordinal as i32);
+        let expected = T::type_oid();
+        if actual != expected && !pg_sys::IsBinaryCoercible(actual, expected) {
+            let type_name = |oid| {
+                std::ffi::CStr::from_ptr(pg_sys::format_type_be(oid))
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            return Err(SpiError::TypeMismatch {
+                column: name
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|| ordinal.to_string()),
+                expected_type: type_name(expected),
+                actual_type: type_name(actual),
+            });
+        }
+
+        Ok(ordinal)
+    }
 }
 
 impl SpiHeapTupleData {
     /// Create a new `SpiHeapTupleData` from its constituent parts
     pub unsafe fn new(tupdesc: pg_sys::TupleDesc, htup: *mut pg_sys::HeapTupleData) -> Self {
+        let generation = transaction_generation();
         let mut data = SpiHeapTupleData {
             tupdesc,
             entries: HashMap::default(),
+            generation,
         };
 
         for i in 1..=tupdesc.as_ref().unwrap().natts {
@@ -478,12 +1649,20 @@ impl SpiHeapTupleData {
                 .or_insert_with(|| SpiHeapTupleDataEntry {
                     datum: if is_null { None } else { Some(datum) },
                     type_oid: pg_sys::SPI_gettypeid(tupdesc, i),
+                    generation,
                 });
         }
 
         data
     }
 
+    /// Panic if the tuple was freed by [`Spi::commit`] or [`Spi::rollback`]
+    fn check_generation(&self) {
+        if self.generation != transaction_generation() {
+            panic!("SpiHeapTupleData used after its transaction ended");
+        }
+    }
+
     /// Get a typed Datum value from this HeapTuple by its ordinal position.  
     ///
     /// The ordinal position is 1-based
@@ -515,6 +1694,7 @@ impl SpiHeapTupleData {
     /// If the specified name does not exist a `Err(SpiError::Noattribute)` is returned
     pub fn by_name(&self, name: &str) -> std::result::Result<&SpiHeapTupleDataEntry, SpiError> {
         use crate::pg_sys::AsPgCStr;
+        self.check_generation();
         unsafe {
             let fnumber = pg_sys::SPI_fnumber(self.tupdesc, name.as_pg_cstr());
             if fnumber == pg_sys::SPI_ERROR_NOATTRIBUTE {
@@ -548,6 +1728,7 @@ impl SpiHeapTupleData {
         name: &str,
     ) -> std::result::Result<&mut SpiHeapTupleDataEntry, SpiError> {
         use crate::pg_sys::AsPgCStr;
+        self.check_generation();
         unsafe {
             let fnumber = pg_sys::SPI_fnumber(self.tupdesc, name.as_pg_cstr());
             if fnumber == pg_sys::SPI_ERROR_NOATTRIBUTE {
@@ -566,6 +1747,7 @@ impl SpiHeapTupleData {
         ordinal: usize,
         datum: T,
     ) -> std::result::Result<(), SpiError> {
+        self.check_generation();
         unsafe {
             if ordinal < 1 || ordinal > self.tupdesc.as_ref().unwrap().natts as usize {
                 Err(SpiError::Noattribute)
@@ -575,6 +1757,7 @@ impl SpiHeapTupleData {
                     SpiHeapTupleDataEntry {
                         datum: datum.into_datum(),
                         type_oid: T::type_oid(),
+                        generation: self.generation,
                     },
                 );
                 Ok(())
@@ -591,6 +1774,7 @@ impl SpiHeapTupleData {
         datum: T,
     ) -> std::result::Result<(), SpiError> {
         use crate::pg_sys::AsPgCStr;
+        self.check_generation();
         unsafe {
             let fnumber = pg_sys::SPI_fnumber(self.tupdesc, name.as_pg_cstr());
             if fnumber == pg_sys::SPI_ERROR_NOATTRIBUTE {
@@ -607,12 +1791,17 @@ impl<Datum: IntoDatum + FromDatum> From<Datum> for SpiHeapTupleDataEntry {
         SpiHeapTupleDataEntry {
             datum: datum.into_datum(),
             type_oid: Datum::type_oid(),
+            generation: transaction_generation(),
         }
     }
 }
 
 impl SpiHeapTupleDataEntry {
     pub fn value<T: FromDatum>(&self) -> Option<T> {
+        if self.generation != transaction_generation() {
+            panic!("SpiHeapTupleData used after its transaction ended");
+        }
+
         match self.datum.as_ref() {
             Some(datum) => unsafe { T::from_datum(*datum, false, self.type_oid) },
             None => None,
@@ -0,0 +1,155 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Subtransactions, which can fail without aborting the transaction they're in
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//!
+//! // load what we can, skipping the rows that fail
+//! fn load(rows: Vec<(i32, String)>) -> usize {
+//!     let mut loaded = 0;
+//!     for (id, name) in rows {
+//!         let result: Result<(), Box<CaughtError>> = subxact(|| {
+//!             Spi::execute(|mut client| {
+//!                 client.update(
+//!                     "INSERT INTO people (id, name) VALUES ($1, $2)",
+//!                     None,
+//!                     (id, name),
+//!                 );
+//!             });
+//!             Ok(())
+//!         });
+//!         if result.is_ok() {
+//!             loaded += 1;
+//!         }
+//!     }
+//!     loaded
+//! }
+//! ```
+
+use crate::{pg_sys, pg_try, CaughtError};
+
+/// Run `f` in a subtransaction, which is committed if `f` returns `Ok`, and rolled back if it
+/// returns `Err` or raises an error.
+///
+/// A Postgres ERROR, or Rust panic, raised by `f` is returned as an `Err` converted from the
+/// boxed [`CaughtError`], and the transaction `subxact()` was called in can carry on.  What `f`
+/// allocates is allocated in the caller's memory context, so it can be returned.
+pub fn subxact<F, T, E>(f: F) -> Result<T, E>
+where
+    F: FnOnce() -> Result<T, E> + std::panic::UnwindSafe + std::panic::RefUnwindSafe,
+    E: From<Box<CaughtError>>,
+{
+    let subxact = PgSubTransaction::begin();
+    let result = pg_try(f);
+
+    // an ERROR can leave us in any memory context, and the caught error must be copied out of
+    // the ErrorContext into the caller's
+    unsafe {
+        pg_sys::CurrentMemoryContext = subxact.oldcontext;
+    }
+
+    match unsafe { result.catch_error() } {
+        Ok(Ok(value)) => {
+            subxact.commit();
+            Ok(value)
+        }
+        Ok(Err(e)) => {
+            subxact.rollback();
+            Err(e)
+        }
+        Err(caught) => {
+            subxact.rollback();
+            Err(caught.into())
+        }
+    }
+}
+
+/// A subtransaction, started by [`PgSubTransaction::begin`], that's rolled back if it's dropped
+/// without being committed.
+///
+/// Subtransactions must be committed or rolled back in the reverse order they were started in,
+/// and finishing one that isn't the current subtransaction panics.  [`subxact()`] takes care of
+/// that, and of catching errors.
+#[must_use = "a subtransaction is rolled back when it's dropped"]
+pub struct PgSubTransaction {
+    id: pg_sys::SubTransactionId,
+    oldcontext: pg_sys::MemoryContext,
+    oldowner: pg_sys::ResourceOwner,
+    finished: bool,
+}
+
+impl PgSubTransaction {
+    /// Start a subtransaction.  The current memory context is unchanged.
+    pub fn begin() -> Self {
+        unsafe {
+            let oldcontext = pg_sys::CurrentMemoryContext;
+            let oldowner = pg_sys::CurrentResourceOwner;
+
+            pg_sys::BeginInternalSubTransaction(std::ptr::null_mut());
+            pg_sys::CurrentMemoryContext = oldcontext;
+
+            PgSubTransaction {
+                id: pg_sys::GetCurrentSubTransactionId(),
+                oldcontext,
+                oldowner,
+                finished: false,
+            }
+        }
+    }
+
+    /// Commit the subtransaction, making its changes part of the transaction it's in
+    pub fn commit(mut self) {
+        self.check_current();
+        unsafe {
+            pg_sys::ReleaseCurrentSubTransaction();
+            self.restore();
+        }
+    }
+
+    /// Roll back the subtransaction, undoing its changes
+    pub fn rollback(mut self) {
+        self.check_current();
+        unsafe { self.rollback_and_restore() }
+    }
+
+    /// Is this the innermost subtransaction, the only one Postgres can finish?
+    fn is_current(&self) -> bool {
+        self.id == unsafe { pg_sys::GetCurrentSubTransactionId() }
+    }
+
+    fn check_current(&mut self) {
+        if !self.is_current() {
+            // don't let `Drop` try to finish it again
+            self.finished = true;
+            panic!("subtransactions must be finished in the reverse order they were started in");
+        }
+    }
+
+    unsafe fn rollback_and_restore(&mut self) {
+        pg_sys::CurrentMemoryContext = self.oldcontext;
+        pg_sys::RollbackAndReleaseCurrentSubTransaction();
+        self.restore();
+    }
+
+    unsafe fn restore(&mut self) {
+        pg_sys::CurrentMemoryContext = self.oldcontext;
+        pg_sys::CurrentResourceOwner = self.oldowner;
+        self.finished = true;
+    }
+}
+
+impl Drop for PgSubTransaction {
+    fn drop(&mut self) {
+        if !self.finished {
+            // an error unwinding past an out-of-order subtransaction aborts it with the rest of
+            // the transaction, so only panic if we aren't already
+            if !self.is_current() && std::thread::panicking() {
+                return;
+            }
+            self.check_current();
+            unsafe { self.rollback_and_restore() }
+        }
+    }
+}
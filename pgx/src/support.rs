@@ -0,0 +1,286 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Planner [support functions](https://www.postgresql.org/docs/current/xfunc-optimization.html),
+//! which tell the planner more about a function than its `COST` and `ROWS` can.
+//!
+//! A support function takes a [`SupportRequest`] and returns a [`SupportResponse`], and is attached
+//! to the function it supports with `#[pg_extern(support = ...)]`:
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//!
+//! #[pg_extern]
+//! fn count_to_support(request: SupportRequest) -> SupportResponse {
+//!     match request {
+//!         // when `n` is a constant, we know exactly how many rows we'll return
+//!         SupportRequest::Rows(rows) => match rows.const_arg::<i32>(0) {
+//!             Some(Some(n)) => rows.set_rows(n as f64),
+//!             _ => SupportResponse::not_handled(),
+//!         },
+//!         _ => SupportResponse::not_handled(),
+//!     }
+//! }
+//!
+//! #[pg_extern(support = count_to_support)]
+//! fn count_to(n: i32) -> impl Iterator<Item = i32> {
+//!     1..=n
+//! }
+//! ```
+//!
+//! Support functions require Postgres 12 or later.
+
+use crate::{is_a, pg_sys, FromDatum, IntoDatum, PgBox, PgList};
+use std::ptr::NonNull;
+
+/// A request from the planner to a support function, wrapping Postgres' `SupportRequest*` nodes
+pub enum SupportRequest {
+    /// Replace a call to the function with a simpler expression
+    Simplify(SupportRequestSimplify),
+    /// Estimate the selectivity of a call to a boolean function
+    Selectivity(SupportRequestSelectivity),
+    /// Estimate the cost of a call to the function
+    Cost(SupportRequestCost),
+    /// Estimate how many rows a call to a set-returning function returns
+    Rows(SupportRequestRows),
+    /// Build index conditions from a call to a boolean function
+    IndexCondition(SupportRequestIndexCondition),
+    /// A request pgx doesn't know about, which should not be handled
+    Other(NonNull<pg_sys::Node>),
+}
+
+impl FromDatum for SupportRequest {
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<Self> {
+        if is_null || datum == 0 {
+            return None;
+        }
+
+        let node = datum as *mut pg_sys::Node;
+        let request = match (*node).type_ {
+            pg_sys::NodeTag_T_SupportRequestSimplify => {
+                SupportRequest::Simplify(SupportRequestSimplify(NonNull::new_unchecked(node as _)))
+            }
+            pg_sys::NodeTag_T_SupportRequestSelectivity => SupportRequest::Selectivity(
+                SupportRequestSelectivity(NonNull::new_unchecked(node as _)),
+            ),
+            pg_sys::NodeTag_T_SupportRequestCost => {
+                SupportRequest::Cost(SupportRequestCost(NonNull::new_unchecked(node as _)))
+            }
+            pg_sys::NodeTag_T_SupportRequestRows => {
+                SupportRequest::Rows(SupportRequestRows(NonNull::new_unchecked(node as _)))
+            }
+            pg_sys::NodeTag_T_SupportRequestIndexCondition => SupportRequest::IndexCondition(
+                SupportRequestIndexCondition(NonNull::new_unchecked(node as _)),
+            ),
+            _ => SupportRequest::Other(NonNull::new_unchecked(node)),
+        };
+        Some(request)
+    }
+}
+
+/// What a support function returns to the planner
+pub struct SupportResponse(*mut pg_sys::Node);
+
+impl SupportResponse {
+    /// The support function doesn't handle the request, so the planner uses its default behavior
+    pub fn not_handled() -> Self {
+        SupportResponse(std::ptr::null_mut())
+    }
+}
+
+impl IntoDatum for SupportResponse {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        // the planner expects a NULL pointer rather than an SQL NULL
+        Some(self.0 as pg_sys::Datum)
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        pg_sys::INTERNALOID
+    }
+}
+
+/// See [`SupportRequest::Simplify`]
+pub struct SupportRequestSimplify(NonNull<pg_sys::SupportRequestSimplify>);
+
+impl SupportRequestSimplify {
+    /// The call being simplified
+    pub fn fcall(&self) -> PgBox<pg_sys::FuncExpr> {
+        unsafe { PgBox::from_pg(self.0.as_ref().fcall) }
+    }
+
+    /// The value of the zero-based argument `argno`, if it is a constant
+    pub fn const_arg<T: FromDatum>(&self, argno: usize) -> Option<Option<T>> {
+        unsafe { const_arg(self.fcall().args, argno) }
+    }
+
+    /// Replace the call with `expr`, an expression node that's been allocated by Postgres
+    pub fn replace_with(self, expr: PgBox<pg_sys::Node>) -> SupportResponse {
+        SupportResponse(expr.into_pg())
+    }
+}
+
+/// See [`SupportRequest::Selectivity`]
+pub struct SupportRequestSelectivity(NonNull<pg_sys::SupportRequestSelectivity>);
+
+impl SupportRequestSelectivity {
+    /// The oid of the function being estimated
+    pub fn funcid(&self) -> pg_sys::Oid {
+        unsafe { self.0.as_ref().funcid }
+    }
+
+    /// The arguments of the call
+    pub fn args(&self) -> PgList<pg_sys::Node> {
+        unsafe { PgList::from_pg(self.0.as_ref().args) }
+    }
+
+    /// The value of the zero-based argument `argno`, if it is a constant
+    pub fn const_arg<T: FromDatum>(&self, argno: usize) -> Option<Option<T>> {
+        unsafe { const_arg(self.0.as_ref().args, argno) }
+    }
+
+    /// Is this a join clause, rather than a restriction clause?
+    pub fn is_join(&self) -> bool {
+        unsafe { self.0.as_ref().is_join }
+    }
+
+    /// Answer with the fraction of rows, between `0.0` and `1.0`, the call returns `true` for
+    pub fn set_selectivity(mut self, selectivity: f64) -> SupportResponse {
+        unsafe {
+            self.0.as_mut().selectivity = selectivity;
+        }
+        SupportResponse(self.0.as_ptr() as *mut pg_sys::Node)
+    }
+}
+
+/// See [`SupportRequest::Cost`]
+pub struct SupportRequestCost(NonNull<pg_sys::SupportRequestCost>);
+
+impl SupportRequestCost {
+    /// The oid of the function being estimated
+    pub fn funcid(&self) -> pg_sys::Oid {
+        unsafe { self.0.as_ref().funcid }
+    }
+
+    /// The expression calling the function, which may be NULL
+    pub fn node(&self) -> *mut pg_sys::Node {
+        unsafe { self.0.as_ref().node }
+    }
+
+    /// The value of the zero-based argument `argno`, if it is a constant
+    pub fn const_arg<T: FromDatum>(&self, argno: usize) -> Option<Option<T>> {
+        unsafe { node_const_arg(self.node(), argno) }
+    }
+
+    /// Answer with the one-time startup cost, and the cost of each call, in units of
+    /// `cpu_operator_cost`
+    pub fn set_cost(mut self, startup: f64, per_tuple: f64) -> SupportResponse {
+        unsafe {
+            let request = self.0.as_mut();
+            request.startup = startup;
+            request.per_tuple = per_tuple;
+        }
+        SupportResponse(self.0.as_ptr() as *mut pg_sys::Node)
+    }
+}
+
+/// See [`SupportRequest::Rows`]
+pub struct SupportRequestRows(NonNull<pg_sys::SupportRequestRows>);
+
+impl SupportRequestRows {
+    /// The oid of the function being estimated
+    pub fn funcid(&self) -> pg_sys::Oid {
+        unsafe { self.0.as_ref().funcid }
+    }
+
+    /// The expression calling the function, which may be NULL
+    pub fn node(&self) -> *mut pg_sys::Node {
+        unsafe { self.0.as_ref().node }
+    }
+
+    /// The value of the zero-based argument `argno`, if it is a constant
+    pub fn const_arg<T: FromDatum>(&self, argno: usize) -> Option<Option<T>> {
+        unsafe { node_const_arg(self.node(), argno) }
+    }
+
+    /// Answer with how many rows the call returns
+    pub fn set_rows(mut self, rows: f64) -> SupportResponse {
+        unsafe {
+            self.0.as_mut().rows = rows;
+        }
+        SupportResponse(self.0.as_ptr() as *mut pg_sys::Node)
+    }
+}
+
+/// See [`SupportRequest::IndexCondition`]
+pub struct SupportRequestIndexCondition(NonNull<pg_sys::SupportRequestIndexCondition>);
+
+impl SupportRequestIndexCondition {
+    /// The oid of the function being estimated
+    pub fn funcid(&self) -> pg_sys::Oid {
+        unsafe { self.0.as_ref().funcid }
+    }
+
+    /// The call, or operator expression, the index conditions are derived from
+    pub fn node(&self) -> *mut pg_sys::Node {
+        unsafe { self.0.as_ref().node }
+    }
+
+    /// The zero-based argument of the call that matches the index column
+    pub fn indexarg(&self) -> usize {
+        unsafe { self.0.as_ref().indexarg as usize }
+    }
+
+    /// The zero-based index column
+    pub fn indexcol(&self) -> usize {
+        unsafe { self.0.as_ref().indexcol as usize }
+    }
+
+    /// The operator family of the index column
+    pub fn opfamily(&self) -> pg_sys::Oid {
+        unsafe { self.0.as_ref().opfamily }
+    }
+
+    /// The value of the zero-based argument `argno`, if it is a constant
+    pub fn const_arg<T: FromDatum>(&self, argno: usize) -> Option<Option<T>> {
+        unsafe { node_const_arg(self.node(), argno) }
+    }
+
+    /// Answer with index conditions that are implied by the call.  If they match more rows than
+    /// the call, they're `lossy`, and the call is rechecked for each row.
+    pub fn set_index_conditions(
+        mut self,
+        conditions: PgList<pg_sys::Expr>,
+        lossy: bool,
+    ) -> SupportResponse {
+        unsafe {
+            self.0.as_mut().lossy = lossy;
+        }
+        SupportResponse(conditions.into_pg() as *mut pg_sys::Node)
+    }
+}
+
+/// The constant value of argument `argno` of a `FuncExpr` or `OpExpr`
+unsafe fn node_const_arg<T: FromDatum>(node: *mut pg_sys::Node, argno: usize) -> Option<Option<T>> {
+    if is_a(node, pg_sys::NodeTag_T_FuncExpr) {
+        const_arg((*(node as *mut pg_sys::FuncExpr)).args, argno)
+    } else if is_a(node, pg_sys::NodeTag_T_OpExpr) {
+        const_arg((*(node as *mut pg_sys::OpExpr)).args, argno)
+    } else {
+        None
+    }
+}
+
+unsafe fn const_arg<T: FromDatum>(args: *mut pg_sys::List, argno: usize) -> Option<Option<T>> {
+    let args = PgList::<pg_sys::Node>::from_pg(args);
+    match args.get_ptr(argno) {
+        Some(arg) if is_a(arg, pg_sys::NodeTag_T_Const) => {
+            let arg = &*(arg as *mut pg_sys::Const);
+            Some(T::from_datum(
+                arg.constvalue,
+                arg.constisnull,
+                arg.consttype,
+            ))
+        }
+        _ => None,
+    }
+}
@@ -2,8 +2,39 @@
 // governed by the MIT license that can be found in the LICENSE file.
 
 //! Helper functions for working with custom Rust trigger functions
+//!
+//! A `#[pg_extern]` function that takes a [`PgTrigger`] is a trigger function.  It's declared as
+//! `RETURNS trigger`, and the [`PgTrigger`] describes the event that fired it:
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//!
+//! /// Lowercases the `email` column of each inserted or updated row
+//! #[pg_extern]
+//! fn lowercase_email(
+//!     trigger: &PgTrigger,
+//! ) -> Result<Option<PgHeapTuple>, PgHeapTupleError> {
+//!     let mut new = trigger.new_row().expect("not fired by a row-level INSERT or UPDATE");
+//!     if let Some(email) = new.get_by_name::<String>("email")? {
+//!         new.set_by_name("email", email.to_lowercase())?;
+//!     }
+//!     Ok(Some(new))
+//! }
+//! ```
+//!
+//! The function's return value is converted with [`IntoTriggerResult`].  A `BEFORE ... FOR EACH
+//! ROW` trigger returns the row to use in place of [`PgTrigger::new_row`], `None` to skip the
+//! operation for this row, or `()` to carry on with the row unchanged.  The return value of any
+//! other trigger is ignored.
+//!
+//! An `AFTER` trigger declared with `REFERENCING NEW TABLE AS ...` or `OLD TABLE AS ...` can read
+//! the rows its statement changed from [`PgTrigger::new_table`] and [`PgTrigger::old_table`].
+//! The trigger itself can be declared next to its function, with `#[pg_trigger]`.
 
-use crate::{is_a, pg_sys};
+use crate::{is_a, pg_sys, FromDatum, PgHeapTuple, PgRelation};
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::ptr::NonNull;
 
 #[inline]
 pub unsafe fn called_as_trigger(fcinfo: pg_sys::FunctionCallInfo) -> bool {
@@ -55,3 +86,403 @@ pub fn trigger_fired_after(event: u32) -> bool {
 pub fn trigger_fired_instead(event: u32) -> bool {
     event & pg_sys::TRIGGER_EVENT_TIMINGMASK == pg_sys::TRIGGER_EVENT_INSTEAD
 }
+
+/// When a trigger fires, relative to the event it fires for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgTriggerWhen {
+    Before,
+    After,
+    InsteadOf,
+}
+
+/// The operation that fired a trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgTriggerOperation {
+    Insert,
+    Update,
+    Delete,
+    Truncate,
+}
+
+/// Whether a trigger fires once for each row, or once for each statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgTriggerLevel {
+    Row,
+    Statement,
+}
+
+/// The context of a call to a trigger function, wrapping Postgres' `TriggerData`
+pub struct PgTrigger {
+    trigdata: NonNull<pg_sys::TriggerData>,
+    // the tuplestore read pointers of dropped `PgTransitionTable`s, for the next one to reuse
+    new_table_readptr: Cell<Option<i32>>,
+    old_table_readptr: Cell<Option<i32>>,
+}
+
+impl PgTrigger {
+    /// The trigger `fcinfo`'s function was called for
+    ///
+    /// ## Safety
+    ///
+    /// `fcinfo` must be a valid `FunctionCallInfo`.  Panics if the function wasn't called by the
+    /// trigger manager.
+    pub unsafe fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        if !called_as_trigger(fcinfo) {
+            panic!("not called by trigger manager");
+        }
+
+        PgTrigger {
+            trigdata: NonNull::new_unchecked((*fcinfo).context as *mut pg_sys::TriggerData),
+            new_table_readptr: Cell::new(None),
+            old_table_readptr: Cell::new(None),
+        }
+    }
+
+    /// The wrapped `TriggerData`
+    pub fn as_ptr(&self) -> *mut pg_sys::TriggerData {
+        self.trigdata.as_ptr()
+    }
+
+    /// Did the trigger fire before, after, or instead of the operation?
+    pub fn when(&self) -> PgTriggerWhen {
+        let event = self.event();
+        if trigger_fired_before(event) {
+            PgTriggerWhen::Before
+        } else if trigger_fired_after(event) {
+            PgTriggerWhen::After
+        } else {
+            PgTriggerWhen::InsteadOf
+        }
+    }
+
+    /// The operation that fired the trigger
+    pub fn op(&self) -> PgTriggerOperation {
+        let event = self.event();
+        if trigger_fired_by_insert(event) {
+            PgTriggerOperation::Insert
+        } else if trigger_fired_by_update(event) {
+            PgTriggerOperation::Update
+        } else if trigger_fired_by_delete(event) {
+            PgTriggerOperation::Delete
+        } else {
+            PgTriggerOperation::Truncate
+        }
+    }
+
+    /// Did the trigger fire for a row, or for a statement?
+    pub fn level(&self) -> PgTriggerLevel {
+        if trigger_fired_for_row(self.event()) {
+            PgTriggerLevel::Row
+        } else {
+            PgTriggerLevel::Statement
+        }
+    }
+
+    /// The name of the trigger, from `CREATE TRIGGER`
+    pub fn name(&self) -> &str {
+        unsafe { CStr::from_ptr(self.trigger().tgname) }
+            .to_str()
+            .expect("trigger name is not valid UTF8")
+    }
+
+    /// The arguments given to the trigger function in `CREATE TRIGGER`
+    pub fn args(&self) -> Vec<&str> {
+        let trigger = self.trigger();
+        (0..trigger.tgnargs as usize)
+            .map(|i| {
+                unsafe { CStr::from_ptr(*trigger.tgargs.add(i)) }
+                    .to_str()
+                    .expect("trigger argument is not valid UTF8")
+            })
+            .collect()
+    }
+
+    /// The table, or view, the trigger is on
+    pub fn relation(&self) -> PgRelation {
+        unsafe { PgRelation::from_pg(self.trigdata.as_ref().tg_relation) }
+    }
+
+    /// The row being inserted, or the new version of the row being updated, for a row-level
+    /// `INSERT` or `UPDATE` trigger
+    pub fn new_row(&self) -> Option<PgHeapTuple> {
+        let trigdata = unsafe { self.trigdata.as_ref() };
+        match (self.level(), self.op()) {
+            (PgTriggerLevel::Row, PgTriggerOperation::Insert) => self.tuple(trigdata.tg_trigtuple),
+            (PgTriggerLevel::Row, PgTriggerOperation::Update) => self.tuple(trigdata.tg_newtuple),
+            _ => None,
+        }
+    }
+
+    /// The row being deleted, or the old version of the row being updated, for a row-level
+    /// `UPDATE` or `DELETE` trigger
+    pub fn old_row(&self) -> Option<PgHeapTuple> {
+        let trigdata = unsafe { self.trigdata.as_ref() };
+        match (self.level(), self.op()) {
+            (PgTriggerLevel::Row, PgTriggerOperation::Update)
+            | (PgTriggerLevel::Row, PgTriggerOperation::Delete) => {
+                self.tuple(trigdata.tg_trigtuple)
+            }
+            _ => None,
+        }
+    }
+
+    /// The rows inserted, or the new versions of the rows updated, by the statement that fired the
+    /// trigger, if it's an `AFTER` trigger declared with `REFERENCING NEW TABLE AS ...`
+    ///
+    /// Each table iterates from its first row.  Postgres can't free a tuplestore's read pointers,
+    /// so each table that's alive at the same time as another for the same transition table costs
+    /// one, for the rest of the trigger call.
+    pub fn new_table(&self) -> Option<PgTransitionTable<'_>> {
+        unsafe {
+            self.transition_table(self.trigdata.as_ref().tg_newtable, &self.new_table_readptr)
+        }
+    }
+
+    /// The rows deleted, or the old versions of the rows updated, by the statement that fired the
+    /// trigger, if it's an `AFTER` trigger declared with `REFERENCING OLD TABLE AS ...`
+    ///
+    /// Like [`PgTrigger::new_table`], each table iterates from its first row.
+    pub fn old_table(&self) -> Option<PgTransitionTable<'_>> {
+        unsafe {
+            self.transition_table(self.trigdata.as_ref().tg_oldtable, &self.old_table_readptr)
+        }
+    }
+
+    unsafe fn transition_table<'a>(
+        &'a self,
+        tuplestore: *mut pg_sys::Tuplestorestate,
+        free_readptr: &'a Cell<Option<i32>>,
+    ) -> Option<PgTransitionTable<'a>> {
+        let tuplestore = NonNull::new(tuplestore)?;
+        let tupdesc = self.trigdata.as_ref().tg_relation.as_ref().unwrap().rd_att;
+        Some(PgTransitionTable::new(tuplestore, tupdesc, free_readptr))
+    }
+
+    fn event(&self) -> u32 {
+        unsafe { self.trigdata.as_ref().tg_event }
+    }
+
+    fn trigger(&self) -> &pg_sys::Trigger {
+        unsafe {
+            self.trigdata
+                .as_ref()
+                .tg_trigger
+                .as_ref()
+                .expect("TriggerData has no trigger")
+        }
+    }
+
+    fn tuple(&self, tuple: pg_sys::HeapTuple) -> Option<PgHeapTuple> {
+        if tuple.is_null() {
+            return None;
+        }
+        unsafe {
+            let tupdesc = self.trigdata.as_ref().tg_relation.as_ref().unwrap().rd_att;
+            Some(PgHeapTuple::from_heap_tuple(tuple, tupdesc))
+        }
+    }
+}
+
+/// A transition table of the statement that fired a trigger, from [`PgTrigger::new_table`] or
+/// [`PgTrigger::old_table`], which iterates over its rows.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[derive(PostgresComposite)]
+/// struct Order {
+///     id: i64,
+///     total: Option<i32>,
+/// }
+///
+/// #[pg_extern]
+/// fn log_new_orders(trigger: &PgTrigger) {
+///     for order in trigger.new_table().unwrap().rows::<Order>() {
+///         notice!("new order {} totalling {:?}", order.id, order.total);
+///     }
+/// }
+/// ```
+pub struct PgTransitionTable<'a> {
+    tuplestore: NonNull<pg_sys::Tuplestorestate>,
+    readptr: i32,
+    slot: *mut pg_sys::TupleTableSlot,
+    tupdesc: pg_sys::TupleDesc,
+    free_readptr: &'a Cell<Option<i32>>,
+}
+
+impl<'a> PgTransitionTable<'a> {
+    unsafe fn new(
+        tuplestore: NonNull<pg_sys::Tuplestorestate>,
+        tupdesc: pg_sys::TupleDesc,
+        free_readptr: &'a Cell<Option<i32>>,
+    ) -> Self {
+        // our own read pointer, so we start at the first row no matter who else has read the table
+        let readptr = free_readptr.take().unwrap_or_else(|| {
+            pg_sys::tuplestore_alloc_read_pointer(
+                tuplestore.as_ptr(),
+                pg_sys::EXEC_FLAG_REWIND as i32,
+            )
+        });
+        pg_sys::tuplestore_select_read_pointer(tuplestore.as_ptr(), readptr);
+        pg_sys::tuplestore_rescan(tuplestore.as_ptr());
+
+        PgTransitionTable {
+            tuplestore,
+            readptr,
+            slot: make_slot(tupdesc),
+            tupdesc,
+            free_readptr,
+        }
+    }
+
+    /// How many rows are in the table
+    pub fn len(&self) -> usize {
+        unsafe { pg_sys::tuplestore_tuple_count(self.tuplestore.as_ptr()) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the rows decoded into `T`, such as a `#[derive(PostgresComposite)]` struct whose
+    /// fields are named like the table's columns
+    pub fn rows<T: FromDatum>(self) -> impl Iterator<Item = T> + 'a {
+        self.map(|tuple| {
+            let typoid = tuple.type_oid();
+            unsafe { T::from_datum(tuple.into_composite_datum(), false, typoid) }
+                .expect("transition table row is NULL")
+        })
+    }
+}
+
+impl<'a> Iterator for PgTransitionTable<'a> {
+    type Item = PgHeapTuple;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            pg_sys::tuplestore_select_read_pointer(self.tuplestore.as_ptr(), self.readptr);
+            if !pg_sys::tuplestore_gettupleslot(self.tuplestore.as_ptr(), true, false, self.slot) {
+                return None;
+            }
+            Some(fetch_slot_tuple(self.slot, self.tupdesc))
+        }
+    }
+}
+
+impl<'a> Drop for PgTransitionTable<'a> {
+    fn drop(&mut self) {
+        unsafe { pg_sys::ExecDropSingleTupleTableSlot(self.slot) }
+        self.free_readptr.set(Some(self.readptr));
+    }
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+unsafe fn make_slot(tupdesc: pg_sys::TupleDesc) -> *mut pg_sys::TupleTableSlot {
+    pg_sys::MakeSingleTupleTableSlot(tupdesc)
+}
+
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+unsafe fn make_slot(tupdesc: pg_sys::TupleDesc) -> *mut pg_sys::TupleTableSlot {
+    pg_sys::MakeSingleTupleTableSlot(tupdesc, &pg_sys::TTSOpsMinimalTuple)
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+unsafe fn fetch_slot_tuple(
+    slot: *mut pg_sys::TupleTableSlot,
+    tupdesc: pg_sys::TupleDesc,
+) -> PgHeapTuple {
+    PgHeapTuple::from_heap_tuple(pg_sys::ExecFetchSlotTuple(slot), tupdesc)
+}
+
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+unsafe fn fetch_slot_tuple(
+    slot: *mut pg_sys::TupleTableSlot,
+    tupdesc: pg_sys::TupleDesc,
+) -> PgHeapTuple {
+    let mut should_free = false;
+    let tuple = pg_sys::ExecFetchSlotHeapTuple(slot, false, &mut should_free);
+    let copy = PgHeapTuple::from_heap_tuple(tuple, tupdesc);
+    if should_free {
+        pg_sys::heap_freetuple(tuple);
+    }
+    copy
+}
+
+/// Converts what a trigger function returns into what the trigger manager expects
+pub trait IntoTriggerResult {
+    /// ## Safety
+    ///
+    /// `fcinfo` must be the valid `FunctionCallInfo` of the trigger function call returning `self`
+    unsafe fn into_trigger_result(self, fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum;
+}
+
+/// The row the trigger fired for, unchanged, which is [`PgTrigger::new_row`] for a row-level
+/// `INSERT` or `UPDATE` trigger, and [`PgTrigger::old_row`] for a row-level `DELETE` trigger
+impl IntoTriggerResult for () {
+    unsafe fn into_trigger_result(self, fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+        let trigger = PgTrigger::from_fcinfo(fcinfo);
+        let trigdata = trigger.trigdata.as_ref();
+        // NULL for a statement-level trigger, whose result is ignored
+        match (trigger.level(), trigger.op()) {
+            (PgTriggerLevel::Row, PgTriggerOperation::Update) => {
+                trigdata.tg_newtuple as pg_sys::Datum
+            }
+            _ => trigdata.tg_trigtuple as pg_sys::Datum,
+        }
+    }
+}
+
+/// The row to use for the operation, which must have the same structure as the table's rows, like
+/// the row a PL/pgSQL trigger function returns
+impl IntoTriggerResult for PgHeapTuple {
+    unsafe fn into_trigger_result(self, fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+        let trigger = PgTrigger::from_fcinfo(fcinfo);
+        let reldesc = trigger
+            .trigdata
+            .as_ref()
+            .tg_relation
+            .as_ref()
+            .unwrap()
+            .rd_att;
+        let msg = CStr::from_bytes_with_nul(
+            b"returned row structure does not match the structure of the triggering table\0",
+        )
+        .expect("hardcoded error message failed");
+        let map =
+            pg_sys::convert_tuples_by_position(self.tupdesc().as_ptr(), reldesc, msg.as_ptr());
+        if map.is_null() {
+            return self.into_pg() as pg_sys::Datum;
+        }
+
+        let converted = convert_tuple(self.as_ptr(), map);
+        pg_sys::free_conversion_map(map);
+        converted as pg_sys::Datum
+    }
+}
+
+/// The row to use for the operation, or `None` to skip the operation for this row
+impl IntoTriggerResult for Option<PgHeapTuple> {
+    unsafe fn into_trigger_result(self, fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+        match self {
+            Some(tuple) => tuple.into_trigger_result(fcinfo),
+            // a NULL pointer, rather than an SQL NULL, which the trigger manager would reject
+            None => 0,
+        }
+    }
+}
+
+#[cfg(any(feature = "pg10", feature = "pg11"))]
+unsafe fn convert_tuple(
+    tuple: pg_sys::HeapTuple,
+    map: *mut pg_sys::TupleConversionMap,
+) -> pg_sys::HeapTuple {
+    pg_sys::do_convert_tuple(tuple, map)
+}
+
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+unsafe fn convert_tuple(
+    tuple: pg_sys::HeapTuple,
+    map: *mut pg_sys::TupleConversionMap,
+) -> pg_sys::HeapTuple {
+    pg_sys::execute_attr_map_tuple(tuple, map)
+}
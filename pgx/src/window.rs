@@ -0,0 +1,175 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Support for writing [window functions](https://www.postgresql.org/docs/current/functions-window.html)
+//!
+//! A window function is declared with `#[pg_extern(window)]` and takes a [`WindowObject`] argument,
+//! which gives it random access to the rows of the current partition and window frame:
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//!
+//! /// The value of `value` in the previous row of the partition, like `lag(value)`
+//! #[pg_extern(window)]
+//! fn previous_value(value: Option<i32>, window: WindowObject) -> Option<i32> {
+//!     window.arg_in_partition(0, -1, WindowSeek::Current, false).flatten()
+//! }
+//! ```
+//!
+//! Postgres doesn't pass the arguments of a window function like those of a regular function.
+//! pgx reads them from the current row, so `value` above is the current row's value.  The
+//! [`WindowObject`] argument itself doesn't appear in the function's SQL signature.
+//!
+//! Postgres doesn't skip a window function for `NULL` arguments, even if it's `STRICT`.  A
+//! `NULL` argument that isn't an `Option` makes the function return `NULL` for that row instead.
+
+use crate::{pg_sys, FromDatum};
+use std::ptr::NonNull;
+
+/// Where [`WindowObject::arg_in_partition`] and [`WindowObject::arg_in_frame`] count their
+/// relative position from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowSeek {
+    /// The current row
+    Current,
+    /// The first row of the partition or frame
+    Head,
+    /// The last row of the partition or frame
+    Tail,
+}
+
+impl WindowSeek {
+    fn value(self) -> i32 {
+        (match self {
+            WindowSeek::Current => pg_sys::WINDOW_SEEK_CURRENT,
+            WindowSeek::Head => pg_sys::WINDOW_SEEK_HEAD,
+            WindowSeek::Tail => pg_sys::WINDOW_SEEK_TAIL,
+        }) as i32
+    }
+}
+
+/// The window of a call to a window function, wrapping Postgres' `WindowObject`.
+///
+/// Row positions are zero-based positions within the current partition.
+pub struct WindowObject {
+    winobj: NonNull<pg_sys::WindowObjectData>,
+    fcinfo: pg_sys::FunctionCallInfo,
+}
+
+impl WindowObject {
+    /// The window of the window function call `fcinfo` belongs to
+    ///
+    /// ## Safety
+    ///
+    /// `fcinfo` must be a valid `FunctionCallInfo`.  Panics if the function wasn't called as a
+    /// window function.
+    pub unsafe fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        let context = fcinfo.as_ref().expect("fcinfo was null").context;
+        if !crate::is_a(context, pg_sys::NodeTag_T_WindowObjectData) {
+            panic!("function was not called as a window function");
+        }
+
+        WindowObject {
+            winobj: NonNull::new_unchecked(context as pg_sys::WindowObject),
+            fcinfo,
+        }
+    }
+
+    /// The position of the current row
+    pub fn current_position(&self) -> i64 {
+        unsafe { pg_sys::WinGetCurrentPosition(self.winobj.as_ptr()) }
+    }
+
+    /// How many rows are in the current partition
+    pub fn partition_row_count(&self) -> i64 {
+        unsafe { pg_sys::WinGetPartitionRowCount(self.winobj.as_ptr()) }
+    }
+
+    /// Tell Postgres that rows before `position` won't be read again, so it may free them
+    pub fn set_mark_position(&self, position: i64) {
+        unsafe { pg_sys::WinSetMarkPosition(self.winobj.as_ptr(), position) }
+    }
+
+    /// Are the rows at these two positions peers, as in equal according to the window's `ORDER BY`?
+    pub fn rows_are_peers(&self, position1: i64, position2: i64) -> bool {
+        unsafe { pg_sys::WinRowsArePeers(self.winobj.as_ptr(), position1, position2) }
+    }
+
+    /// The value of the zero-based argument `argno` in the current row
+    pub fn current_arg<T: FromDatum>(&self, argno: usize) -> Option<T> {
+        let mut isnull = false;
+        unsafe {
+            let datum =
+                pg_sys::WinGetFuncArgCurrent(self.winobj.as_ptr(), argno as i32, &mut isnull);
+            T::from_datum(datum, isnull, self.arg_type::<T>(argno))
+        }
+    }
+
+    /// The value of the zero-based argument `argno` in the row `relpos` rows from `seek` within the
+    /// partition, or `None` if there's no such row.
+    ///
+    /// With `set_mark`, rows before the one read are no longer needed, like with
+    /// [`WindowObject::set_mark_position`].
+    pub fn arg_in_partition<T: FromDatum>(
+        &self,
+        argno: usize,
+        relpos: i32,
+        seek: WindowSeek,
+        set_mark: bool,
+    ) -> Option<Option<T>> {
+        let mut isnull = false;
+        let mut isout = false;
+        unsafe {
+            let datum = pg_sys::WinGetFuncArgInPartition(
+                self.winobj.as_ptr(),
+                argno as i32,
+                relpos,
+                seek.value(),
+                set_mark,
+                &mut isnull,
+                &mut isout,
+            );
+            if isout {
+                None
+            } else {
+                Some(T::from_datum(datum, isnull, self.arg_type::<T>(argno)))
+            }
+        }
+    }
+
+    /// Like [`WindowObject::arg_in_partition`], but within the current row's window frame
+    pub fn arg_in_frame<T: FromDatum>(
+        &self,
+        argno: usize,
+        relpos: i32,
+        seek: WindowSeek,
+        set_mark: bool,
+    ) -> Option<Option<T>> {
+        let mut isnull = false;
+        let mut isout = false;
+        unsafe {
+            let datum = pg_sys::WinGetFuncArgInFrame(
+                self.winobj.as_ptr(),
+                argno as i32,
+                relpos,
+                seek.value(),
+                set_mark,
+                &mut isnull,
+                &mut isout,
+            );
+            if isout {
+                None
+            } else {
+                Some(T::from_datum(datum, isnull, self.arg_type::<T>(argno)))
+            }
+        }
+    }
+
+    unsafe fn arg_type<T: FromDatum>(&self, argno: usize) -> pg_sys::Oid {
+        if T::NEEDS_TYPID {
+            crate::get_getarg_type(self.fcinfo, argno)
+        } else {
+            pg_sys::InvalidOid
+        }
+    }
+}